@@ -1,24 +1,64 @@
+use crate::bss_in_band;
+use crate::connect::{connect_outcome, disconnect_result};
+use crate::dedup_bss;
+use crate::error::{response_error, Error, ENODEV, ENOENT, EOPNOTSUPP};
+use crate::event::EVENT_GROUPS;
+use crate::power::power_save_enabled;
+use crate::request::{self, Request};
+use crate::scan::scan_outcome;
+use crate::socket::{
+    event_group_ids, failed_interfaces, mcast_group_id, phy_interfaces, unresolved_group,
+};
 use crate::Bss;
+use crate::ChannelWidth;
+use crate::ConnectParams;
+use crate::CsaIes;
+use crate::FrequencyBand;
 use crate::Interface;
+use crate::InterfaceType;
+use crate::MeshParam;
+use crate::MonitorFlags;
 use crate::Nl80211Attr;
 use crate::Nl80211Cmd;
+use crate::RegDomain;
+use crate::ScanRequest;
+use crate::SchedScanRequest;
 use crate::Socket;
 use crate::Station;
-use crate::NL_80211_GENL_VERSION;
+use crate::Survey;
+use crate::TxPowerSetting;
+use crate::WifiEvent;
+use crate::Wiphy;
 
 use neli::consts::genl::{CtrlAttr, CtrlCmd};
-use neli::consts::{nl::GenlId, nl::NlmF, nl::NlmFFlags, nl::Nlmsg};
+use neli::consts::socket::NlFamily;
+use neli::consts::MAX_NL_LENGTH;
+use neli::consts::{nl::GenlId, nl::NlType, nl::Nlmsg};
 use neli::err::NlError;
-use neli::genl::{Genlmsghdr, Nlattr};
+use neli::genl::Genlmsghdr;
 use neli::nl::{NlPayload, Nlmsghdr};
-use neli::socket::tokio::NlSocket;
-use neli::types::GenlBuffer;
+use neli::socket::{tokio::NlSocket, NlSocketHandle};
+use neli::types::{Buffer, NlBuffer};
+use neli::{FromBytesWithInput, Size, ToBytes};
+
+use futures_core::Stream;
+use tokio::io::{AsyncRead, ReadBuf};
+
+use std::collections::VecDeque;
+use std::future::poll_fn;
+use std::io::Cursor;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
 
 /// A generic netlink socket to send commands and receive messages
 #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
 pub struct AsyncSocket {
     sock: NlSocket,
     family_id: u16,
+    validate_channels: bool,
+    events: Option<EventStream>,
+    seq: u32,
+    buf: Vec<u8>,
 }
 
 impl TryFrom<Socket> for AsyncSocket {
@@ -28,16 +68,33 @@ impl TryFrom<Socket> for AsyncSocket {
         Ok(Self {
             sock: NlSocket::new(from.sock)?,
             family_id: from.family_id,
+            validate_channels: from.validate_channels,
+            events: None,
+            seq: from.seq,
+            buf: Vec::new(),
         })
     }
 }
 
 impl AsyncSocket {
     /// Create a new nl80211 socket with netlink
-    pub fn connect() -> Result<Self, NlError<GenlId, Genlmsghdr<CtrlCmd, CtrlAttr>>> {
+    pub fn connect() -> Result<Self, Error> {
         Ok(Socket::connect()?.try_into()?)
     }
 
+    /// Close the socket, releasing its file descriptor
+    ///
+    /// This is what dropping the socket does as well, but at a well defined point. The socket is
+    /// consumed, so it can't be used afterwards.
+    pub fn close(self) {
+        drop(self.sock);
+    }
+
+    /// Returns the underlying generic netlink socket, see also `From<AsyncSocket>`
+    pub fn into_inner(self) -> NlSocket {
+        self.sock
+    }
+
     /// Get information for all your wifi interfaces
     ///
     /// # Example
@@ -54,37 +111,27 @@ impl AsyncSocket {
     /// #   Ok(())
     /// # };
     ///```
-    pub async fn get_interfaces_info(&mut self) -> Result<Vec<Interface>, NlError> {
-        let msghdr = Genlmsghdr::<Nl80211Cmd, Nl80211Attr>::new(
-            Nl80211Cmd::CmdGetInterface,
-            NL_80211_GENL_VERSION,
-            GenlBuffer::new(),
-        );
-
-        let nlhdr = {
-            let len = None;
-            let nl_type = self.family_id;
-            let flags = NlmFFlags::new(&[NlmF::Request, NlmF::Dump]);
-            let seq = None;
-            let pid = None;
-            let payload = NlPayload::Payload(msghdr);
-            Nlmsghdr::new(len, nl_type, flags, seq, pid, payload)
-        };
-
-        self.sock.send(&nlhdr).await?;
+    pub async fn get_interfaces_info(&mut self) -> Result<Vec<Interface>, Error> {
+        let seq = self.send(request::get_interfaces(self.family_id)).await?;
 
-        let mut buf = Vec::new();
         let mut interfaces = Vec::new();
 
         loop {
             let res = self
                 .sock
-                .recv::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(&mut buf)
+                .recv::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(&mut self.buf)
                 .await?;
             for response in res {
+                if response.nl_seq != seq {
+                    continue;
+                }
                 match response.nl_type {
                     Nlmsg::Noop => (),
-                    Nlmsg::Error => panic!("Error"),
+                    Nlmsg::Error => {
+                        if let Some(err) = response_error(response.nl_payload) {
+                            return Err(err);
+                        }
+                    }
                     Nlmsg::Done => return Ok(interfaces),
                     _ => {
                         let handle = response.nl_payload.get_payload().unwrap().get_attr_handle();
@@ -95,8 +142,42 @@ impl AsyncSocket {
         }
     }
 
+    /// Get information about a single interface by its index
+    ///
+    /// Unlike `get_interfaces_info` this doesn't dump all interfaces but has the kernel look up
+    /// just this one. Returns `None` if there is no interface with this index (`ENODEV`).
+    pub async fn get_interface_info(
+        &mut self,
+        interface_index: i32,
+    ) -> Result<Option<Interface>, Error> {
+        let seq = self
+            .send(request::get_interface(self.family_id, interface_index)?)
+            .await?;
+
+        loop {
+            let res = self
+                .sock
+                .recv::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(&mut self.buf)
+                .await?;
+            for response in res {
+                if response.nl_seq != seq {
+                    continue;
+                }
+                match response.nl_payload {
+                    NlPayload::Err(err) if err.error == -ENODEV => return Ok(None),
+                    NlPayload::Err(err) => return Err(Error::from_errno(-err.error)),
+                    NlPayload::Payload(msg) => return Ok(Some(msg.get_attr_handle().try_into()?)),
+                    _ => (),
+                }
+            }
+        }
+    }
+
     /// Get access point information for a specific interface
     ///
+    /// Monitor interfaces, which have no stations, and interfaces whose driver doesn't support
+    /// station queries (`EOPNOTSUPP`) report an empty station instead of an error.
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -117,46 +198,118 @@ impl AsyncSocket {
     /// #   Ok(())
     /// # }
     ///```
-    pub async fn get_station_info(&mut self, interface_index: i32) -> Result<Station, NlError> {
-        let msghdr = Genlmsghdr::<Nl80211Cmd, Nl80211Attr>::new(
-            Nl80211Cmd::CmdGetStation,
-            NL_80211_GENL_VERSION,
-            {
-                let mut attrs = GenlBuffer::new();
-                attrs.push(
-                    Nlattr::new(false, false, Nl80211Attr::AttrIfindex, interface_index).unwrap(),
-                );
-                attrs
-            },
-        );
+    pub async fn get_station_info(&mut self, interface_index: i32) -> Result<Station, Error> {
+        let mut stations = self.get_all_station_info(interface_index).await?;
+        Ok(stations.pop().unwrap_or_default())
+    }
 
-        let nlhdr = {
-            let len = None;
-            let nl_type = self.family_id;
-            let flags = NlmFFlags::new(&[NlmF::Request, NlmF::Dump]);
-            let seq = None;
-            let pid = None;
-            let payload = NlPayload::Payload(msghdr);
-            Nlmsghdr::new(len, nl_type, flags, seq, pid, payload)
-        };
+    /// Get all stations of a specific interface
+    ///
+    /// An interface in station mode usually has a single station, the access point it is
+    /// connected to, while access point and mesh interfaces report one station per peer.
+    /// Monitor interfaces, which have no stations, and interfaces whose driver doesn't support
+    /// station queries (`EOPNOTSUPP`) report no stations instead of an error.
+    pub async fn get_all_station_info(
+        &mut self,
+        interface_index: i32,
+    ) -> Result<Vec<Station>, Error> {
+        let seq = self
+            .send(request::get_stations(self.family_id, interface_index)?)
+            .await?;
 
-        self.sock.send(&nlhdr).await?;
+        let mut retval = Vec::new();
 
-        let mut buf = Vec::new();
-        let mut retval = None;
+        loop {
+            let res = self
+                .sock
+                .recv::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(&mut self.buf)
+                .await?;
+            for response in res {
+                if response.nl_seq != seq {
+                    continue;
+                }
+                match response.nl_type {
+                    Nlmsg::Noop => (),
+                    Nlmsg::Error => match response.nl_payload {
+                        NlPayload::Err(err) if err.error == -EOPNOTSUPP => return Ok(Vec::new()),
+                        payload => {
+                            if let Some(err) = response_error(payload) {
+                                return Err(err);
+                            }
+                        }
+                    },
+                    Nlmsg::Done => return Ok(retval),
+                    _ => {
+                        if let Some(payload) = response.nl_payload.get_payload() {
+                            retval.push(payload.get_attr_handle().try_into()?);
+                        }
+                    }
+                };
+            }
+        }
+    }
+
+    /// Get information about a single station of a specific interface by its MAC address
+    ///
+    /// Unlike `get_station_info` this doesn't dump all stations but has the kernel look up just
+    /// this one. Returns `None` if no station with this address is associated (`ENOENT`).
+    pub async fn get_station_info_for(
+        &mut self,
+        interface_index: i32,
+        mac: &[u8],
+    ) -> Result<Option<Station>, Error> {
+        let request = request::get_station(self.family_id, interface_index, mac)?;
+        let seq = self.send(request).await?;
+
+        loop {
+            let res = self
+                .sock
+                .recv::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(&mut self.buf)
+                .await?;
+            for response in res {
+                if response.nl_seq != seq {
+                    continue;
+                }
+                match response.nl_payload {
+                    NlPayload::Err(err) if err.error == -ENOENT => return Ok(None),
+                    NlPayload::Err(err) => return Err(Error::from_errno(-err.error)),
+                    NlPayload::Payload(msg) => return Ok(Some(msg.get_attr_handle().try_into()?)),
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    /// Get all BSSes the kernel has cached from scans on a specific interface
+    ///
+    /// Every BSS in the dump is returned in the order the kernel reports them. The cache is only
+    /// filled by scans, see `trigger_scan`.
+    pub async fn get_bss_info(&mut self, interface_index: i32) -> Result<Vec<Bss>, Error> {
+        let seq = self
+            .send(request::get_scan(self.family_id, interface_index)?)
+            .await?;
+
+        let mut retval = Vec::new();
 
         loop {
             let res = self
                 .sock
-                .recv::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(&mut buf)
+                .recv::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(&mut self.buf)
                 .await?;
             for response in res {
+                if response.nl_seq != seq {
+                    continue;
+                }
                 match response.nl_type {
                     Nlmsg::Noop => (),
-                    Nlmsg::Error => panic!("Error"),
-                    Nlmsg::Done => return Ok(retval.unwrap_or_default()),
+                    Nlmsg::Error => {
+                        if let Some(err) = response_error(response.nl_payload) {
+                            return Err(err);
+                        }
+                    }
+                    Nlmsg::Done => return Ok(retval),
                     _ => {
-                        retval = Some(
+                        retval.push(
                             response
                                 .nl_payload
                                 .get_payload()
@@ -165,48 +318,37 @@ impl AsyncSocket {
                                 .try_into()?,
                         );
                     }
-                };
+                }
             }
         }
     }
 
-    pub async fn get_bss_info(&mut self, interface_index: i32) -> Result<Vec<Bss>, NlError> {
-        let msghdr = Genlmsghdr::<Nl80211Cmd, Nl80211Attr>::new(
-            Nl80211Cmd::CmdGetScan,
-            NL_80211_GENL_VERSION,
-            {
-                let mut attrs = GenlBuffer::new();
-                attrs.push(
-                    Nlattr::new(false, false, Nl80211Attr::AttrIfindex, interface_index).unwrap(),
-                );
-                attrs
-            },
-        );
-
-        let nlhdr = {
-            let len = None;
-            let nl_type = self.family_id;
-            let flags = NlmFFlags::new(&[NlmF::Request, NlmF::Dump]);
-            let seq = None;
-            let pid = None;
-            let payload = NlPayload::Payload(msghdr);
-            Nlmsghdr::new(len, nl_type, flags, seq, pid, payload)
-        };
-
-        self.sock.send(&nlhdr).await?;
+    /// Get the channel surveys of a specific interface, one per channel the driver reports
+    ///
+    /// Many drivers only survey the channel in use unless a scan visited the others.
+    pub async fn get_survey_info(&mut self, interface_index: i32) -> Result<Vec<Survey>, Error> {
+        let seq = self
+            .send(request::get_survey(self.family_id, interface_index)?)
+            .await?;
 
-        let mut buf = Vec::new();
         let mut retval = Vec::new();
 
         loop {
             let res = self
                 .sock
-                .recv::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(&mut buf)
+                .recv::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(&mut self.buf)
                 .await?;
             for response in res {
+                if response.nl_seq != seq {
+                    continue;
+                }
                 match response.nl_type {
                     Nlmsg::Noop => (),
-                    Nlmsg::Error => panic!("Error"),
+                    Nlmsg::Error => {
+                        if let Some(err) = response_error(response.nl_payload) {
+                            return Err(err);
+                        }
+                    }
                     Nlmsg::Done => return Ok(retval),
                     _ => {
                         retval.push(
@@ -222,6 +364,789 @@ impl AsyncSocket {
             }
         }
     }
+
+    /// Get information about all wireless physical devices (phys) and their capabilities
+    pub async fn get_wiphy_info(&mut self) -> Result<Vec<Wiphy>, Error> {
+        let seq = self.send(request::get_wiphys(self.family_id)?).await?;
+
+        let mut retval: Vec<Wiphy> = Vec::new();
+
+        loop {
+            let res = self
+                .sock
+                .recv::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(&mut self.buf)
+                .await?;
+            for response in res {
+                if response.nl_seq != seq {
+                    continue;
+                }
+                match response.nl_type {
+                    Nlmsg::Noop => (),
+                    Nlmsg::Error => {
+                        if let Some(err) = response_error(response.nl_payload) {
+                            return Err(err);
+                        }
+                    }
+                    Nlmsg::Done => return Ok(retval),
+                    _ => {
+                        // A split dump describes every wiphy in several consecutive messages
+                        let wiphy: Wiphy = response
+                            .nl_payload
+                            .get_payload()
+                            .unwrap()
+                            .get_attr_handle()
+                            .try_into()?;
+                        match retval.last_mut() {
+                            Some(last) if last.index == wiphy.index => last.merge(wiphy),
+                            _ => retval.push(wiphy),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Get the current regulatory domain
+    pub async fn get_reg_domain(&mut self) -> Result<RegDomain, Error> {
+        self.request_reg_domain(None).await
+    }
+
+    /// Request the kernel to use the regulatory domain of a country
+    ///
+    /// `alpha2` is the ISO 3166 country code of two ASCII letters, like `"DE"`, anything else is
+    /// rejected before it is sent. The kernel applies the domain asynchronously, a change is
+    /// reported as [`WifiEvent::RegChange`](crate::WifiEvent::RegChange). Without the
+    /// `CAP_NET_ADMIN` capability this fails with `Error::KernelErrno(EPERM)`.
+    pub async fn set_reg_domain(&mut self, alpha2: &str) -> Result<(), Error> {
+        let request = request::set_reg(self.family_id, alpha2)?;
+        self.send_with_ack(request).await
+    }
+
+    /// Get the regulatory domain a wiphy operates under
+    ///
+    /// Wiphys whose driver manages the regulatory domain itself may operate under a different
+    /// domain than the global one, for all others the kernel reports the global domain.
+    pub async fn get_wiphy_reg_domain(&mut self, phy: u32) -> Result<RegDomain, Error> {
+        self.request_reg_domain(Some(phy)).await
+    }
+
+    async fn request_reg_domain(&mut self, phy: Option<u32>) -> Result<RegDomain, Error> {
+        let seq = self.send(request::get_reg(self.family_id, phy)?).await?;
+
+        let reg_domain = loop {
+            let res = self
+                .sock
+                .recv::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(&mut self.buf)
+                .await?;
+            let response = res
+                .into_iter()
+                .find(|response| response.nl_seq == seq && response.nl_type != Nlmsg::Noop);
+            if let Some(response) = response {
+                match response.nl_type {
+                    Nlmsg::Error => {
+                        if let Some(err) = response_error(response.nl_payload) {
+                            return Err(err);
+                        }
+                    }
+                    Nlmsg::Done => break RegDomain::default(),
+                    _ => {
+                        break response
+                            .nl_payload
+                            .get_payload()
+                            .unwrap()
+                            .get_attr_handle()
+                            .try_into()?
+                    }
+                }
+            }
+        };
+
+        Ok(reg_domain)
+    }
+
+    /// Check channels against the regulatory domain in `set_channel`, off by default
+    ///
+    /// When enabled, [`set_channel`](AsyncSocket::set_channel) fetches the current regulatory
+    /// domain of the interface's wiphy and rejects channels it doesn't permit before they are
+    /// sent to the kernel, see [`RegDomain::permits_channel`]. This costs a few extra requests
+    /// per call.
+    pub fn validate_channels(&mut self, validate: bool) {
+        self.validate_channels = validate;
+    }
+
+    /// Get access point information for a specific interface, with one entry per BSSID
+    ///
+    /// See [`dedup_bss`] for how duplicate entries are resolved.
+    pub async fn get_bss_list_deduped(&mut self, interface_index: i32) -> Result<Vec<Bss>, Error> {
+        Ok(dedup_bss(self.get_bss_info(interface_index).await?))
+    }
+
+    /// Get access point information for a specific interface, only of the BSSes in `band`
+    ///
+    /// See [`bss_in_band`].
+    pub async fn get_bss_list_for_band(
+        &mut self,
+        interface_index: i32,
+        band: FrequencyBand,
+    ) -> Result<Vec<Bss>, Error> {
+        Ok(bss_in_band(self.get_bss_info(interface_index).await?, band))
+    }
+
+    /// Connect to the network `ssid` on a specific interface
+    ///
+    /// This only initiates the connection, the outcome is reported asynchronously by the
+    /// kernel, see `connect_and_wait` for the AP's status code if it rejects the connection. An
+    /// empty [`ConnectParams`] connects to an open network.
+    pub async fn connect_network(
+        &mut self,
+        interface_index: i32,
+        ssid: &[u8],
+        params: &ConnectParams,
+    ) -> Result<(), Error> {
+        if params.needs_check() {
+            if let Some(wiphy) = self.interface_wiphy(interface_index).await? {
+                params.check(&wiphy)?;
+            }
+        }
+        let request = request::connect(self.family_id, interface_index, ssid, params)?;
+        self.send_with_ack(request).await
+    }
+
+    /// Connect to the network `ssid` on a specific interface and wait for the outcome
+    ///
+    /// The outcome is received on a separate socket, which joins the "mlme" multicast group
+    /// before connecting so the event can't be missed. If the AP rejects the connection or
+    /// doesn't answer, this fails with [`Error::ConnectFailed`] carrying the AP's status code.
+    pub async fn connect_and_wait(
+        &mut self,
+        interface_index: i32,
+        ssid: &[u8],
+        params: &ConnectParams,
+    ) -> Result<(), Error> {
+        let mut events = self.events_socket(&["mlme"]).await?;
+
+        self.connect_network(interface_index, ssid, params).await?;
+        wait_for_event(&mut events, |event| connect_outcome(event, interface_index)).await
+    }
+
+    /// Disconnect a specific interface from its network
+    ///
+    /// Disconnecting an interface which isn't connected does nothing. The kernel reports the
+    /// disconnection as an event of the "mlme" group.
+    pub async fn disconnect(&mut self, interface_index: i32) -> Result<(), Error> {
+        let request = request::disconnect(self.family_id, interface_index)?;
+        disconnect_result(self.send_with_ack(request).await)
+    }
+
+    /// Deauthenticate a specific interface from the BSS `bssid` with the given reason code
+    ///
+    /// This is the lower level counterpart of `disconnect`, for interfaces which authenticate and
+    /// associate step by step. Reason code 3 means the station is leaving.
+    pub async fn deauthenticate(
+        &mut self,
+        interface_index: i32,
+        bssid: &[u8],
+        reason: u16,
+    ) -> Result<(), Error> {
+        let request = request::deauthenticate(self.family_id, interface_index, bssid, reason)?;
+        self.send_with_ack(request).await
+    }
+
+    /// Trigger a scan on a specific interface
+    ///
+    /// This only starts the scan, the results can be fetched once the kernel has completed it.
+    /// Options the wiphy of the interface doesn't support, like a dwell time, fail with
+    /// [`Error::Unsupported`] before anything is sent.
+    pub async fn trigger_scan(
+        &mut self,
+        interface_index: i32,
+        request: &ScanRequest,
+    ) -> Result<(), Error> {
+        if request.needs_check() {
+            if let Some(wiphy) = self.interface_wiphy(interface_index).await? {
+                request.check(&wiphy)?;
+            }
+        }
+        let request = request::trigger_scan(self.family_id, interface_index, request)?;
+        self.send_with_ack(request).await
+    }
+
+    /// Scan on a specific interface and return the fresh scan results
+    ///
+    /// The completion of the scan is received on a separate socket, which joins the "scan"
+    /// multicast group before the scan is triggered so the event can't be missed. Scans aborted
+    /// by the kernel fail with [`Error::ScanAborted`], see also [`Error::is_retriable`].
+    pub async fn scan_now(
+        &mut self,
+        interface_index: i32,
+        request: &ScanRequest,
+    ) -> Result<Vec<Bss>, Error> {
+        let mut events = self.events_socket(&["scan"]).await?;
+
+        self.trigger_scan(interface_index, request).await?;
+        wait_for_event(&mut events, |event| scan_outcome(event, interface_index)).await?;
+        self.get_bss_info(interface_index).await
+    }
+
+    /// Subscribe to all nl80211 events, which can then be received with `next_event`
+    ///
+    /// This joins the "config", "scan", "mlme", "regulatory" and "vendor" (if offered) multicast
+    /// groups on a separate socket and returns the joined group ids. Subscribing again replaces
+    /// the previous subscription.
+    ///
+    /// Beware that this can be a lot of events: every scan of any process on any interface, every
+    /// interface change and every vendor event of the drivers. The kernel queues them in the
+    /// socket's receive buffer until `next_event` is called and drops events once it is full, so
+    /// events should be received continuously.
+    pub async fn subscribe_all(&mut self) -> Result<Vec<u32>, Error> {
+        let ids = event_group_ids(&self.resolve_mcast_groups(&EVENT_GROUPS).await?)?;
+        self.events = Some(EventStream::new(events_socket(&ids)?));
+        Ok(ids)
+    }
+
+    /// Wait for the next event of the groups joined with `subscribe_all`
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use neli_wifi::{AsyncSocket, WifiEvent};
+    /// # use std::error::Error;
+    ///
+    /// # async fn test() -> Result<(), Box<dyn Error>> {
+    ///     let mut socket = AsyncSocket::connect()?;
+    ///     socket.subscribe_all().await?;
+    ///     loop {
+    ///         if let WifiEvent::Disconnect { interface_index, .. } = socket.next_event().await? {
+    ///             println!("{} disconnected", interface_index);
+    ///         }
+    ///     }
+    /// # }
+    /// ```
+    pub async fn next_event(&mut self) -> Result<WifiEvent, Error> {
+        let events = self
+            .events
+            .as_mut()
+            .ok_or_else(|| Error::Netlink(NlError::new("Not subscribed to any events")))?;
+        poll_fn(|cx| Pin::new(&mut *events).poll_next(cx))
+            .await
+            .unwrap_or_else(|| Err(Error::Netlink(NlError::new("Event socket closed"))))
+    }
+
+    /// Receive the events of all nl80211 multicast groups as a [`Stream`]
+    ///
+    /// The stream takes over the subscription of `subscribe_all`, subscribing first if there is
+    /// none, so `next_event` fails afterwards until `subscribe_all` is called again. The events
+    /// are queued by the kernel as well and should be received continuously.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use neli_wifi::{AsyncSocket, WifiEvent};
+    /// # use std::error::Error;
+    /// # use std::future::poll_fn;
+    /// # use std::pin::Pin;
+    /// # use futures_core::Stream;
+    ///
+    /// # async fn test() -> Result<(), Box<dyn Error>> {
+    ///     let mut events = AsyncSocket::connect()?.events().await?;
+    ///     while let Some(event) = poll_fn(|cx| Pin::new(&mut events).poll_next(cx)).await {
+    ///         if let WifiEvent::ScanResults { interface_index } = event? {
+    ///             println!("New scan results on {}", interface_index);
+    ///         }
+    ///     }
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub async fn events(&mut self) -> Result<EventStream, Error> {
+        if self.events.is_none() {
+            self.subscribe_all().await?;
+        }
+        Ok(self.events.take().unwrap())
+    }
+
+    /// Start a scheduled scan on a specific interface
+    ///
+    /// The kernel scans periodically until the scan is stopped with `stop_sched_scan` and
+    /// reports matching results with `CmdSchedScanResults` on the "scan" multicast group.
+    pub async fn start_sched_scan(
+        &mut self,
+        interface_index: i32,
+        request: &SchedScanRequest,
+    ) -> Result<(), Error> {
+        let request = request::start_sched_scan(self.family_id, interface_index, request)?;
+        self.send_with_ack(request).await
+    }
+
+    /// Stop the scheduled scan running on a specific interface
+    pub async fn stop_sched_scan(&mut self, interface_index: i32) -> Result<(), Error> {
+        let request = request::stop_sched_scan(self.family_id, interface_index)?;
+        self.send_with_ack(request).await
+    }
+
+    /// Set the operating channel of a phy
+    ///
+    /// This tunes the phy itself rather than one of its interfaces, which is what monitor
+    /// interfaces without a channel of their own follow. `phy` is the wiphy index as reported in
+    /// [`Interface::phy`](crate::Interface::phy), the center frequency of wide channels is derived
+    /// with [`center_freq`](crate::center_freq).
+    pub async fn set_phy_channel(
+        &mut self,
+        phy: u32,
+        freq: u32,
+        width: ChannelWidth,
+    ) -> Result<(), Error> {
+        let request = request::set_phy_channel(self.family_id, phy, freq, width)?;
+        self.send_with_ack(request).await
+    }
+
+    /// Set the operating channel of a specific interface
+    ///
+    /// If enabled with [`validate_channels`](AsyncSocket::validate_channels), channels the current
+    /// regulatory domain of the interface's wiphy doesn't permit are rejected before the channel
+    /// is set. The domain is fetched again for every call, as it changes with the country and
+    /// may be managed by the driver for some wiphys.
+    /// The center frequency of wide channels is derived with [`center_freq`](crate::center_freq).
+    pub async fn set_channel(
+        &mut self,
+        interface_index: i32,
+        freq: u32,
+        width: ChannelWidth,
+    ) -> Result<(), Error> {
+        let reg_domain = if self.validate_channels {
+            self.interface_reg_domain(interface_index).await?
+        } else {
+            None
+        };
+        let request = request::set_channel(
+            self.family_id,
+            interface_index,
+            freq,
+            width,
+            reg_domain.as_ref(),
+        )?;
+        self.send_with_ack(request).await
+    }
+
+    /// The wiphy of an interface, `None` if the interface doesn't exist
+    async fn interface_wiphy(&mut self, interface_index: i32) -> Result<Option<Wiphy>, Error> {
+        let interface = self.get_interface_info(interface_index).await?;
+        match interface.and_then(|interface| interface.phy) {
+            Some(phy) => Ok(self
+                .get_wiphy_info()
+                .await?
+                .into_iter()
+                .find(|wiphy| wiphy.index == Some(phy))),
+            None => Ok(None),
+        }
+    }
+
+    /// The current regulatory domain of the wiphy of an interface, `None` if the interface
+    /// doesn't exist
+    async fn interface_reg_domain(
+        &mut self,
+        interface_index: i32,
+    ) -> Result<Option<RegDomain>, Error> {
+        let interface = self.get_interface_info(interface_index).await?;
+        match interface.and_then(|interface| interface.phy) {
+            Some(phy) => Ok(Some(self.get_wiphy_reg_domain(phy).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Set the transmit power of a specific interface
+    ///
+    /// Power is configured per interface, but drivers that only support a single power level
+    /// per phy apply it to all interfaces of the phy. See [`TxPowerSetting`] for the units.
+    pub async fn set_tx_power(
+        &mut self,
+        interface_index: i32,
+        setting: TxPowerSetting,
+    ) -> Result<(), Error> {
+        let request = request::set_tx_power(self.family_id, interface_index, setting)?;
+        self.send_with_ack(request).await
+    }
+
+    /// Change the type of a specific interface, e.g. to [`InterfaceType::Monitor`] for capturing
+    ///
+    /// Most drivers require the interface to be down for this, otherwise the kernel rejects the
+    /// change with `Error::KernelErrno(EBUSY)`.
+    pub async fn set_interface_type(
+        &mut self,
+        interface_index: i32,
+        iftype: InterfaceType,
+    ) -> Result<(), Error> {
+        let request = request::set_interface_type(self.family_id, interface_index, iftype)?;
+        self.send_with_ack(request).await
+    }
+
+    /// Set which frames a specific monitor interface passes on
+    ///
+    /// The flags replace the interface's current ones. `cook` and `active` can't be changed while
+    /// the interface is up (`EBUSY`), and `active` requires driver support (`EOPNOTSUPP`).
+    pub async fn set_monitor_flags(
+        &mut self,
+        interface_index: i32,
+        flags: MonitorFlags,
+    ) -> Result<(), Error> {
+        let request = request::set_monitor_flags(self.family_id, interface_index, flags)?;
+        self.send_with_ack(request).await
+    }
+
+    /// Rename a specific interface
+    ///
+    /// `name` must be a valid interface name of at most 15 bytes without slashes, colons or
+    /// whitespace, otherwise the request is rejected before it is sent.
+    pub async fn set_interface_name(
+        &mut self,
+        interface_index: i32,
+        name: &str,
+    ) -> Result<(), Error> {
+        let request = request::set_interface_name(self.family_id, interface_index, name)?;
+        self.send_with_ack(request).await
+    }
+
+    /// Create a new interface `name` of type `iftype` on a wiphy and return it
+    ///
+    /// Fails without contacting the kernel if the wiphy does not support `iftype`, see
+    /// [`Wiphy::supports_iftype`]. The kernel answers with the new interface, including its
+    /// index, so it doesn't have to be looked up with `get_interfaces_info`.
+    pub async fn create_interface(
+        &mut self,
+        wiphy: &Wiphy,
+        name: &str,
+        iftype: InterfaceType,
+    ) -> Result<Interface, Error> {
+        let request = request::create_interface(self.family_id, wiphy, name, iftype)?;
+        self.send_new_interface(request).await
+    }
+
+    /// Create a new monitor interface `name` on a phy with the given flags and return it
+    ///
+    /// Unlike `create_interface` followed by `set_monitor_flags`, the interface never exists
+    /// with other flags. `phy` is the wiphy index as reported in
+    /// [`Interface::phy`](crate::Interface::phy).
+    pub async fn create_monitor(
+        &mut self,
+        phy: u32,
+        name: &str,
+        flags: MonitorFlags,
+    ) -> Result<Interface, Error> {
+        let request = request::create_monitor(self.family_id, phy, name, flags)?;
+        self.send_new_interface(request).await
+    }
+
+    /// Delete a specific interface, usually one created with `create_interface`
+    ///
+    /// Deleting an interface the driver can't remove, like the primary interface of many wiphys,
+    /// fails with the kernel's error, usually [`Error::Unsupported`]. An index without interface
+    /// fails with `KernelErrno(ENODEV)`, whose [`std::io::ErrorKind`] is `NotFound`.
+    pub async fn delete_interface(&mut self, interface_index: i32) -> Result<(), Error> {
+        let request = request::delete_interface(self.family_id, interface_index)?;
+        self.send_with_ack(request).await
+    }
+
+    /// Announce a switch of an interface to another channel and perform it after `count` beacons
+    ///
+    /// APs announce the switch with the beacons in `csa_ies`, `block_tx` asks the associated
+    /// stations to stop transmitting until the switch. With a `count` of 0 the channel is
+    /// switched immediately.
+    pub async fn channel_switch(
+        &mut self,
+        interface_index: i32,
+        freq: u32,
+        width: ChannelWidth,
+        count: u8,
+        block_tx: bool,
+        csa_ies: &CsaIes,
+    ) -> Result<(), Error> {
+        let request = request::channel_switch(
+            self.family_id,
+            interface_index,
+            freq,
+            width,
+            count,
+            block_tx,
+            csa_ies,
+        )?;
+        self.send_with_ack(request).await
+    }
+
+    /// Whether power save is enabled on a specific interface
+    pub async fn get_power_save(&mut self, interface_index: i32) -> Result<bool, Error> {
+        let seq = self
+            .send(request::get_power_save(self.family_id, interface_index)?)
+            .await?;
+
+        loop {
+            let res = self
+                .sock
+                .recv::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(&mut self.buf)
+                .await?;
+            for response in res {
+                if response.nl_seq != seq {
+                    continue;
+                }
+                match response.nl_payload {
+                    NlPayload::Err(err) => return Err(Error::from_errno(-err.error)),
+                    NlPayload::Payload(reply) => return Ok(power_save_enabled(&reply)?),
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    /// Enable or disable power save of a specific interface
+    ///
+    /// Without the `CAP_NET_ADMIN` capability this fails with `Error::KernelErrno(EPERM)`.
+    pub async fn set_power_save(
+        &mut self,
+        interface_index: i32,
+        enabled: bool,
+    ) -> Result<(), Error> {
+        let request = request::set_power_save(self.family_id, interface_index, enabled)?;
+        self.send_with_ack(request).await
+    }
+
+    /// Enable or disable power save of all interfaces of a phy
+    ///
+    /// The setting is applied to every interface of the phy, even if it fails for some of them,
+    /// and is kept on the interfaces where it succeeded. Returns the interfaces where it failed
+    /// with their errors, so an empty list means power save is set on all interfaces. Only
+    /// failing to list the interfaces is an error.
+    pub async fn set_power_save_all(
+        &mut self,
+        phy: u32,
+        enabled: bool,
+    ) -> Result<Vec<(i32, Error)>, Error> {
+        let interfaces = self.get_interfaces_info().await?;
+        let mut results = Vec::new();
+        for index in phy_interfaces(&interfaces, phy) {
+            results.push((index, self.set_power_save(index, enabled).await));
+        }
+        Ok(failed_interfaces(results))
+    }
+
+    /// Hand the keys for GTK rekeying to the driver, e.g. before suspending with WoWLAN
+    ///
+    /// `kek` and `kck` are the key encryption and confirmation keys of the PTK, `replay_ctr` the
+    /// EAPOL-Key replay counter. The KEK must be 16 or 32 bytes, the KCK 16, 24 or 32 bytes and
+    /// the replay counter 8 bytes, other lengths are rejected before the request is sent.
+    pub async fn set_rekey_data(
+        &mut self,
+        interface_index: i32,
+        kek: &[u8],
+        kck: &[u8],
+        replay_ctr: &[u8],
+    ) -> Result<(), Error> {
+        let request =
+            request::set_rekey_data(self.family_id, interface_index, kek, kck, replay_ctr)?;
+        self.send_with_ack(request).await
+    }
+
+    /// Set a single parameter of a mesh interface
+    ///
+    /// `value` is rejected without contacting the kernel if it doesn't fit the parameter.
+    pub async fn set_mesh_param(
+        &mut self,
+        interface_index: i32,
+        param: MeshParam,
+        value: u32,
+    ) -> Result<(), Error> {
+        let request = request::set_mesh_param(self.family_id, interface_index, param, value)?;
+        self.send_with_ack(request).await
+    }
+
+    /// Send a request numbered with the next sequence number of the socket, which is returned
+    ///
+    /// Responses carrying another number are leftovers of earlier requests and are skipped.
+    async fn send<T, P>(&mut self, mut request: Nlmsghdr<T, P>) -> Result<u32, Error>
+    where
+        T: NlType,
+        P: Size + ToBytes,
+    {
+        // Events of multicast groups are numbered 0
+        self.seq = self.seq.checked_add(1).unwrap_or(1);
+        request.nl_seq = self.seq;
+        self.sock.send(&request).await?;
+        Ok(self.seq)
+    }
+
+    /// Resolve the ids of several named multicast groups, `None` for groups nl80211 doesn't offer
+    async fn resolve_mcast_groups(&mut self, names: &[&str]) -> Result<Vec<Option<u32>>, Error> {
+        let seq = self.send(request::get_family()?).await?;
+
+        let mut retval = vec![None; names.len()];
+        loop {
+            let res = self
+                .sock
+                .recv::<GenlId, Genlmsghdr<CtrlCmd, CtrlAttr>>(&mut self.buf)
+                .await?;
+            for response in res {
+                if response.nl_seq != seq {
+                    continue;
+                }
+                match response.nl_payload {
+                    NlPayload::Err(err) => return Err(Error::from_errno(-err.error)),
+                    NlPayload::Payload(family) => {
+                        for (id, name) in retval.iter_mut().zip(names) {
+                            *id = id.or_else(|| mcast_group_id(&family, name));
+                        }
+                        return Ok(retval);
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    /// Create a separate socket receiving the events of the named multicast groups
+    async fn events_socket(&mut self, names: &[&str]) -> Result<NlSocket, Error> {
+        let resolved = self.resolve_mcast_groups(names).await?;
+        let mut ids = Vec::new();
+        for (id, name) in resolved.into_iter().zip(names) {
+            ids.push(id.ok_or_else(|| unresolved_group(name))?);
+        }
+        events_socket(&ids)
+    }
+
+    /// Send a request and wait for the kernel to acknowledge it
+    async fn send_with_ack(&mut self, request: Request) -> Result<(), Error> {
+        let seq = self.send(request).await?;
+
+        loop {
+            let res = self.sock.recv::<u16, Buffer>(&mut self.buf).await?;
+            for response in res {
+                if response.nl_seq != seq {
+                    continue;
+                }
+                match response.nl_payload {
+                    NlPayload::Ack(_) => return Ok(()),
+                    NlPayload::Err(err) => return Err(Error::from_errno(-err.error)),
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    /// Send a request creating an interface and return the interface the kernel answers with
+    async fn send_new_interface(&mut self, request: Request) -> Result<Interface, Error> {
+        let seq = self.send(request).await?;
+
+        let mut retval = None;
+
+        loop {
+            let res = self
+                .sock
+                .recv::<u16, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(&mut self.buf)
+                .await?;
+            for response in res {
+                if response.nl_seq != seq {
+                    continue;
+                }
+                match response.nl_payload {
+                    NlPayload::Ack(_) => {
+                        return retval
+                            .ok_or_else(|| Error::Netlink(NlError::new("No interface received")))
+                    }
+                    NlPayload::Err(err) => return Err(Error::from_errno(-err.error)),
+                    NlPayload::Payload(interface)
+                        if retval.is_none() && interface.cmd == Nl80211Cmd::CmdNewInterface =>
+                    {
+                        retval = Some(interface.get_attr_handle().try_into()?);
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+/// Create a socket which is a member of the multicast groups `ids`
+///
+/// Connecting and joining are single syscalls which don't block, so this needs no await.
+fn events_socket(ids: &[u32]) -> Result<NlSocket, Error> {
+    let events = NlSocketHandle::connect(NlFamily::Generic, None, &[])?;
+    events.add_mcast_membership(ids)?;
+    Ok(NlSocket::new(events)?)
+}
+
+/// A [`Stream`] of the events of the multicast groups joined with `subscribe_all`
+///
+/// Created with [`AsyncSocket::events`]. It ends only if the socket is closed.
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub struct EventStream {
+    sock: NlSocket,
+    pending: VecDeque<Genlmsghdr<Nl80211Cmd, Nl80211Attr>>,
+    buf: Vec<u8>,
+}
+
+impl EventStream {
+    fn new(sock: NlSocket) -> Self {
+        Self {
+            sock,
+            pending: VecDeque::new(),
+            buf: Vec::new(),
+        }
+    }
+}
+
+impl Stream for EventStream {
+    type Item = Result<WifiEvent, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if let Some(event) = this.pending.pop_front() {
+                return Poll::Ready(Some((&event).try_into().map_err(Error::from)));
+            }
+
+            // A single read receives one datagram, which may hold several events
+            this.buf.resize(MAX_NL_LENGTH, 0);
+            let mut buf = ReadBuf::new(&mut this.buf);
+            if let Err(err) = ready!(Pin::new(&mut this.sock).poll_read(cx, &mut buf)) {
+                return Poll::Ready(Some(Err(err.into())));
+            }
+            let len = buf.filled().len();
+            if len == 0 {
+                return Poll::Ready(None);
+            }
+            let res = NlBuffer::<u16, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>::from_bytes_with_input(
+                &mut Cursor::new(&this.buf[..len]),
+                len,
+            );
+            match res {
+                Ok(res) => {
+                    for event in res {
+                        if let NlPayload::Payload(event) = event.nl_payload {
+                            this.pending.push_back(event);
+                        }
+                    }
+                }
+                Err(err) => return Poll::Ready(Some(Err(err.into()))),
+            }
+        }
+    }
+}
+
+/// Wait on a socket in a multicast group for the event `outcome` returns the result for
+async fn wait_for_event<F>(events: &mut NlSocket, outcome: F) -> Result<(), Error>
+where
+    F: Fn(&Genlmsghdr<Nl80211Cmd, Nl80211Attr>) -> Option<Result<(), Error>>,
+{
+    let mut buf = Vec::new();
+
+    loop {
+        let res = events
+            .recv::<u16, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(&mut buf)
+            .await?;
+        for event in res {
+            if let NlPayload::Payload(event) = event.nl_payload {
+                if let Some(result) = outcome(&event) {
+                    return result;
+                }
+            }
+        }
+    }
 }
 
 impl From<AsyncSocket> for NlSocket {
@@ -230,3 +1155,163 @@ impl From<AsyncSocket> for NlSocket {
         sock.sock
     }
 }
+
+#[cfg(test)]
+mod test_async_socket {
+    use super::*;
+    use crate::{NL_80211_GENL_NAME, NL_80211_GENL_VERSION};
+    use neli::consts::genl::{CtrlAttrMcastGrp, Index};
+    use neli::consts::nl::NlmFFlags;
+    use neli::genl::Nlattr;
+    use neli::types::GenlBuffer;
+    use std::os::unix::io::{FromRawFd, IntoRawFd};
+    use std::os::unix::net::UnixDatagram;
+
+    /// A socket receiving the given events, as if sent by the kernel
+    fn mock_socket(events: &[(Nl80211Cmd, i32)]) -> NlSocket {
+        let (sock, kernel) = UnixDatagram::pair().unwrap();
+        for (cmd, interface_index) in events {
+            let mut attrs = GenlBuffer::new();
+            attrs.push(
+                Nlattr::new(false, false, Nl80211Attr::AttrIfindex, *interface_index).unwrap(),
+            );
+            let event = Nlmsghdr::new(
+                None,
+                28u16,
+                NlmFFlags::empty(),
+                None,
+                None,
+                NlPayload::Payload(Genlmsghdr::new(*cmd, NL_80211_GENL_VERSION, attrs)),
+            );
+            let mut buffer = Cursor::new(Vec::new());
+            event.to_bytes(&mut buffer).unwrap();
+            kernel.send(buffer.get_ref()).unwrap();
+        }
+        NlSocket::new(unsafe { neli::socket::NlSocket::from_raw_fd(sock.into_raw_fd()) }).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_event() {
+        let mut events = mock_socket(&[
+            (Nl80211Cmd::CmdTriggerScan, 3),
+            (Nl80211Cmd::CmdNewScanResults, 4),
+            (Nl80211Cmd::CmdNewScanResults, 3),
+        ]);
+        assert!(wait_for_event(&mut events, |event| scan_outcome(event, 3))
+            .await
+            .is_ok());
+
+        let mut events = mock_socket(&[
+            (Nl80211Cmd::CmdTriggerScan, 3),
+            (Nl80211Cmd::CmdScanAborted, 3),
+        ]);
+        assert!(matches!(
+            wait_for_event(&mut events, |event| scan_outcome(event, 3)).await,
+            Err(Error::ScanAborted)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_next_event() {
+        let mut socket = AsyncSocket {
+            sock: mock_socket(&[]),
+            family_id: 28,
+            validate_channels: false,
+            events: None,
+            seq: 0,
+            buf: Vec::new(),
+        };
+        assert!(socket.next_event().await.is_err());
+
+        socket.events = Some(EventStream::new(mock_socket(&[
+            (Nl80211Cmd::CmdTriggerScan, 3),
+            (Nl80211Cmd::CmdScanAborted, 3),
+        ])));
+        assert_eq!(
+            socket.next_event().await.unwrap(),
+            WifiEvent::ScanStarted { interface_index: 3 }
+        );
+        assert_eq!(
+            socket.next_event().await.unwrap(),
+            WifiEvent::ScanAborted { interface_index: 3 }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_events() {
+        let mut socket = AsyncSocket {
+            sock: mock_socket(&[]),
+            family_id: 28,
+            validate_channels: false,
+            events: Some(EventStream::new(mock_socket(&[
+                (Nl80211Cmd::CmdTriggerScan, 3),
+                (Nl80211Cmd::CmdNewScanResults, 3),
+            ]))),
+            seq: 0,
+            buf: Vec::new(),
+        };
+        let mut events = socket.events().await.unwrap();
+        assert!(socket.next_event().await.is_err());
+
+        for expected in [
+            WifiEvent::ScanStarted { interface_index: 3 },
+            WifiEvent::ScanResults { interface_index: 3 },
+        ] {
+            let event = poll_fn(|cx| Pin::new(&mut events).poll_next(cx)).await;
+            assert_eq!(event.unwrap().unwrap(), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_mcast_groups() {
+        // The kernel answers the request numbered 1, after an event of an earlier subscription
+        let (sock, kernel) = UnixDatagram::pair().unwrap();
+        for (seq, id) in [(0, 9u32), (1, 5)] {
+            let mut group = Nlattr::new(true, false, Index::from(1), Vec::<u8>::new()).unwrap();
+            group
+                .add_nested_attribute(&Nlattr::new(false, false, CtrlAttrMcastGrp::Id, id).unwrap())
+                .unwrap();
+            group
+                .add_nested_attribute(
+                    &Nlattr::new(false, false, CtrlAttrMcastGrp::Name, "scan").unwrap(),
+                )
+                .unwrap();
+            let mut groups =
+                Nlattr::new(true, false, CtrlAttr::McastGroups, Vec::<u8>::new()).unwrap();
+            groups.add_nested_attribute(&group).unwrap();
+            let mut attrs = GenlBuffer::new();
+            attrs
+                .push(Nlattr::new(false, false, CtrlAttr::FamilyName, NL_80211_GENL_NAME).unwrap());
+            attrs.push(groups);
+            let family = Nlmsghdr::new(
+                None,
+                GenlId::Ctrl,
+                NlmFFlags::empty(),
+                Some(seq),
+                None,
+                NlPayload::Payload(Genlmsghdr::new(CtrlCmd::Newfamily, 2, attrs)),
+            );
+            let mut buffer = Cursor::new(Vec::new());
+            family.to_bytes(&mut buffer).unwrap();
+            kernel.send(buffer.get_ref()).unwrap();
+        }
+        let mut socket = AsyncSocket {
+            sock: NlSocket::new(unsafe { neli::socket::NlSocket::from_raw_fd(sock.into_raw_fd()) })
+                .unwrap(),
+            family_id: 28,
+            validate_channels: false,
+            events: None,
+            seq: 0,
+            buf: Vec::new(),
+        };
+
+        assert_eq!(
+            socket
+                .resolve_mcast_groups(&["scan", "vendor"])
+                .await
+                .unwrap(),
+            vec![Some(5), None]
+        );
+        assert_eq!(socket.seq, 1);
+    }
+}
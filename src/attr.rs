@@ -260,6 +260,81 @@ pub enum Nl80211Attr {
     AttrNanDual = 239,
     AttrNanFunc = 240,
     AttrNanMatch = 241,
+    AttrFilsKek = 242,
+    AttrFilsNonces = 243,
+    AttrMulticastToUnicastEnabled = 244,
+    AttrBssid = 245,
+    AttrSchedScanRelativeRssi = 246,
+    AttrSchedScanRssiAdjust = 247,
+    AttrTimeoutReason = 248,
+    AttrFilsErpUsername = 249,
+    AttrFilsErpRealm = 250,
+    AttrFilsErpNextSeqNum = 251,
+    AttrFilsErpRrk = 252,
+    AttrFilsCacheId = 253,
+    AttrPmk = 254,
+    AttrSchedScanMulti = 255,
+    AttrSchedScanMaxReqs = 256,
+    AttrWant1x4wayHs = 257,
+    AttrPmkr0Name = 258,
+    AttrPortAuthorized = 259,
+    AttrExternalAuthAction = 260,
+    AttrExternalAuthSupport = 261,
+    AttrNss = 262,
+    AttrAckSignal = 263,
+    AttrControlPortOverNl80211 = 264,
+    AttrTxqStats = 265,
+    AttrTxqLimit = 266,
+    AttrTxqMemoryLimit = 267,
+    AttrTxqQuantum = 268,
+    AttrHeCapability = 269,
+    AttrFtmResponder = 270,
+    AttrFtmResponderStats = 271,
+    AttrTimeout = 272,
+    AttrPeerMeasurements = 273,
+    AttrAirtimeWeight = 274,
+    AttrStaTxPowerSetting = 275,
+    AttrStaTxPower = 276,
+    AttrSaePassword = 277,
+    AttrTwtResponder = 278,
+    AttrHeObssPd = 279,
+    AttrWiphyEdmgChannels = 280,
+    AttrWiphyEdmgBwConfig = 281,
+    AttrVlanId = 282,
+    AttrHeBssColor = 283,
+    AttrIftypeAkmSuites = 284,
+    AttrTidConfig = 285,
+    AttrControlPortNoPreauth = 286,
+    AttrPmkLifetime = 287,
+    AttrPmkReauthThreshold = 288,
+    AttrReceiveMulticast = 289,
+    AttrWiphyFreqOffset = 290,
+    AttrCenterFreq1Offset = 291,
+    AttrScanFreqKhz = 292,
+    AttrHe6ghzCapability = 293,
+    AttrFilsDiscovery = 294,
+    AttrUnsolBcastProbeResp = 295,
+    AttrS1gCapability = 296,
+    AttrS1gCapabilityMask = 297,
+    AttrSaePwe = 298,
+    AttrReconnectRequested = 299,
+    AttrSarSpec = 300,
+    AttrDisableHe = 301,
+    AttrObssColorBitmap = 302,
+    AttrColorChangeCount = 303,
+    AttrColorChangeColor = 304,
+    AttrColorChangeElems = 305,
+    AttrMbssidConfig = 306,
+    AttrMbssidElems = 307,
+    AttrRadarBackground = 308,
+    AttrApSettingsFlags = 309,
+    AttrEhtCapability = 310,
+    AttrDisableEht = 311,
+    AttrMloLinks = 312,
+    AttrMloLinkId = 313,
+    AttrMldAddr = 314,
+    AttrMloSupport = 315,
+    AttrMaxNumAkmSuites = 316,
 }
 
 impl NlAttrType for Nl80211Attr {}
@@ -330,6 +405,16 @@ pub enum Nl80211RateInfo {
     RateInfo160MhzWidth = 10,
     RateInfo10MhzWidth = 11,
     RateInfo5MhzWidth = 12,
+    RateInfoHeMcs = 13,
+    RateInfoHeNss = 14,
+    RateInfoHeGi = 15,
+    RateInfoHeDcm = 16,
+    RateInfoHeRuAlloc = 17,
+    RateInfo320MhzWidth = 18,
+    RateInfoEhtMcs = 19,
+    RateInfoEhtNss = 20,
+    RateInfoEhtGi = 21,
+    RateInfoEhtRuAlloc = 22,
 }
 
 impl NlAttrType for Nl80211RateInfo {}
@@ -397,7 +482,7 @@ pub enum Nl80211StaInfo {
     StaInfoAirtimeWeight = 40,
     StaInfoAirtimeLinkMetric = 41,
     StaInfoAssocAtBoottime = 42,
-    StaInfoConnectedToAs = 42,
+    StaInfoConnectedToAs = 43,
 }
 
 impl NlAttrType for Nl80211StaInfo {}
@@ -462,10 +547,36 @@ pub enum Nl80211BandAttr {
     BandAttrHtAmpduDensity = 6,
     BandAttrVhtMcsSet = 7,
     BandAttrVhtCapa = 8,
+    BandAttrIftypeData = 9,
+    BandAttrEdmgChannels = 10,
+    BandAttrEdmgBwConfig = 11,
+    BandAttrS1gMcsNssSet = 12,
+    BandAttrS1gCapa = 13,
 }
 
 impl NlAttrType for Nl80211BandAttr {}
 
+/// nl80211BandIftypeAttr
+///
+/// Enumeration from nl80211/nl80211.h
+#[neli_enum(serialized_type = "u16")]
+pub enum Nl80211BandIftypeAttr {
+    BandIftypeAttrInvalid = 0,
+    BandIftypeAttrIftypes = 1,
+    BandIftypeAttrHeCapMac = 2,
+    BandIftypeAttrHeCapPhy = 3,
+    BandIftypeAttrHeCapMcsSet = 4,
+    BandIftypeAttrHeCapPpe = 5,
+    BandIftypeAttrHe6ghzCapa = 6,
+    BandIftypeAttrVendorElems = 7,
+    BandIftypeAttrEhtCapMac = 8,
+    BandIftypeAttrEhtCapPhy = 9,
+    BandIftypeAttrEhtCapMcsSet = 10,
+    BandIftypeAttrEhtCapPpe = 11,
+}
+
+impl NlAttrType for Nl80211BandIftypeAttr {}
+
 /// nl80211FrequencyAttr
 ///
 /// Enumeration from nl80211/nl80211.h:2833
@@ -767,6 +878,12 @@ pub enum Nl80211ChanWidth {
     ChanWidth160 = 5,
     ChanWidth5 = 6,
     ChanWidth10 = 7,
+    ChanWidth1 = 8,
+    ChanWidth2 = 9,
+    ChanWidth4 = 10,
+    ChanWidth8 = 11,
+    ChanWidth16 = 12,
+    ChanWidth320 = 13,
 }
 
 impl NlAttrType for Nl80211ChanWidth {}
@@ -938,6 +1055,9 @@ pub enum Nl80211Bandc {
     Band2ghz = 0,
     Band5ghz = 1,
     Band60ghz = 2,
+    Band6ghz = 3,
+    BandS1ghz = 4,
+    BandLc = 5,
 }
 
 impl NlAttrType for Nl80211Bandc {}
@@ -1256,6 +1376,38 @@ pub enum Nl80211ExtFeatureIndex {
     ExtFeatureBeaconRateLegacy = 6,
     ExtFeatureBeaconRateHt = 7,
     ExtFeatureBeaconRateVht = 8,
+    ExtFeatureFilsSta = 9,
+    ExtFeatureMgmtTxRandomTa = 10,
+    ExtFeatureMgmtTxRandomTaConnected = 11,
+    ExtFeatureSchedScanRelativeRssi = 12,
+    ExtFeatureCqmRssiList = 13,
+    ExtFeatureFilsSkOffload = 14,
+    ExtFeatureFourWayHandshakeStaPsk = 15,
+    ExtFeatureFourWayHandshakeSta1x = 16,
+    ExtFeatureFilsMaxChannelTime = 17,
+    ExtFeatureAcceptBcastProbeResp = 18,
+    ExtFeatureOceProbeReqHighTxRate = 19,
+    ExtFeatureOceProbeReqDeferralSuppression = 20,
+    ExtFeatureMfpOptional = 21,
+    ExtFeatureLowSpanScan = 22,
+    ExtFeatureLowPowerScan = 23,
+    ExtFeatureHighAccuracyScan = 24,
+    ExtFeatureDfsOffload = 25,
+    ExtFeatureControlPortOverNl80211 = 26,
+    ExtFeatureAckSignalSupport = 27,
+    ExtFeatureTxqs = 28,
+    ExtFeatureScanRandomSn = 29,
+    ExtFeatureScanMinPreqContent = 30,
+    ExtFeatureCanReplacePtk0 = 31,
+    ExtFeatureEnableFtmResponder = 32,
+    ExtFeatureAirtimeFairness = 33,
+    ExtFeatureApPmksaCaching = 34,
+    ExtFeatureSchedScanBandSpecificRssiThold = 35,
+    ExtFeatureExtKeyId = 36,
+    ExtFeatureStaTxPwr = 37,
+    ExtFeatureSaeOffload = 38,
+    ExtFeatureVlanOffload = 39,
+    ExtFeatureAql = 40,
 }
 
 impl NlAttrType for Nl80211ExtFeatureIndex {}
@@ -1293,6 +1445,13 @@ pub enum Nl80211ScanFlags {
     ScanFlagFlush = 1 << 1,
     ScanFlagAp = 1 << 2,
     ScanFlagRandomAddr = 1 << 3,
+    ScanFlagFilsMaxChannelTime = 1 << 4,
+    ScanFlagAcceptBcastProbeResp = 1 << 5,
+    ScanFlagOceProbeReqHighTxRate = 1 << 6,
+    ScanFlagOceProbeReqDeferralSuppression = 1 << 7,
+    ScanFlagLowSpan = 1 << 8,
+    ScanFlagLowPower = 1 << 9,
+    ScanFlagHighAccuracy = 1 << 10,
 }
 
 impl NlAttrType for Nl80211ScanFlags {}
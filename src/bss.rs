@@ -1,27 +1,324 @@
-use crate::attr::{Attrs, Nl80211Attr, Nl80211Bss};
+use crate::attr::{Attrs, Nl80211Attr, Nl80211Bss, Nl80211BssStatus};
+use crate::channel::{band_of_freq, FrequencyBand};
+use crate::interface::ssid_to_string;
+use crate::mac::MacAddress;
+use crate::metrics::{insert_metric, Metrics};
+use crate::security::{elements, SecurityInfo};
 
 use neli::attr::Attribute;
 use neli::err::DeError;
 
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
 /// A struct representing a BSS (Basic Service Set)
+///
+/// BSSes normally come from the scan results of `get_bss_info`. Scan results to test with, e.g.
+/// for network selection, can be put together with the `with_*` methods:
+///
+/// ```
+/// # use neli_wifi::{Bss, FrequencyBand};
+/// let bss = Bss::default()
+///     .with_frequency(2412)
+///     .with_information_elements(vec![0, 4, b't', b'e', b's', b't']);
+/// assert_eq!(bss.band(), Some(FrequencyBand::Band2G));
+/// assert_eq!(bss.ssid_str().as_deref(), Some("test"));
+/// ```
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub struct Bss {
     /// BSSID
-    pub bssid: Option<Vec<u8>>,
+    pub bssid: Option<MacAddress>,
     /// Frequency in MHz
     pub frequency: Option<u32>,
     /// Beacon interval of the (I)BSS
     pub beacon_interval: Option<u16>,
+    /// Capability information field of the probe response/beacon
+    pub capability: Option<u16>,
     /// Age of this BSS entry in ms
     pub seen_ms_ago: Option<u32>,
+    /// Timing synchronization function (TSF) timer of the last received frame in µs, from a
+    /// probe response if the BSS answered one
+    pub tsf: Option<u64>,
+    /// TSF timer of the last received beacon in µs
+    pub beacon_tsf: Option<u64>,
     /// Status, if this BSS is "used"
     pub status: Option<u32>,
+    /// Whether the interface is currently associated with (or joined to) this BSS
+    pub is_current: bool,
     /// Signal strength of probe response/beacon in mBm (100 * dBm)
     pub signal: Option<i32>,
     /// binary attribute containing the raw information elements from the probe response/beacon.
+    ///
+    /// This is the unmodified blob as reported by the kernel, see [`Bss::ies_raw`].
     pub information_elements: Option<Vec<u8>>,
 }
 
+/// A single information element of a beacon or probe response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InformationElement<'a> {
+    /// Element id, 255 for extension elements whose extension id is the first data byte
+    pub id: u8,
+    /// The element contents without the id and length
+    pub data: &'a [u8],
+}
+
+/// A rate from the (extended) supported rates elements
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SupportedRate {
+    /// Rate in 500 kbit/s
+    pub rate: u8,
+    /// Whether every station of the BSS has to support the rate
+    pub basic: bool,
+}
+
+impl SupportedRate {
+    /// The rate in Mbit/s
+    pub fn mbps(&self) -> f64 {
+        f64::from(self.rate) / 2.0
+    }
+}
+
+/// A vendor specific information element
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VendorElement<'a> {
+    /// Organizationally unique identifier of the vendor
+    pub oui: [u8; 3],
+    /// The element contents following the OUI, usually starting with a vendor specific type
+    pub data: &'a [u8],
+}
+
+/// The PHY capabilities a BSS advertises in its HT, VHT and HE capabilities elements
+///
+/// These describe what the AP supports, the channel it operates on may be narrower.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PhyCapabilities {
+    /// Whether the BSS supports HT (802.11n)
+    pub ht_supported: bool,
+    /// Highest received HT MCS index with equal modulation (0 to 31), which determines the
+    /// number of spatial streams
+    pub max_ht_mcs: Option<u8>,
+    /// Whether the BSS supports VHT (802.11ac)
+    pub vht_supported: bool,
+    /// Whether the BSS supports HE (802.11ax)
+    pub he_supported: bool,
+    /// Widest supported channel in MHz, `None` without any of the elements
+    pub max_bandwidth: Option<u32>,
+}
+
+impl PhyCapabilities {
+    /// Derive the PHY capabilities from raw information elements
+    ///
+    /// Elements too short for the fields decoded here are ignored.
+    pub fn from_ies(ies: &[u8]) -> Self {
+        let mut res = Self::default();
+        for (id, data) in elements(ies) {
+            let bandwidth = match (id, data) {
+                (ELEMENT_HT_CAPABILITIES, [cap, _, _, mcs @ ..]) if data.len() >= 26 => {
+                    res.ht_supported = true;
+                    res.max_ht_mcs = (0..32u8)
+                        .rev()
+                        .find(|&i| mcs[usize::from(i / 8)] & (1 << (i % 8)) != 0);
+                    if cap & 0x02 != 0 {
+                        40
+                    } else {
+                        20
+                    }
+                }
+                (ELEMENT_VHT_CAPABILITIES, [cap, ..]) if data.len() >= 12 => {
+                    res.vht_supported = true;
+                    // Supported channel width set, 160 MHz or 160 and 80+80 MHz
+                    if cap & 0x0c != 0 {
+                        160
+                    } else {
+                        80
+                    }
+                }
+                (ELEMENT_EXTENSION, [EXT_ELEMENT_HE_CAPABILITIES, _, _, _, _, _, _, phy, ..])
+                    if data.len() >= 18 =>
+                {
+                    res.he_supported = true;
+                    // Channel width set of the first HE PHY capabilities byte
+                    if phy & 0x18 != 0 {
+                        160
+                    } else if phy & 0x04 != 0 {
+                        80
+                    } else if phy & 0x02 != 0 {
+                        40
+                    } else {
+                        20
+                    }
+                }
+                _ => continue,
+            };
+            res.max_bandwidth = res.max_bandwidth.max(Some(bandwidth));
+        }
+        res
+    }
+}
+
+/// How busy a BSS is, as advertised in its BSS load element
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BssLoad {
+    /// Number of associated stations
+    pub station_count: u16,
+    /// Share of the time the AP sensed the medium busy, scaled to 0 to 255
+    pub channel_utilization: u8,
+    /// Remaining medium time available for admission control in 32 µs/s
+    pub available_admission_capacity: u16,
+}
+
+impl BssLoad {
+    /// [`channel_utilization`](Self::channel_utilization) in percent
+    pub fn utilization_percent(&self) -> f64 {
+        f64::from(self.channel_utilization) * 100.0 / 255.0
+    }
+}
+
+impl Bss {
+    /// Set the [`bssid`](Self::bssid)
+    pub fn with_bssid(mut self, bssid: MacAddress) -> Self {
+        self.bssid = Some(bssid);
+        self
+    }
+
+    /// Set the [`frequency`](Self::frequency) in MHz
+    pub fn with_frequency(mut self, frequency: u32) -> Self {
+        self.frequency = Some(frequency);
+        self
+    }
+
+    /// Set the [`signal`](Self::signal) strength
+    pub fn with_signal(mut self, signal: i32) -> Self {
+        self.signal = Some(signal);
+        self
+    }
+
+    /// Set the [`information_elements`](Self::information_elements), which hold e.g. the SSID
+    pub fn with_information_elements(mut self, ies: Vec<u8>) -> Self {
+        self.information_elements = Some(ies);
+        self
+    }
+
+    /// Set whether the interface is associated with the BSS, cf. [`is_current`](Self::is_current)
+    pub fn with_current(mut self, is_current: bool) -> Self {
+        self.is_current = is_current;
+        self
+    }
+
+    /// The band of [`frequency`](Self::frequency)
+    pub fn band(&self) -> Option<FrequencyBand> {
+        self.frequency.and_then(band_of_freq)
+    }
+
+    /// The raw information elements of the BSS, or an empty slice if none were reported
+    ///
+    /// The elements are returned exactly as received, including vendor specific elements, so
+    /// they can be handed to a custom IE parser.
+    pub fn ies_raw(&self) -> &[u8] {
+        self.information_elements.as_deref().unwrap_or_default()
+    }
+
+    /// The information elements of the BSS in the order they were received
+    ///
+    /// Decoding stops at the first truncated element, the elements before it are still
+    /// returned.
+    pub fn elements(&self) -> Vec<InformationElement<'_>> {
+        elements(self.ies_raw())
+            .map(|(id, data)| InformationElement { id, data })
+            .collect()
+    }
+
+    /// The rates of the supported rates and extended supported rates elements
+    ///
+    /// BSS membership selectors (e.g. "HT PHY" or "SAE hash-to-element only") share the
+    /// encoding with the rates, they are left out.
+    pub fn supported_rates(&self) -> Vec<SupportedRate> {
+        elements(self.ies_raw())
+            .filter(|&(id, _)| id == 1 || id == 50)
+            .flat_map(|(_, rates)| rates)
+            .filter(|&rate| !BSS_MEMBERSHIP_SELECTORS.contains(rate))
+            .map(|&rate| SupportedRate {
+                rate: rate & 0x7f,
+                basic: rate & 0x80 != 0,
+            })
+            .collect()
+    }
+
+    /// The vendor specific elements, skipping those too short to hold an OUI
+    pub fn vendor_elements(&self) -> Vec<VendorElement<'_>> {
+        elements(self.ies_raw())
+            .filter(|&(id, _)| id == 221)
+            .filter_map(|(_, data)| {
+                let (oui, data) = data.split_first_chunk::<3>()?;
+                Some(VendorElement { oui: *oui, data })
+            })
+            .collect()
+    }
+
+    /// The SSID from the information elements, empty for hidden networks
+    pub fn ssid(&self) -> Option<&[u8]> {
+        elements(self.ies_raw())
+            .find(|&(id, _)| id == 0)
+            .map(|(_, ssid)| ssid)
+    }
+
+    /// [`ssid`](Self::ssid) as a string without trailing NULs, invalid UTF-8 is replaced with
+    /// U+FFFD
+    pub fn ssid_str(&self) -> Option<String> {
+        self.ssid().map(ssid_to_string)
+    }
+
+    /// The security configuration advertised in the information elements
+    ///
+    /// Its `Display` implementation gives a short label like "WPA2-PSK" for scan lists. WEP is
+    /// detected from the privacy bit of [`capability`](Self::capability).
+    pub fn security(&self) -> SecurityInfo {
+        let mut res = SecurityInfo::from_ies(self.ies_raw());
+        let privacy = self
+            .capability
+            .is_some_and(|cap| cap & CAPABILITY_PRIVACY != 0);
+        res.wep = privacy && !res.rsn && !res.wpa;
+        res
+    }
+
+    /// The HT, VHT and HE capabilities advertised in the information elements
+    pub fn phy_capabilities(&self) -> PhyCapabilities {
+        PhyCapabilities::from_ies(self.ies_raw())
+    }
+
+    /// The station count and channel utilization from the BSS load element
+    ///
+    /// Only APs with QoS support advertise the element.
+    pub fn bss_load(&self) -> Option<BssLoad> {
+        elements(self.ies_raw()).find_map(|(id, data)| match (id, data) {
+            (ELEMENT_BSS_LOAD, &[s1, s2, utilization, c1, c2]) => Some(BssLoad {
+                station_count: u16::from_le_bytes([s1, s2]),
+                channel_utilization: utilization,
+                available_admission_capacity: u16::from_le_bytes([c1, c2]),
+            }),
+            _ => None,
+        })
+    }
+
+    /// All known numeric values of the BSS, for metric exporters
+    ///
+    /// Uses the same key naming as [`Station::metrics`](crate::Station::metrics).
+    pub fn metrics(&self) -> HashMap<&'static str, f64> {
+        let mut metrics = Metrics::new();
+        let m = &mut metrics;
+        insert_metric(m, "frequency_mhz", self.frequency.map(f64::from));
+        insert_metric(m, "signal_dbm", self.signal.map(|s| f64::from(s) / 100.0));
+        insert_metric(m, "beacon_interval_tu", self.beacon_interval.map(f64::from));
+        insert_metric(m, "seen_ms_ago", self.seen_ms_ago.map(f64::from));
+        metrics
+    }
+}
+
 impl TryFrom<Attrs<'_, Nl80211Attr>> for Bss {
     type Error = DeError;
 
@@ -32,7 +329,7 @@ impl TryFrom<Attrs<'_, Nl80211Attr>> for Bss {
             for attr in attrs.iter() {
                 match attr.nla_type.nla_type {
                     Nl80211Bss::BssBssid => {
-                        res.bssid = Some(attr.get_payload_as_with_len()?);
+                        res.bssid = Some(attr.nla_payload.as_ref().try_into()?);
                     }
                     Nl80211Bss::BssFrequency => {
                         res.frequency = Some(attr.get_payload_as()?);
@@ -40,11 +337,26 @@ impl TryFrom<Attrs<'_, Nl80211Attr>> for Bss {
                     Nl80211Bss::BssBeaconInterval => {
                         res.beacon_interval = Some(attr.get_payload_as()?);
                     }
+                    Nl80211Bss::BssCapability => {
+                        res.capability = Some(attr.get_payload_as()?);
+                    }
                     Nl80211Bss::BssSeenMsAgo => {
                         res.seen_ms_ago = Some(attr.get_payload_as()?);
                     }
+                    Nl80211Bss::BssTsf => {
+                        res.tsf = Some(attr.get_payload_as()?);
+                    }
+                    Nl80211Bss::BssBeaconTsf => {
+                        res.beacon_tsf = Some(attr.get_payload_as()?);
+                    }
                     Nl80211Bss::BssStatus => {
-                        res.status = Some(attr.get_payload_as()?);
+                        let status: u32 = attr.get_payload_as()?;
+                        res.status = Some(status);
+                        res.is_current = matches!(
+                            Nl80211BssStatus::from(status as u16),
+                            Nl80211BssStatus::BssStatusAssociated
+                                | Nl80211BssStatus::BssStatusIbssJoined
+                        );
                     }
                     Nl80211Bss::BssSignalMbm => {
                         res.signal = Some(attr.get_payload_as()?);
@@ -60,10 +372,70 @@ impl TryFrom<Attrs<'_, Nl80211Attr>> for Bss {
     }
 }
 
+/// Element id of the BSS load element
+const ELEMENT_BSS_LOAD: u8 = 11;
+/// Element id of the HT capabilities element
+const ELEMENT_HT_CAPABILITIES: u8 = 45;
+/// Element id of the VHT capabilities element
+const ELEMENT_VHT_CAPABILITIES: u8 = 191;
+/// Element id of extension elements, the first data byte is the extension id
+const ELEMENT_EXTENSION: u8 = 255;
+/// Extension id of the HE capabilities element
+const EXT_ELEMENT_HE_CAPABILITIES: u8 = 35;
+
+/// Privacy bit of the capability information field, set by all networks requiring encryption
+const CAPABILITY_PRIVACY: u16 = 1 << 4;
+
+/// BSS membership selectors with the basic rate bit set, as found in the rates elements
+const BSS_MEMBERSHIP_SELECTORS: [u8; 6] = [0xff, 0xfe, 0xfd, 0xfc, 0xfb, 0xfa];
+
+/// Find the BSS the interface is currently associated with in a list of scan results
+pub fn current_bss(bss_list: &[Bss]) -> Option<&Bss> {
+    bss_list.iter().find(|bss| bss.is_current)
+}
+
+/// Keep the scan results in `band`, dropping entries without a (known) frequency
+pub fn bss_in_band(bss_list: Vec<Bss>, band: FrequencyBand) -> Vec<Bss> {
+    bss_list
+        .into_iter()
+        .filter(|bss| bss.band() == Some(band))
+        .collect()
+}
+
+/// Collapse scan results to a single entry per BSSID
+///
+/// For every BSSID the entry with the strongest `signal` is kept. Ties, including entries which
+/// report no signal at all, are broken in favour of the most recently seen entry (the smallest
+/// `seen_ms_ago`). Entries without a BSSID are kept as-is. The order of first appearance is
+/// preserved.
+pub fn dedup_bss(bss_list: Vec<Bss>) -> Vec<Bss> {
+    let rank = |bss: &Bss| {
+        (
+            bss.signal.unwrap_or(i32::MIN),
+            Reverse(bss.seen_ms_ago.unwrap_or(u32::MAX)),
+        )
+    };
+
+    let mut retval: Vec<Bss> = Vec::with_capacity(bss_list.len());
+    for bss in bss_list {
+        let existing = bss
+            .bssid
+            .as_ref()
+            .and_then(|bssid| retval.iter_mut().find(|b| b.bssid.as_ref() == Some(bssid)));
+        match existing {
+            Some(existing) if rank(&bss) > rank(existing) => *existing = bss,
+            Some(_) => (),
+            None => retval.push(bss),
+        }
+    }
+    retval
+}
+
 #[cfg(test)]
 mod test_bss {
     use super::*;
     use crate::attr::Nl80211Attr::*;
+    use crate::security::SecurityType;
     use neli::attr::AttrHandle;
     use neli::genl::{AttrType, Nlattr};
     use neli::types::Buffer;
@@ -131,11 +503,15 @@ mod test_bss {
             .try_into()
             .unwrap();
         let expected_bss = Bss {
-            bssid: Some(vec![255, 255, 255, 255, 255, 255]),
+            bssid: Some(MacAddress([255; 6])),
             frequency: Some(u32::from_le_bytes([108, 9, 0, 0])),
             beacon_interval: Some(u16::from_le_bytes([100, 0])),
+            capability: Some(u16::from_le_bytes([17, 21])),
             seen_ms_ago: Some(u32::from_le_bytes([100, 0, 0, 0])),
+            tsf: Some(u64::from_le_bytes([132, 12, 93, 163, 39, 0, 0, 0])),
+            beacon_tsf: Some(u64::from_le_bytes([187, 118, 116, 163, 39, 0, 0, 0])),
             status: Some(u32::from_le_bytes([1, 0, 0, 0])),
+            is_current: true,
             signal: Some(i32::from_le_bytes([76, 235, 255, 255])),
             information_elements: Some(vec![
                 0, 8, 83, 70, 82, 45, 49, 99, 50, 56, 1, 8, 130, 132, 139, 150, 36, 48, 72, 108, 3,
@@ -159,4 +535,258 @@ mod test_bss {
 
         assert_eq!(bss, expected_bss)
     }
+
+    #[test]
+    fn test_dedup() {
+        let bss = |bssid: u8, signal: i32, seen_ms_ago: u32| Bss {
+            bssid: Some(MacAddress([bssid; 6])),
+            signal: Some(signal),
+            seen_ms_ago: Some(seen_ms_ago),
+            ..Default::default()
+        };
+
+        let deduped = dedup_bss(vec![
+            bss(1, -7000, 100),
+            bss(2, -5000, 100),
+            bss(1, -4000, 300),
+            bss(2, -5000, 20),
+            bss(2, -5000, 50),
+        ]);
+
+        assert_eq!(deduped, vec![bss(1, -4000, 300), bss(2, -5000, 20)]);
+    }
+
+    #[test]
+    fn test_current_bss() {
+        let bss = |bssid: u8, status: &[u8]| -> Bss {
+            let mut attr = vec![10, 0, 1, 0, bssid, bssid, bssid, bssid, bssid, bssid, 0, 0];
+            if !status.is_empty() {
+                attr.extend([8, 0, 9, 0]);
+                attr.extend(status);
+            }
+            AttrHandle::new(vec![new_attr(AttrBss, attr)].into_iter().collect())
+                .try_into()
+                .unwrap()
+        };
+
+        let bss_list = vec![bss(1, &[]), bss(2, &[0, 0, 0, 0]), bss(3, &[1, 0, 0, 0])];
+
+        assert!(!bss_list[0].is_current);
+        assert!(!bss_list[1].is_current);
+        assert_eq!(current_bss(&bss_list), Some(&bss_list[2]));
+        assert_eq!(current_bss(&bss_list[..2]), None);
+    }
+
+    #[test]
+    fn test_bss_in_band() {
+        let bss = |frequency| Bss {
+            frequency,
+            ..Default::default()
+        };
+        let bss_list = vec![
+            bss(Some(2412)),
+            bss(Some(5180)),
+            bss(None),
+            bss(Some(2462)),
+            bss(Some(5745)),
+        ];
+
+        assert_eq!(
+            bss_in_band(bss_list, FrequencyBand::Band5G),
+            vec![bss(Some(5180)), bss(Some(5745))]
+        );
+    }
+
+    #[test]
+    fn test_ies_raw() {
+        // SSID "test" and a vendor specific element, 15 bytes so the attribute is padded
+        let ies = [0, 4, 116, 101, 115, 116, 221, 7, 0, 80, 242, 4, 16, 74, 0];
+        let mut attr = vec![19, 0, 6, 0];
+        attr.extend(ies);
+        attr.push(0);
+
+        let bss: Bss = AttrHandle::new(vec![new_attr(AttrBss, attr)].into_iter().collect())
+            .try_into()
+            .unwrap();
+
+        assert_eq!(bss.ies_raw(), &ies);
+        assert_eq!(bss.ssid(), Some(&b"test"[..]));
+        assert_eq!(bss.ssid_str().as_deref(), Some("test"));
+        assert_eq!(Bss::default().ies_raw(), &[] as &[u8]);
+        assert_eq!(Bss::default().ssid(), None);
+    }
+
+    #[test]
+    fn test_elements() {
+        // SSID "ab", 1 (basic) and 2 Mbit/s, HT PHY selector, extended rate 54 Mbit/s, a WPS
+        // element, a vendor element too short for an OUI and a truncated RSN element
+        let bss = Bss {
+            information_elements: Some(vec![
+                0, 2, 97, 98, 1, 3, 130, 4, 255, 50, 1, 108, 221, 5, 0, 80, 242, 4, 16, 221, 2, 0,
+                80, 48, 5, 1,
+            ]),
+            ..Default::default()
+        };
+
+        let elements = bss.elements();
+        assert_eq!(elements.len(), 5);
+        assert_eq!(elements[0], InformationElement { id: 0, data: b"ab" });
+        assert_eq!(elements[4].id, 221);
+
+        let rates = bss.supported_rates();
+        assert_eq!(
+            rates,
+            vec![
+                SupportedRate {
+                    rate: 2,
+                    basic: true
+                },
+                SupportedRate {
+                    rate: 4,
+                    basic: false
+                },
+                SupportedRate {
+                    rate: 108,
+                    basic: false
+                },
+            ]
+        );
+        assert_eq!(rates[2].mbps(), 54.0);
+
+        assert_eq!(
+            bss.vendor_elements(),
+            vec![VendorElement {
+                oui: [0, 80, 242],
+                data: &[4, 16]
+            }]
+        );
+        assert!(Bss::default().elements().is_empty());
+    }
+
+    #[test]
+    fn test_wep() {
+        // ESS with the privacy bit, but without RSN or WPA element
+        let attr = vec![
+            6, 0, 5, 0, 17, 0, 0, 0, 10, 0, 6, 0, 0, 4, 116, 101, 115, 116, 0, 0,
+        ];
+        let bss: Bss = AttrHandle::new(vec![new_attr(AttrBss, attr)].into_iter().collect())
+            .try_into()
+            .unwrap();
+
+        assert_eq!(bss.capability, Some(17));
+        assert!(bss.security().wep);
+        assert_eq!(bss.security().to_string(), "WEP");
+        assert_eq!(bss.security().security_type(), SecurityType::Wep);
+
+        // The privacy bit is set by WPA2 networks as well
+        let wpa2 = Bss {
+            capability: Some(17),
+            information_elements: Some(vec![
+                48, 20, 1, 0, 0, 15, 172, 4, 1, 0, 0, 15, 172, 4, 1, 0, 0, 15, 172, 2, 0, 0,
+            ]),
+            ..Default::default()
+        };
+        assert!(!wpa2.security().wep);
+        assert_eq!(wpa2.security().to_string(), "WPA2-PSK");
+        assert_eq!(Bss::default().security().to_string(), "Open");
+    }
+
+    #[test]
+    fn test_phy_capabilities() {
+        // HT with 40 MHz and MCS 0 to 15, VHT with 160 MHz and HE with 80 MHz
+        let ht: &[u8] = &[
+            45, 26, 2, 0, 23, 255, 255, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0,
+        ];
+        let vht: &[u8] = &[191, 12, 4, 0, 0, 0, 250, 255, 0, 0, 250, 255, 0, 0];
+        let he: &[u8] = &[
+            255, 22, 35, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 250, 255, 250, 255,
+        ];
+        let bss = |ies: &[&[u8]]| Bss {
+            information_elements: Some(ies.concat()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            bss(&[ht, vht, he]).phy_capabilities(),
+            PhyCapabilities {
+                ht_supported: true,
+                max_ht_mcs: Some(15),
+                vht_supported: true,
+                he_supported: true,
+                max_bandwidth: Some(160),
+            }
+        );
+        // A 6 GHz AP only has the HE element
+        assert_eq!(bss(&[he]).phy_capabilities().max_bandwidth, Some(80));
+        // Truncated VHT element
+        let caps = bss(&[ht, &[191, 4, 4, 0, 0, 0]]).phy_capabilities();
+        assert!(!caps.vht_supported);
+        assert_eq!(caps.max_bandwidth, Some(40));
+        assert_eq!(
+            Bss::default().phy_capabilities(),
+            PhyCapabilities::default()
+        );
+    }
+
+    #[test]
+    fn test_bss_load() {
+        // 3 stations, 51/255 utilization and 31250 * 32 µs/s admission capacity
+        let bss = Bss {
+            information_elements: Some(vec![0, 1, 97, 11, 5, 3, 0, 51, 18, 122]),
+            ..Default::default()
+        };
+
+        let load = bss.bss_load().unwrap();
+        assert_eq!(
+            load,
+            BssLoad {
+                station_count: 3,
+                channel_utilization: 51,
+                available_admission_capacity: 31250,
+            }
+        );
+        assert_eq!(load.utilization_percent(), 20.0);
+
+        let truncated = Bss {
+            information_elements: Some(vec![11, 3, 3, 0, 51]),
+            ..Default::default()
+        };
+        assert_eq!(truncated.bss_load(), None);
+        assert_eq!(Bss::default().bss_load(), None);
+    }
+
+    #[test]
+    fn test_metrics() {
+        let bss = Bss {
+            bssid: Some(MacAddress([1; 6])),
+            frequency: Some(2412),
+            signal: Some(-4250),
+            beacon_interval: Some(100),
+            ..Default::default()
+        };
+
+        let metrics = bss.metrics();
+        assert_eq!(metrics.len(), 3);
+        assert_eq!(metrics["frequency_mhz"], 2412.0);
+        assert_eq!(metrics["signal_dbm"], -42.5);
+        assert_eq!(metrics["beacon_interval_tu"], 100.0);
+    }
+
+    #[test]
+    fn test_builder() {
+        let bss = Bss::default()
+            .with_bssid(MacAddress([2, 0, 0, 0, 0, 1]))
+            .with_frequency(5180)
+            .with_signal(-6000)
+            .with_information_elements(vec![0, 4, b'h', b'o', b'm', b'e'])
+            .with_current(true);
+
+        assert_eq!(bss.bssid, Some(MacAddress([2, 0, 0, 0, 0, 1])));
+        assert_eq!(bss.band(), Some(FrequencyBand::Band5G));
+        assert_eq!(bss.signal, Some(-6000));
+        assert_eq!(bss.ssid(), Some(&b"home"[..]));
+        assert!(bss.is_current);
+        assert_eq!(bss.seen_ms_ago, None);
+    }
 }
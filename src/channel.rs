@@ -0,0 +1,233 @@
+use crate::attr::{Nl80211Attr, Nl80211ChanWidth};
+
+use neli::err::SerError;
+use neli::genl::Nlattr;
+use neli::types::{Buffer, GenlBuffer};
+
+/// Width of an operating channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChannelWidth {
+    /// 20 MHz without HT
+    Width20NoHt,
+    Width20,
+    Width40,
+    Width80,
+    Width160,
+    Width320,
+}
+
+impl ChannelWidth {
+    /// Width in MHz
+    pub fn mhz(self) -> u32 {
+        match self {
+            Self::Width20NoHt | Self::Width20 => 20,
+            Self::Width40 => 40,
+            Self::Width80 => 80,
+            Self::Width160 => 160,
+            Self::Width320 => 320,
+        }
+    }
+}
+
+impl From<ChannelWidth> for Nl80211ChanWidth {
+    fn from(width: ChannelWidth) -> Self {
+        match width {
+            ChannelWidth::Width20NoHt => Self::ChanWidth20Noht,
+            ChannelWidth::Width20 => Self::ChanWidth20,
+            ChannelWidth::Width40 => Self::ChanWidth40,
+            ChannelWidth::Width80 => Self::ChanWidth80,
+            ChannelWidth::Width160 => Self::ChanWidth160,
+            ChannelWidth::Width320 => Self::ChanWidth320,
+        }
+    }
+}
+
+/// Compute the center frequency of the `width` wide channel containing the 20 MHz channel `freq`
+///
+/// In the 5 GHz and 6 GHz bands the channel is picked from the standard channelization. In the
+/// 2.4 GHz band, where 40 MHz channels may extend in either direction, the secondary channel is
+/// placed above the primary one for channels 1-7 and below it otherwise.
+pub fn center_freq(freq: u32, width: ChannelWidth) -> Option<u32> {
+    let mhz = width.mhz();
+    if mhz == 20 {
+        return Some(freq);
+    }
+
+    // Lowest 20 MHz channel of the first block of each channelization
+    let first = match freq {
+        2412..=2472 if width == ChannelWidth::Width40 => {
+            return Some(if freq <= 2442 { freq + 10 } else { freq - 10 });
+        }
+        5180..=5720 if mhz <= 160 => 5180,
+        5745..=5885 if mhz <= 160 => 5745,
+        5955..=7115 => 5955,
+        _ => return None,
+    };
+    if !(freq - first).is_multiple_of(20) {
+        return None;
+    }
+    let start = first + (freq - first) / mhz * mhz;
+    Some(start + mhz / 2 - 10)
+}
+
+/// A frequency band wifi operates in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FrequencyBand {
+    /// 2.4 GHz
+    Band2G,
+    /// 5 GHz, including the 4.9 GHz public safety band
+    Band5G,
+    /// 6 GHz
+    Band6G,
+    /// 60 GHz (DMG)
+    Band60G,
+}
+
+/// Classify a channel frequency (MHz) into its band
+///
+/// Follows the kernel's channel numbering, i.e. 5925 MHz already belongs to the 6 GHz band.
+pub fn band_of_freq(freq_mhz: u32) -> Option<FrequencyBand> {
+    match freq_mhz {
+        2400..=2500 => Some(FrequencyBand::Band2G),
+        4900..=5924 => Some(FrequencyBand::Band5G),
+        5925..=7125 => Some(FrequencyBand::Band6G),
+        57000..=71000 => Some(FrequencyBand::Band60G),
+        _ => None,
+    }
+}
+
+/// The IEEE 802.11 channel number of a channel frequency (MHz)
+pub fn channel_of_freq(freq_mhz: u32) -> Option<u32> {
+    match freq_mhz {
+        2484 => Some(14),
+        2412..=2472 => Some((freq_mhz - 2407) / 5),
+        4910..=4980 => Some((freq_mhz - 4000) / 5),
+        5000..=5924 => Some((freq_mhz - 5000) / 5),
+        5935 => Some(2),
+        5955..=7115 => Some((freq_mhz - 5950) / 5),
+        58320..=70200 => Some((freq_mhz - 56160) / 2160),
+        _ => None,
+    }
+}
+
+/// The center frequency (MHz) of an IEEE 802.11 channel number in `band`
+///
+/// The inverse of [`channel_of_freq`]. Like the kernel, 5 GHz channels 182 to 196 are mapped to
+/// the 4.9 GHz public safety band.
+pub fn freq_of_channel(channel: u32, band: FrequencyBand) -> Option<u32> {
+    match (band, channel) {
+        (FrequencyBand::Band2G, 14) => Some(2484),
+        (FrequencyBand::Band2G, 1..=13) => Some(2407 + channel * 5),
+        (FrequencyBand::Band5G, 182..=196) => Some(4000 + channel * 5),
+        (FrequencyBand::Band5G, 1..=181) => Some(5000 + channel * 5),
+        (FrequencyBand::Band6G, 2) => Some(5935),
+        (FrequencyBand::Band6G, 1..=233) => Some(5950 + channel * 5),
+        (FrequencyBand::Band60G, 1..=6) => Some(56160 + channel * 2160),
+        _ => None,
+    }
+}
+
+/// Append the attributes describing the channel `freq`/`width` to `attrs`
+pub(crate) fn push_channel_attrs(
+    attrs: &mut GenlBuffer<Nl80211Attr, Buffer>,
+    freq: u32,
+    width: ChannelWidth,
+) -> Result<(), SerError> {
+    let center = center_freq(freq, width).ok_or_else(|| {
+        SerError::new(format!(
+            "No {} MHz channel contains {} MHz",
+            width.mhz(),
+            freq
+        ))
+    })?;
+
+    attrs.push(Nlattr::new(false, false, Nl80211Attr::AttrWiphyFreq, freq)?);
+    attrs.push(Nlattr::new(
+        false,
+        false,
+        Nl80211Attr::AttrChannelWidth,
+        u32::from(u16::from(Nl80211ChanWidth::from(width))),
+    )?);
+    attrs.push(Nlattr::new(
+        false,
+        false,
+        Nl80211Attr::AttrCenterFreq1,
+        center,
+    )?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_channel {
+    use super::*;
+
+    #[test]
+    fn test_center_freq() {
+        assert_eq!(center_freq(2412, ChannelWidth::Width20), Some(2412));
+        assert_eq!(center_freq(2412, ChannelWidth::Width40), Some(2422));
+        assert_eq!(center_freq(2462, ChannelWidth::Width40), Some(2452));
+        assert_eq!(center_freq(2412, ChannelWidth::Width80), None);
+        assert_eq!(center_freq(5200, ChannelWidth::Width40), Some(5190));
+        assert_eq!(center_freq(5200, ChannelWidth::Width80), Some(5210));
+        assert_eq!(center_freq(5300, ChannelWidth::Width160), Some(5250));
+        assert_eq!(center_freq(5765, ChannelWidth::Width80), Some(5775));
+        assert_eq!(center_freq(5975, ChannelWidth::Width160), Some(6025));
+        assert_eq!(center_freq(6135, ChannelWidth::Width320), Some(6105));
+        assert_eq!(center_freq(5190, ChannelWidth::Width40), None);
+    }
+
+    #[test]
+    fn test_band_of_freq() {
+        assert_eq!(band_of_freq(2412), Some(FrequencyBand::Band2G));
+        assert_eq!(band_of_freq(2484), Some(FrequencyBand::Band2G));
+        assert_eq!(band_of_freq(5180), Some(FrequencyBand::Band5G));
+        assert_eq!(band_of_freq(5920), Some(FrequencyBand::Band5G));
+        assert_eq!(band_of_freq(5925), Some(FrequencyBand::Band6G));
+        assert_eq!(band_of_freq(5955), Some(FrequencyBand::Band6G));
+        assert_eq!(band_of_freq(58320), Some(FrequencyBand::Band60G));
+        assert_eq!(band_of_freq(3000), None);
+    }
+
+    #[test]
+    fn test_channel_of_freq() {
+        assert_eq!(channel_of_freq(2412), Some(1));
+        assert_eq!(channel_of_freq(2484), Some(14));
+        assert_eq!(channel_of_freq(5180), Some(36));
+        assert_eq!(channel_of_freq(5935), Some(2));
+        assert_eq!(channel_of_freq(5955), Some(1));
+        assert_eq!(channel_of_freq(60480), Some(2));
+        assert_eq!(channel_of_freq(3000), None);
+    }
+
+    #[test]
+    fn test_freq_of_channel() {
+        assert_eq!(freq_of_channel(1, FrequencyBand::Band2G), Some(2412));
+        assert_eq!(freq_of_channel(13, FrequencyBand::Band2G), Some(2472));
+        assert_eq!(freq_of_channel(14, FrequencyBand::Band2G), Some(2484));
+        assert_eq!(freq_of_channel(15, FrequencyBand::Band2G), None);
+        assert_eq!(freq_of_channel(36, FrequencyBand::Band5G), Some(5180));
+        assert_eq!(freq_of_channel(184, FrequencyBand::Band5G), Some(4920));
+        assert_eq!(freq_of_channel(2, FrequencyBand::Band6G), Some(5935));
+        assert_eq!(freq_of_channel(1, FrequencyBand::Band6G), Some(5955));
+        assert_eq!(freq_of_channel(233, FrequencyBand::Band6G), Some(7115));
+        assert_eq!(freq_of_channel(2, FrequencyBand::Band60G), Some(60480));
+        assert_eq!(freq_of_channel(0, FrequencyBand::Band5G), None);
+
+        for freq in [
+            2412, 2437, 2484, 4940, 5180, 5825, 5935, 5975, 7115, 58320, 69120,
+        ] {
+            let band = band_of_freq(freq).unwrap();
+            let channel = channel_of_freq(freq).unwrap();
+            assert_eq!(freq_of_channel(channel, band), Some(freq));
+        }
+    }
+
+    #[test]
+    fn test_invalid_channel() {
+        let mut attrs = GenlBuffer::new();
+        assert!(push_channel_attrs(&mut attrs, 2484, ChannelWidth::Width40).is_err());
+        assert!(attrs.is_empty());
+    }
+}
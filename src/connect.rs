@@ -0,0 +1,523 @@
+use crate::attr::Nl80211Attr;
+use crate::cmd::Nl80211Cmd;
+use crate::error::{Error, ENOTCONN};
+use crate::mac::MacAddress;
+use crate::security::{AkmSuite, CipherSuite};
+use crate::wiphy::Wiphy;
+
+use neli::err::SerError;
+use neli::genl::{Genlmsghdr, Nlattr};
+use neli::types::{Buffer, GenlBuffer};
+
+use std::fmt;
+
+/// Parameters of a connect/associate request
+///
+/// Only the parameters which were explicitly set are included in the request, so an empty
+/// `ConnectParams` leaves everything up to the kernel and the driver.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ConnectParams {
+    ies: Option<Vec<u8>>,
+    pmk: Option<Vec<u8>>,
+    pmkid: Option<Vec<u8>>,
+    akm_suite: Option<AkmSuite>,
+    bssid: Option<MacAddress>,
+    freq: Option<u32>,
+    wpa_versions: Option<u32>,
+    ciphers: Option<(CipherSuite, CipherSuite)>,
+}
+
+/// Length of a PMK in bytes
+const PMK_LEN: usize = 32;
+/// Length of a PMK in bytes for the AKM suites deriving it with SHA-384
+const PMK_LEN_SHA384: usize = 48;
+/// Length of a PMKID in bytes
+const PMKID_LEN: usize = 16;
+
+impl ConnectParams {
+    /// Create empty connect parameters
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append information elements to the (re)association request
+    ///
+    /// `bytes` must be a sequence of complete elements, each encoded as in the frame body (ID,
+    /// length, data), e.g. the mobility domain element for FT or the Diffie-Hellman parameter
+    /// element for OWE. The bytes are passed to the kernel verbatim, elements generated by the
+    /// kernel or driver itself must not be repeated. Calling this again replaces earlier IEs.
+    pub fn ies(&mut self, bytes: &[u8]) -> &mut Self {
+        self.ies = Some(bytes.to_vec());
+        self
+    }
+
+    /// Use the given PMK (pairwise master key) for the connection, e.g. for FT over DS
+    ///
+    /// With WPA-PSK the PMK is the PSK derived from the passphrase, for drivers which perform
+    /// the 4-way handshake themselves. The PMK must be 32 bytes long, or 48 bytes with
+    /// [`AkmSuite::SuiteB192`] which derives it with SHA-384, otherwise the connect request
+    /// fails before it is sent.
+    pub fn pmk(&mut self, pmk: &[u8]) -> &mut Self {
+        self.pmk = Some(pmk.to_vec());
+        self
+    }
+
+    /// Use the given PMKID to identify the PMK of the connection
+    ///
+    /// The PMKID must be 16 bytes long, otherwise the connect request fails before it is sent.
+    pub fn pmkid(&mut self, pmkid: &[u8]) -> &mut Self {
+        self.pmkid = Some(pmkid.to_vec());
+        self
+    }
+
+    /// Connect to the BSS with the given BSSID only, instead of any BSS of the network
+    pub fn bssid(&mut self, bssid: MacAddress) -> &mut Self {
+        self.bssid = Some(bssid);
+        self
+    }
+
+    /// Connect on the channel with the given frequency (MHz) only
+    pub fn freq(&mut self, freq: u32) -> &mut Self {
+        self.freq = Some(freq);
+        self
+    }
+
+    /// Connect with the given WPA versions
+    ///
+    /// `versions` is a bitmap of the versions the kernel may use (bit 0: WPA, bit 1: WPA2, bit
+    /// 2: WPA3). Like [`ConnectParams::ciphers`] this marks the connection as protected.
+    pub fn wpa_versions(&mut self, versions: u32) -> &mut Self {
+        self.wpa_versions = Some(versions & 0x7);
+        self
+    }
+
+    /// Connect with the given pairwise and group cipher suites, e.g. CCMP for both with WPA2
+    pub fn ciphers(&mut self, pairwise: CipherSuite, group: CipherSuite) -> &mut Self {
+        self.ciphers = Some((pairwise, group));
+        self
+    }
+
+    /// Connect with the given AKM suite, e.g. SAE for WPA3-Personal
+    ///
+    /// Without an AKM suite the kernel or driver picks one the AP offers. An AKM suite the wiphy
+    /// of the interface doesn't support (see [`Wiphy::supports_akm`]) fails the connect request
+    /// with [`Error::Unsupported`] before it is sent, rather than a connection attempt the
+    /// driver can't complete.
+    pub fn akm_suite(&mut self, akm: AkmSuite) -> &mut Self {
+        self.akm_suite = Some(akm);
+        self
+    }
+
+    /// Check that the wiphy the connection is made on supports the requested parameters
+    pub(crate) fn check(&self, wiphy: &Wiphy) -> Result<(), Error> {
+        match self.akm_suite {
+            Some(akm) if !wiphy.supports_akm(akm) => Err(Error::Unsupported),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether the parameters need to be checked with [`ConnectParams::check`]
+    pub(crate) fn needs_check(&self) -> bool {
+        self.akm_suite.is_some()
+    }
+
+    pub(crate) fn attrs(&self) -> Result<GenlBuffer<Nl80211Attr, Buffer>, SerError> {
+        let mut attrs = GenlBuffer::new();
+        if let Some(ies) = &self.ies {
+            attrs.push(Nlattr::new(
+                false,
+                false,
+                Nl80211Attr::AttrIe,
+                ies.as_slice(),
+            )?);
+        }
+        if let Some(pmk) = &self.pmk {
+            let len = match self.akm_suite {
+                Some(AkmSuite::SuiteB192) => PMK_LEN_SHA384,
+                _ => PMK_LEN,
+            };
+            if pmk.len() != len {
+                return Err(SerError::new(format!(
+                    "PMK must be {} bytes long with AKM suite {:?}, got {}",
+                    len,
+                    self.akm_suite,
+                    pmk.len()
+                )));
+            }
+            attrs.push(Nlattr::new(
+                false,
+                false,
+                Nl80211Attr::AttrPmk,
+                pmk.as_slice(),
+            )?);
+        }
+        if let Some(pmkid) = &self.pmkid {
+            if pmkid.len() != PMKID_LEN {
+                return Err(SerError::new(format!(
+                    "PMKID must be {} bytes long, got {}",
+                    PMKID_LEN,
+                    pmkid.len()
+                )));
+            }
+            attrs.push(Nlattr::new(
+                false,
+                false,
+                Nl80211Attr::AttrPmkid,
+                pmkid.as_slice(),
+            )?);
+        }
+        if let Some(akm) = self.akm_suite {
+            attrs.push(Nlattr::new(
+                false,
+                false,
+                Nl80211Attr::AttrAkmSuites,
+                &u32::from(akm).to_ne_bytes()[..],
+            )?);
+        }
+        if let Some(bssid) = self.bssid {
+            attrs.push(Nlattr::new(
+                false,
+                false,
+                Nl80211Attr::AttrMac,
+                &bssid.0[..],
+            )?);
+        }
+        if let Some(freq) = self.freq {
+            attrs.push(Nlattr::new(false, false, Nl80211Attr::AttrWiphyFreq, freq)?);
+        }
+        if let Some(versions) = self.wpa_versions {
+            attrs.push(Nlattr::new(
+                false,
+                false,
+                Nl80211Attr::AttrWpaVersions,
+                versions,
+            )?);
+        }
+        if let Some((pairwise, group)) = self.ciphers {
+            attrs.push(Nlattr::new(
+                false,
+                false,
+                Nl80211Attr::AttrCipherSuitesPairwise,
+                &u32::from(pairwise).to_ne_bytes()[..],
+            )?);
+            attrs.push(Nlattr::new(
+                false,
+                false,
+                Nl80211Attr::AttrCipherSuiteGroup,
+                u32::from(group),
+            )?);
+        }
+        if self.wpa_versions.is_some() || self.ciphers.is_some() {
+            attrs.push(Nlattr::new(false, false, Nl80211Attr::AttrPrivacy, ())?);
+        }
+        Ok(attrs)
+    }
+}
+
+/// IEEE 802.11 status code with which an AP answered an authentication or association
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum StatusCode {
+    Success,
+    UnspecifiedFailure,
+    CapabilitiesUnsupported,
+    AssocDeniedUnspecified,
+    AuthAlgorithmUnsupported,
+    ChallengeFailure,
+    AuthTimeout,
+    /// The AP can't handle any more associated stations
+    ApFull,
+    BasicRatesUnsupported,
+    /// The association was rejected for now, it may be retried after the comeback time
+    RejectedTemporarily,
+    RobustMgmtPolicyViolation,
+    InvalidIe,
+    InvalidGroupCipher,
+    InvalidPairwiseCipher,
+    InvalidAkmp,
+    CipherRejectedPerPolicy,
+    InvalidPmkid,
+    /// Any other status code
+    Other(u16),
+}
+
+impl From<u16> for StatusCode {
+    fn from(code: u16) -> Self {
+        match code {
+            0 => Self::Success,
+            1 => Self::UnspecifiedFailure,
+            10 => Self::CapabilitiesUnsupported,
+            12 => Self::AssocDeniedUnspecified,
+            13 => Self::AuthAlgorithmUnsupported,
+            15 => Self::ChallengeFailure,
+            16 => Self::AuthTimeout,
+            17 => Self::ApFull,
+            18 => Self::BasicRatesUnsupported,
+            30 => Self::RejectedTemporarily,
+            31 => Self::RobustMgmtPolicyViolation,
+            40 => Self::InvalidIe,
+            41 => Self::InvalidGroupCipher,
+            42 => Self::InvalidPairwiseCipher,
+            43 => Self::InvalidAkmp,
+            46 => Self::CipherRejectedPerPolicy,
+            53 => Self::InvalidPmkid,
+            code => Self::Other(code),
+        }
+    }
+}
+
+impl fmt::Display for StatusCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self {
+            Self::Success => "success",
+            Self::UnspecifiedFailure => "unspecified failure",
+            Self::CapabilitiesUnsupported => "capabilities not supported",
+            Self::AssocDeniedUnspecified => "association denied",
+            Self::AuthAlgorithmUnsupported => "authentication algorithm not supported",
+            Self::ChallengeFailure => "authentication challenge failed",
+            Self::AuthTimeout => "authentication timed out",
+            Self::ApFull => "capacity full",
+            Self::BasicRatesUnsupported => "basic rates not supported",
+            Self::RejectedTemporarily => "rejected temporarily",
+            Self::RobustMgmtPolicyViolation => "management frame protection policy violated",
+            Self::InvalidIe => "invalid element",
+            Self::InvalidGroupCipher => "invalid group cipher",
+            Self::InvalidPairwiseCipher => "invalid pairwise cipher",
+            Self::InvalidAkmp => "invalid AKM",
+            Self::CipherRejectedPerPolicy => "cipher rejected by policy",
+            Self::InvalidPmkid => "invalid PMKID",
+            Self::Other(code) => return write!(f, "status code {}", code),
+        };
+        f.write_str(reason)
+    }
+}
+
+/// The outcome of a connection attempt on `interface_index` if `event` from the "mlme" multicast
+/// group reports it
+pub(crate) fn connect_outcome(
+    event: &Genlmsghdr<Nl80211Cmd, Nl80211Attr>,
+    interface_index: i32,
+) -> Option<Result<(), Error>> {
+    if event.cmd != Nl80211Cmd::CmdConnect {
+        return None;
+    }
+    let attrs = event.get_attr_handle();
+    let ifindex = attrs
+        .get_attr_payload_as::<i32>(Nl80211Attr::AttrIfindex)
+        .ok()?;
+    if ifindex != interface_index {
+        return None;
+    }
+    // Timeouts without any answer of the AP are reported as unspecified failures
+    let status = attrs
+        .get_attr_payload_as::<u16>(Nl80211Attr::AttrStatusCode)
+        .map_or(StatusCode::UnspecifiedFailure, StatusCode::from);
+    Some(match status {
+        StatusCode::Success => Ok(()),
+        status => Err(Error::ConnectFailed(status)),
+    })
+}
+
+/// The result of a disconnect request, for which not being connected (`ENOTCONN`) is success
+pub(crate) fn disconnect_result(result: Result<(), Error>) -> Result<(), Error> {
+    match result {
+        Err(Error::KernelErrno(ENOTCONN)) => Ok(()),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod test_connect {
+    use super::*;
+    use crate::error::ENODEV;
+
+    #[test]
+    fn test_empty() {
+        assert!(ConnectParams::new().attrs().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_ies() {
+        // Mobility domain element followed by a vendor specific element
+        let ies = [54, 3, 0x12, 0x34, 0x01, 221, 4, 0x00, 0x50, 0xf2, 0x02];
+        let attrs = ConnectParams::new().ies(&ies).attrs().unwrap();
+        let handle = attrs.get_attr_handle();
+
+        assert_eq!(attrs.len(), 1);
+        assert_eq!(
+            handle
+                .get_attribute(Nl80211Attr::AttrIe)
+                .unwrap()
+                .nla_payload
+                .as_ref(),
+            &ies
+        );
+    }
+
+    #[test]
+    fn test_pmk() {
+        let attrs = ConnectParams::new()
+            .pmk(&[0xaa; 32])
+            .pmkid(&[0x55; 16])
+            .attrs()
+            .unwrap();
+        let handle = attrs.get_attr_handle();
+
+        assert_eq!(
+            handle
+                .get_attribute(Nl80211Attr::AttrPmk)
+                .unwrap()
+                .nla_payload
+                .as_ref(),
+            &[0xaa; 32]
+        );
+        assert_eq!(
+            handle
+                .get_attribute(Nl80211Attr::AttrPmkid)
+                .unwrap()
+                .nla_payload
+                .as_ref(),
+            &[0x55; 16]
+        );
+
+        let attrs = ConnectParams::new()
+            .akm_suite(AkmSuite::SuiteB192)
+            .pmk(&[0xaa; 48])
+            .attrs()
+            .unwrap();
+        assert_eq!(
+            attrs
+                .get_attr_handle()
+                .get_attribute(Nl80211Attr::AttrPmk)
+                .unwrap()
+                .nla_payload
+                .as_ref(),
+            &[0xaa; 48]
+        );
+
+        assert!(ConnectParams::new().pmk(&[0; 16]).attrs().is_err());
+        assert!(ConnectParams::new().pmk(&[0; 48]).attrs().is_err());
+        assert!(ConnectParams::new()
+            .akm_suite(AkmSuite::SuiteB192)
+            .pmk(&[0; 32])
+            .attrs()
+            .is_err());
+        assert!(ConnectParams::new().pmkid(&[0; 32]).attrs().is_err());
+    }
+
+    #[test]
+    fn test_wpa2() {
+        let attrs = ConnectParams::new()
+            .bssid(MacAddress([2, 0, 0, 0, 0, 1]))
+            .freq(5180)
+            .wpa_versions(2)
+            .ciphers(CipherSuite::Ccmp, CipherSuite::Ccmp)
+            .attrs()
+            .unwrap();
+        let handle = attrs.get_attr_handle();
+        let u32_attr = |attr| handle.get_attr_payload_as::<u32>(attr).unwrap();
+
+        assert_eq!(
+            handle
+                .get_attribute(Nl80211Attr::AttrMac)
+                .unwrap()
+                .nla_payload
+                .as_ref(),
+            &[2, 0, 0, 0, 0, 1]
+        );
+        assert_eq!(u32_attr(Nl80211Attr::AttrWiphyFreq), 5180);
+        assert_eq!(u32_attr(Nl80211Attr::AttrWpaVersions), 2);
+        assert_eq!(u32_attr(Nl80211Attr::AttrCipherSuitesPairwise), 0x000fac04);
+        assert_eq!(u32_attr(Nl80211Attr::AttrCipherSuiteGroup), 0x000fac04);
+        assert!(handle.get_attribute(Nl80211Attr::AttrPrivacy).is_some());
+
+        // Only protected connections set the privacy flag
+        let attrs = ConnectParams::new().freq(2412).attrs().unwrap();
+        assert!(attrs
+            .get_attr_handle()
+            .get_attribute(Nl80211Attr::AttrPrivacy)
+            .is_none());
+    }
+
+    #[test]
+    fn test_akm_suite() {
+        let attrs = ConnectParams::new()
+            .akm_suite(AkmSuite::Sae)
+            .attrs()
+            .unwrap();
+        assert_eq!(
+            attrs
+                .get_attr_handle()
+                .get_attr_payload_as::<u32>(Nl80211Attr::AttrAkmSuites)
+                .unwrap(),
+            0x000fac08
+        );
+
+        let psk_only = Wiphy {
+            akm_suites: vec![AkmSuite::Psk],
+            ..Default::default()
+        };
+        assert!(!ConnectParams::new().needs_check());
+        assert!(ConnectParams::new().check(&psk_only).is_ok());
+        let sae = ConnectParams::new().akm_suite(AkmSuite::Sae).clone();
+        assert!(sae.needs_check());
+        assert!(matches!(sae.check(&psk_only), Err(Error::Unsupported)));
+        let psk = ConnectParams::new().akm_suite(AkmSuite::Psk).clone();
+        assert!(psk.check(&psk_only).is_ok());
+        // Wiphys without a list of AKM suites leave the check to the kernel
+        assert!(sae.check(&Wiphy::default()).is_ok());
+    }
+
+    #[test]
+    fn test_disconnect_result() {
+        assert!(disconnect_result(Ok(())).is_ok());
+        assert!(disconnect_result(Err(Error::KernelErrno(ENOTCONN))).is_ok());
+        assert!(matches!(
+            disconnect_result(Err(Error::KernelErrno(ENODEV))),
+            Err(Error::KernelErrno(ENODEV))
+        ));
+        assert!(matches!(
+            disconnect_result(Err(Error::Unsupported)),
+            Err(Error::Unsupported)
+        ));
+    }
+
+    #[test]
+    fn test_connect_outcome() {
+        let event = |cmd, ifindex: i32, status: Option<u16>| {
+            let mut attrs = GenlBuffer::new();
+            attrs.push(Nlattr::new(false, false, Nl80211Attr::AttrIfindex, ifindex).unwrap());
+            if let Some(status) = status {
+                attrs.push(Nlattr::new(false, false, Nl80211Attr::AttrStatusCode, status).unwrap());
+            }
+            Genlmsghdr::new(cmd, 1, attrs)
+        };
+
+        assert!(matches!(
+            connect_outcome(&event(Nl80211Cmd::CmdConnect, 3, Some(0)), 3),
+            Some(Ok(()))
+        ));
+        let outcome = connect_outcome(&event(Nl80211Cmd::CmdConnect, 3, Some(17)), 3);
+        let Some(Err(err)) = outcome else {
+            panic!("unexpected {:?}", outcome);
+        };
+        assert!(matches!(err, Error::ConnectFailed(StatusCode::ApFull)));
+        assert_eq!(err.to_string(), "AP rejected the connection: capacity full");
+        assert!(matches!(
+            connect_outcome(&event(Nl80211Cmd::CmdConnect, 3, None), 3),
+            Some(Err(Error::ConnectFailed(StatusCode::UnspecifiedFailure)))
+        ));
+
+        assert!(connect_outcome(&event(Nl80211Cmd::CmdConnect, 4, Some(0)), 3).is_none());
+        assert!(connect_outcome(&event(Nl80211Cmd::CmdDisconnect, 3, None), 3).is_none());
+    }
+
+    #[test]
+    fn test_status_code() {
+        assert_eq!(StatusCode::from(17), StatusCode::ApFull);
+        assert_eq!(StatusCode::from(30), StatusCode::RejectedTemporarily);
+        assert_eq!(StatusCode::from(77), StatusCode::Other(77));
+        assert_eq!(StatusCode::Other(77).to_string(), "status code 77");
+    }
+}
@@ -0,0 +1,144 @@
+use crate::attr::Nl80211Attr;
+
+use neli::err::SerError;
+use neli::genl::Nlattr;
+use neli::types::{Buffer, GenlBuffer};
+
+/// The beacons an AP sends during and after a channel switch announcement (CSA)
+///
+/// Beacons are given as head and tail, the parts before and after the TIM element which the
+/// kernel inserts, cf. hostapd. Interfaces which don't send beacons (e.g. stations) switch
+/// channels with an empty `CsaIes`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CsaIes {
+    csa_beacon: Option<(Vec<u8>, Vec<u8>, Vec<u16>)>,
+    beacon_after: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+impl CsaIes {
+    /// Create empty channel switch beacons
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the beacon to send while counting down to the switch
+    ///
+    /// The tail must contain the channel switch announcement element, `counter_offsets` are the
+    /// offsets of the countdown fields of this (and the extended CSA) element in `tail`. The
+    /// kernel decrements them with every beacon.
+    pub fn csa_beacon(&mut self, head: &[u8], tail: &[u8], counter_offsets: &[u16]) -> &mut Self {
+        self.csa_beacon = Some((head.to_vec(), tail.to_vec(), counter_offsets.to_vec()));
+        self
+    }
+
+    /// Set the beacon to send on the new channel once the switch is complete
+    pub fn beacon_after(&mut self, head: &[u8], tail: &[u8]) -> &mut Self {
+        self.beacon_after = Some((head.to_vec(), tail.to_vec()));
+        self
+    }
+
+    pub(crate) fn attrs(&self) -> Result<GenlBuffer<Nl80211Attr, Buffer>, SerError> {
+        let mut attrs = GenlBuffer::new();
+        if let Some((head, tail)) = &self.beacon_after {
+            attrs.push(Nlattr::new(
+                false,
+                false,
+                Nl80211Attr::AttrBeaconHead,
+                head.as_slice(),
+            )?);
+            attrs.push(Nlattr::new(
+                false,
+                false,
+                Nl80211Attr::AttrBeaconTail,
+                tail.as_slice(),
+            )?);
+        }
+        if let Some((head, tail, counter_offsets)) = &self.csa_beacon {
+            if let Some(offset) = counter_offsets
+                .iter()
+                .find(|&&offset| usize::from(offset) >= tail.len())
+            {
+                return Err(SerError::new(format!(
+                    "CSA counter offset {} outside of the {} byte beacon tail",
+                    offset,
+                    tail.len()
+                )));
+            }
+            let offsets: Vec<u8> = counter_offsets
+                .iter()
+                .flat_map(|offset| offset.to_ne_bytes())
+                .collect();
+
+            let mut csa_ies = Nlattr::new(true, false, Nl80211Attr::AttrCsaIes, Vec::<u8>::new())?;
+            csa_ies.add_nested_attribute(&Nlattr::new(
+                false,
+                false,
+                Nl80211Attr::AttrBeaconHead,
+                head.as_slice(),
+            )?)?;
+            csa_ies.add_nested_attribute(&Nlattr::new(
+                false,
+                false,
+                Nl80211Attr::AttrBeaconTail,
+                tail.as_slice(),
+            )?)?;
+            csa_ies.add_nested_attribute(&Nlattr::new(
+                false,
+                false,
+                Nl80211Attr::AttrCsaCOffBeacon,
+                offsets,
+            )?)?;
+            attrs.push(csa_ies);
+        }
+        Ok(attrs)
+    }
+}
+
+#[cfg(test)]
+mod test_csa {
+    use super::*;
+
+    #[test]
+    fn test_empty() {
+        assert!(CsaIes::new().attrs().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_beacons() {
+        let attrs = CsaIes::new()
+            .csa_beacon(&[0x80, 0], &[37, 3, 1, 36, 5], &[4])
+            .beacon_after(&[0x80, 0], &[])
+            .attrs()
+            .unwrap();
+        let handle = attrs.get_attr_handle();
+
+        assert!(handle.get_attribute(Nl80211Attr::AttrBeaconHead).is_some());
+        let csa_ies = handle
+            .get_attribute(Nl80211Attr::AttrCsaIes)
+            .unwrap()
+            .get_attr_handle::<Nl80211Attr>()
+            .unwrap();
+        assert_eq!(
+            csa_ies
+                .get_attribute(Nl80211Attr::AttrBeaconTail)
+                .unwrap()
+                .nla_payload
+                .as_ref(),
+            &[37, 3, 1, 36, 5]
+        );
+        assert_eq!(
+            csa_ies
+                .get_attr_payload_as::<u16>(Nl80211Attr::AttrCsaCOffBeacon)
+                .unwrap(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_counter_outside_tail() {
+        let err = CsaIes::new()
+            .csa_beacon(&[0x80, 0], &[37, 3, 1, 36, 5], &[5])
+            .attrs();
+        assert!(err.is_err());
+    }
+}
@@ -0,0 +1,211 @@
+use crate::connect::StatusCode;
+
+use neli::err::{DeError, NlError, SerError};
+use neli::nl::NlPayload;
+
+use std::fmt;
+use std::io;
+
+// Kernel error codes (Linux, all architectures this crate runs on)
+pub(crate) const EPERM: i32 = 1;
+pub(crate) const ENOENT: i32 = 2;
+pub(crate) const EINTR: i32 = 4;
+pub(crate) const EAGAIN: i32 = 11;
+pub(crate) const EACCES: i32 = 13;
+pub(crate) const EBUSY: i32 = 16;
+pub(crate) const EEXIST: i32 = 17;
+pub(crate) const ENODEV: i32 = 19;
+pub(crate) const EINVAL: i32 = 22;
+pub(crate) const EOPNOTSUPP: i32 = 95;
+pub(crate) const ENOTCONN: i32 = 107;
+pub(crate) const ETIMEDOUT: i32 = 110;
+
+/// Errors of nl80211 requests
+#[derive(Debug)]
+pub enum Error {
+    /// Communication over the netlink socket failed
+    Netlink(NlError),
+    /// A message from the kernel could not be decoded
+    Deserialize(DeError),
+    /// The kernel rejected a request with the given error code (errno)
+    KernelErrno(i32),
+    /// The request is not supported by the kernel, driver or device
+    Unsupported,
+    /// The kernel aborted a scan before it completed
+    ScanAborted,
+    /// The AP rejected the authentication or association, or didn't answer at all
+    ConnectFailed(StatusCode),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Netlink(err) => write!(f, "netlink error: {}", err),
+            Error::Deserialize(err) => write!(f, "failed to decode nl80211 message: {}", err),
+            Error::KernelErrno(errno) => write!(f, "{}", io::Error::from_raw_os_error(*errno)),
+            Error::Unsupported => write!(f, "operation not supported"),
+            Error::ScanAborted => write!(f, "scan aborted"),
+            Error::ConnectFailed(status) => write!(f, "AP rejected the connection: {}", status),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Netlink(err) => Some(err),
+            Error::Deserialize(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl<T, P> From<NlError<T, P>> for Error
+where
+    T: fmt::Debug,
+    P: fmt::Debug,
+{
+    /// Converts errors of any message type, kernel errors keep only their errno
+    fn from(err: NlError<T, P>) -> Self {
+        match err {
+            NlError::Nlmsgerr(err) => Error::from_errno(-err.error),
+            NlError::De(err) => Error::Deserialize(err),
+            NlError::Ser(err) => Error::Netlink(NlError::Ser(err)),
+            NlError::Wrapped(err) => Error::Netlink(NlError::Wrapped(err)),
+            err => Error::Netlink(NlError::new(err.to_string())),
+        }
+    }
+}
+
+impl From<SerError> for Error {
+    fn from(err: SerError) -> Self {
+        Error::Netlink(err.into())
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Netlink(err.into())
+    }
+}
+
+impl From<DeError> for Error {
+    fn from(err: DeError) -> Self {
+        Error::Deserialize(err)
+    }
+}
+
+impl Error {
+    /// Error for a (positive) errno reported by the kernel
+    pub(crate) fn from_errno(errno: i32) -> Self {
+        match errno {
+            EOPNOTSUPP => Error::Unsupported,
+            errno => Error::KernelErrno(errno),
+        }
+    }
+
+    /// Whether the request may succeed if it is simply sent again later
+    ///
+    /// This is the case if the device was busy, e.g. with a scan already in progress, if the
+    /// request was interrupted or if the AP rejected a connection only temporarily.
+    pub fn is_retriable(&self) -> bool {
+        matches!(
+            self,
+            Error::KernelErrno(EBUSY | EAGAIN | EINTR)
+                | Error::ScanAborted
+                | Error::ConnectFailed(StatusCode::RejectedTemporarily)
+        )
+    }
+}
+
+/// The error carried by the payload of an `Nlmsg::Error` response, `None` for acks
+pub(crate) fn response_error<T, P>(payload: NlPayload<T, P>) -> Option<Error> {
+    match payload {
+        NlPayload::Err(err) => Some(Error::from_errno(-err.error)),
+        _ => None,
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        let kind = match err {
+            Error::KernelErrno(EPERM | EACCES) => io::ErrorKind::PermissionDenied,
+            Error::KernelErrno(ENOENT | ENODEV) => io::ErrorKind::NotFound,
+            Error::KernelErrno(EINTR) => io::ErrorKind::Interrupted,
+            Error::KernelErrno(EAGAIN) => io::ErrorKind::WouldBlock,
+            Error::KernelErrno(EBUSY) => io::ErrorKind::ResourceBusy,
+            Error::KernelErrno(ENOTCONN) => io::ErrorKind::NotConnected,
+            Error::KernelErrno(EEXIST) => io::ErrorKind::AlreadyExists,
+            Error::KernelErrno(EINVAL) => io::ErrorKind::InvalidInput,
+            Error::KernelErrno(ETIMEDOUT) => io::ErrorKind::TimedOut,
+            Error::Unsupported => io::ErrorKind::Unsupported,
+            Error::ScanAborted => io::ErrorKind::Interrupted,
+            Error::ConnectFailed(_) => io::ErrorKind::ConnectionRefused,
+            Error::Deserialize(_) => io::ErrorKind::InvalidData,
+            _ => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, err)
+    }
+}
+
+#[cfg(test)]
+mod test_error {
+    use super::*;
+    use neli::err::{Nlmsgerr, NlmsghdrErr};
+
+    #[test]
+    fn test_io_error_kind() {
+        let kind = |err: Error| io::Error::from(err).kind();
+
+        assert_eq!(
+            kind(Error::KernelErrno(EPERM)),
+            io::ErrorKind::PermissionDenied
+        );
+        assert_eq!(kind(Error::KernelErrno(ENODEV)), io::ErrorKind::NotFound);
+        assert_eq!(kind(Error::KernelErrno(ETIMEDOUT)), io::ErrorKind::TimedOut);
+        assert_eq!(
+            kind(Error::KernelErrno(ENOTCONN)),
+            io::ErrorKind::NotConnected
+        );
+        assert_eq!(kind(Error::Unsupported), io::ErrorKind::Unsupported);
+        // EDOM, nothing nl80211 would report
+        assert_eq!(kind(Error::KernelErrno(33)), io::ErrorKind::Other);
+    }
+
+    #[test]
+    fn test_from_typed_error() {
+        let err = NlError::<u16, u32>::Nlmsgerr(Nlmsgerr {
+            error: -EBUSY,
+            nlmsg: NlmsghdrErr {
+                nl_len: 20,
+                nl_type: 28,
+                nl_flags: neli::consts::nl::NlmFFlags::empty(),
+                nl_seq: 1,
+                nl_pid: 0,
+                nl_payload: 0,
+            },
+        });
+
+        assert!(matches!(Error::from(err), Error::KernelErrno(EBUSY)));
+    }
+
+    #[test]
+    fn test_retriable() {
+        assert!(Error::KernelErrno(EBUSY).is_retriable());
+        assert!(Error::ScanAborted.is_retriable());
+        assert!(Error::ConnectFailed(StatusCode::RejectedTemporarily).is_retriable());
+        assert!(!Error::ConnectFailed(StatusCode::ApFull).is_retriable());
+        assert!(!Error::KernelErrno(EPERM).is_retriable());
+        assert!(!Error::Unsupported.is_retriable());
+    }
+
+    #[test]
+    fn test_from_errno() {
+        assert!(matches!(Error::from_errno(EOPNOTSUPP), Error::Unsupported));
+        assert!(matches!(Error::from_errno(EBUSY), Error::KernelErrno(16)));
+        assert_eq!(
+            Error::KernelErrno(EPERM).to_string(),
+            io::Error::from_raw_os_error(EPERM).to_string()
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use crate::attr::Nl80211Attr;
+use crate::cmd::Nl80211Cmd;
+use crate::connect::StatusCode;
+use crate::interface::Interface;
+use crate::mac::MacAddress;
+
+use neli::err::DeError;
+use neli::genl::Genlmsghdr;
+
+/// The nl80211 multicast groups carrying [`WifiEvent`]s
+///
+/// The "vendor" group is only offered by kernels and drivers with vendor command support.
+pub(crate) const EVENT_GROUPS: [&str; 5] = ["config", "scan", "mlme", "regulatory", "vendor"];
+
+/// An nl80211 multicast group, cf. [`Socket::subscribe_events`](crate::Socket::subscribe_events)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum McastGroup {
+    /// Creation, changes and removal of interfaces
+    Config,
+    /// Scans of any interface
+    Scan,
+    /// Authentication, association, connections and disconnections
+    Mlme,
+    /// Changes of the regulatory domain
+    Regulatory,
+    /// Vendor specific events, only offered by kernels and drivers with vendor command support
+    Vendor,
+}
+
+impl McastGroup {
+    /// The name nl80211 offers the group under
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Config => "config",
+            Self::Scan => "scan",
+            Self::Mlme => "mlme",
+            Self::Regulatory => "regulatory",
+            Self::Vendor => "vendor",
+        }
+    }
+}
+
+/// An event reported by the kernel on one of the nl80211 multicast groups
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WifiEvent {
+    /// An interface was created or changed ("config" group)
+    NewInterface(Interface),
+    /// An interface was removed ("config" group)
+    DelInterface(Interface),
+    /// A scan was started on an interface ("scan" group)
+    ScanStarted { interface_index: i32 },
+    /// A scan finished and its results can be dumped ("scan" group)
+    ScanResults { interface_index: i32 },
+    /// A scan was aborted ("scan" group)
+    ScanAborted { interface_index: i32 },
+    /// The outcome of a connection attempt ("mlme" group)
+    Connect {
+        interface_index: i32,
+        bssid: Option<MacAddress>,
+        status: Option<StatusCode>,
+    },
+    /// An interface was disconnected ("mlme" group)
+    Disconnect {
+        interface_index: i32,
+        reason: Option<u16>,
+    },
+    /// The regulatory domain changed, globally or of a single wiphy ("regulatory" group)
+    RegChange { alpha2: Option<String> },
+    /// A vendor specific event ("vendor" group)
+    Vendor {
+        vendor_id: u32,
+        subcmd: u32,
+        data: Vec<u8>,
+    },
+    /// Any other event, with its interface if it refers to one
+    Other {
+        cmd: Nl80211Cmd,
+        interface_index: Option<i32>,
+    },
+}
+
+impl TryFrom<&Genlmsghdr<Nl80211Cmd, Nl80211Attr>> for WifiEvent {
+    type Error = DeError;
+
+    fn try_from(event: &Genlmsghdr<Nl80211Cmd, Nl80211Attr>) -> Result<Self, Self::Error> {
+        let attrs = event.get_attr_handle();
+        let interface_index = attrs
+            .get_attr_payload_as::<i32>(Nl80211Attr::AttrIfindex)
+            .ok();
+        let u16_attr = |attr| attrs.get_attr_payload_as::<u16>(attr).ok();
+
+        Ok(match (event.cmd, interface_index) {
+            (Nl80211Cmd::CmdNewInterface, _) => Self::NewInterface(attrs.try_into()?),
+            (Nl80211Cmd::CmdDelInterface, _) => Self::DelInterface(attrs.try_into()?),
+            (Nl80211Cmd::CmdTriggerScan, Some(interface_index)) => {
+                Self::ScanStarted { interface_index }
+            }
+            (Nl80211Cmd::CmdNewScanResults, Some(interface_index)) => {
+                Self::ScanResults { interface_index }
+            }
+            (Nl80211Cmd::CmdScanAborted, Some(interface_index)) => {
+                Self::ScanAborted { interface_index }
+            }
+            (Nl80211Cmd::CmdConnect, Some(interface_index)) => Self::Connect {
+                interface_index,
+                bssid: attrs
+                    .get_attribute(Nl80211Attr::AttrMac)
+                    .map(|mac| mac.nla_payload.as_ref().try_into())
+                    .transpose()?,
+                status: u16_attr(Nl80211Attr::AttrStatusCode).map(StatusCode::from),
+            },
+            (Nl80211Cmd::CmdDisconnect, Some(interface_index)) => Self::Disconnect {
+                interface_index,
+                reason: u16_attr(Nl80211Attr::AttrReasonCode),
+            },
+            (Nl80211Cmd::CmdRegChange | Nl80211Cmd::CmdWiphyRegChange, _) => Self::RegChange {
+                alpha2: attrs
+                    .get_attr_payload_as_with_len(Nl80211Attr::AttrRegAlpha2)
+                    .ok(),
+            },
+            (Nl80211Cmd::CmdVendor, _) => Self::Vendor {
+                vendor_id: attrs.get_attr_payload_as(Nl80211Attr::AttrVendorId)?,
+                subcmd: attrs.get_attr_payload_as(Nl80211Attr::AttrVendorSubcmd)?,
+                data: attrs
+                    .get_attribute(Nl80211Attr::AttrVendorData)
+                    .map(|data| data.nla_payload.as_ref().to_vec())
+                    .unwrap_or_default(),
+            },
+            (cmd, interface_index) => Self::Other {
+                cmd,
+                interface_index,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_event {
+    use super::*;
+    use crate::NL_80211_GENL_VERSION;
+    use neli::genl::Nlattr;
+    use neli::types::GenlBuffer;
+
+    fn event(cmd: Nl80211Cmd, attrs: &[(Nl80211Attr, &[u8])]) -> WifiEvent {
+        let mut buffer = GenlBuffer::new();
+        for (attr, payload) in attrs {
+            buffer.push(Nlattr::new(false, false, *attr, *payload).unwrap());
+        }
+        let event = Genlmsghdr::new(cmd, NL_80211_GENL_VERSION, buffer);
+        (&event).try_into().unwrap()
+    }
+
+    const IFINDEX: (Nl80211Attr, &[u8]) = (Nl80211Attr::AttrIfindex, &[3, 0, 0, 0]);
+
+    #[test]
+    fn test_scan() {
+        assert_eq!(
+            event(Nl80211Cmd::CmdNewScanResults, &[IFINDEX]),
+            WifiEvent::ScanResults { interface_index: 3 }
+        );
+        assert_eq!(
+            event(Nl80211Cmd::CmdScanAborted, &[IFINDEX]),
+            WifiEvent::ScanAborted { interface_index: 3 }
+        );
+    }
+
+    #[test]
+    fn test_mlme() {
+        assert_eq!(
+            event(
+                Nl80211Cmd::CmdConnect,
+                &[
+                    IFINDEX,
+                    (Nl80211Attr::AttrMac, &[2, 0, 0, 0, 0, 1]),
+                    (Nl80211Attr::AttrStatusCode, &[17, 0]),
+                ]
+            ),
+            WifiEvent::Connect {
+                interface_index: 3,
+                bssid: Some(MacAddress([2, 0, 0, 0, 0, 1])),
+                status: Some(StatusCode::ApFull),
+            }
+        );
+        assert_eq!(
+            event(
+                Nl80211Cmd::CmdDisconnect,
+                &[IFINDEX, (Nl80211Attr::AttrReasonCode, &[3, 0])]
+            ),
+            WifiEvent::Disconnect {
+                interface_index: 3,
+                reason: Some(3),
+            }
+        );
+    }
+
+    #[test]
+    fn test_config_reg_vendor() {
+        match event(Nl80211Cmd::CmdNewInterface, &[IFINDEX]) {
+            WifiEvent::NewInterface(interface) => assert_eq!(interface.index, Some(3)),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(
+            event(
+                Nl80211Cmd::CmdRegChange,
+                &[(Nl80211Attr::AttrRegAlpha2, b"DE\0")]
+            ),
+            WifiEvent::RegChange {
+                alpha2: Some("DE".to_string())
+            }
+        );
+        assert_eq!(
+            event(
+                Nl80211Cmd::CmdVendor,
+                &[
+                    (Nl80211Attr::AttrVendorId, &[0x18, 0x10, 0, 0]),
+                    (Nl80211Attr::AttrVendorSubcmd, &[1, 0, 0, 0]),
+                    (Nl80211Attr::AttrVendorData, &[1, 2, 3]),
+                ]
+            ),
+            WifiEvent::Vendor {
+                vendor_id: 0x001018,
+                subcmd: 1,
+                data: vec![1, 2, 3],
+            }
+        );
+        assert_eq!(
+            event(Nl80211Cmd::CmdFrame, &[IFINDEX]),
+            WifiEvent::Other {
+                cmd: Nl80211Cmd::CmdFrame,
+                interface_index: Some(3),
+            }
+        );
+    }
+}
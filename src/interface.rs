@@ -1,29 +1,255 @@
 use crate::attr::{Attrs, Nl80211Attr};
+use crate::channel::{band_of_freq, channel_of_freq, FrequencyBand};
+use crate::mac::MacAddress;
+use crate::metrics::{insert_metric, Metrics};
 
 use neli::attr::Attribute;
 use neli::err::DeError;
 
+use std::collections::HashMap;
+
 /// A struct representing a wifi interface
+///
+/// Interfaces are what `get_interfaces_info` lists. Code that is handed interfaces, like a
+/// channel picker, can be fed made-up ones built with the `with_*` methods:
+///
+/// ```
+/// # use neli_wifi::Interface;
+/// let interface = Interface::default().with_index(3).with_frequency(5180);
+/// assert_eq!(interface.channel_number(), Some(36));
+/// ```
+///
+/// With the `serde` feature the serialized form also carries the decoded SSID as `ssid_str`,
+/// see [`ssid_str`](Self::ssid_str).
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[non_exhaustive]
 pub struct Interface {
     /// A netlink interface index. This index is used to fetch extra information with nl80211
     pub index: Option<i32>,
     /// Interface essid
     pub ssid: Option<Vec<u8>>,
     /// Interface MAC address
-    pub mac: Option<Vec<u8>>,
+    pub mac: Option<MacAddress>,
     /// Interface name (u8, String)
     pub name: Option<Vec<u8>>,
     /// Interface frequency of the selected channel (MHz)
     pub frequency: Option<u32>,
-    /// Interface chanel
+    /// Width of the channel as `enum nl80211_chan_width` value, despite the name
+    ///
+    /// The channel number is derived from the frequency by
+    /// [`channel_number`](Self::channel_number).
     pub channel: Option<u32>,
     /// Interface transmit power level in signed mBm units.
+    ///
+    /// This is the power of this interface, other interfaces on the same phy may report a
+    /// different level.
     pub power: Option<u32>,
     /// index of wiphy to operate on, cf. /sys/class/ieee80211/<phyname>/index
     pub phy: Option<u32>,
     /// Wireless device identifier, used for pseudo-devices that don't have a netdev
     pub device: Option<u64>,
+    /// Interface type (station, access point, monitor, ...)
+    pub iftype: Option<InterfaceType>,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Interface {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let Interface {
+            index,
+            ssid,
+            mac,
+            name,
+            frequency,
+            channel,
+            power,
+            phy,
+            device,
+            iftype,
+        } = self;
+        let mut state = serializer.serialize_struct("Interface", 11)?;
+        state.serialize_field("index", index)?;
+        state.serialize_field("ssid", ssid)?;
+        state.serialize_field("ssid_str", &self.ssid_str())?;
+        state.serialize_field("mac", mac)?;
+        state.serialize_field("name", name)?;
+        state.serialize_field("frequency", frequency)?;
+        state.serialize_field("channel", channel)?;
+        state.serialize_field("power", power)?;
+        state.serialize_field("phy", phy)?;
+        state.serialize_field("device", device)?;
+        state.serialize_field("iftype", iftype)?;
+        state.end()
+    }
+}
+
+/// The type (operating mode) of a wifi interface
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InterfaceType {
+    Unspecified,
+    Adhoc,
+    Station,
+    Ap,
+    ApVlan,
+    Wds,
+    Monitor,
+    MeshPoint,
+    P2pClient,
+    P2pGo,
+    P2pDevice,
+    Ocb,
+    Nan,
+    /// An interface type this crate does not know about, with its raw kernel value
+    Unknown(u32),
+}
+
+impl From<u32> for InterfaceType {
+    fn from(iftype: u32) -> Self {
+        match iftype {
+            0 => Self::Unspecified,
+            1 => Self::Adhoc,
+            2 => Self::Station,
+            3 => Self::Ap,
+            4 => Self::ApVlan,
+            5 => Self::Wds,
+            6 => Self::Monitor,
+            7 => Self::MeshPoint,
+            8 => Self::P2pClient,
+            9 => Self::P2pGo,
+            10 => Self::P2pDevice,
+            11 => Self::Ocb,
+            12 => Self::Nan,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+impl From<InterfaceType> for u32 {
+    fn from(iftype: InterfaceType) -> Self {
+        match iftype {
+            InterfaceType::Unspecified => 0,
+            InterfaceType::Adhoc => 1,
+            InterfaceType::Station => 2,
+            InterfaceType::Ap => 3,
+            InterfaceType::ApVlan => 4,
+            InterfaceType::Wds => 5,
+            InterfaceType::Monitor => 6,
+            InterfaceType::MeshPoint => 7,
+            InterfaceType::P2pClient => 8,
+            InterfaceType::P2pGo => 9,
+            InterfaceType::P2pDevice => 10,
+            InterfaceType::Ocb => 11,
+            InterfaceType::Nan => 12,
+            InterfaceType::Unknown(other) => other,
+        }
+    }
+}
+
+/// The frames a monitor interface passes on, as set with `set_monitor_flags`
+///
+/// All flags clear is the default of new monitor interfaces: only frames with a valid FCS from
+/// any BSS, without control frames.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MonitorFlags {
+    /// Pass frames with a bad FCS
+    pub fcsfail: bool,
+    /// Pass frames with a bad PLCP
+    pub plcpfail: bool,
+    /// Pass control frames
+    pub control: bool,
+    /// Disable BSSID filtering
+    pub otherbss: bool,
+    /// Report frames after processing, overrides all other flags
+    pub cook: bool,
+    /// Acknowledge unicast frames addressed to the interface's MAC address
+    pub active: bool,
+}
+
+impl Interface {
+    /// Set the [`index`](Self::index)
+    pub fn with_index(mut self, index: i32) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// Set the [`name`](Self::name)
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.as_bytes().to_vec());
+        self
+    }
+
+    /// Set the [`ssid`](Self::ssid) of the network the interface is connected to
+    pub fn with_ssid(mut self, ssid: &[u8]) -> Self {
+        self.ssid = Some(ssid.to_vec());
+        self
+    }
+
+    /// Set the [`mac`](Self::mac) address
+    pub fn with_mac(mut self, mac: MacAddress) -> Self {
+        self.mac = Some(mac);
+        self
+    }
+
+    /// Set the [`frequency`](Self::frequency) of the channel in MHz
+    pub fn with_frequency(mut self, frequency: u32) -> Self {
+        self.frequency = Some(frequency);
+        self
+    }
+
+    /// Set the index of the wiphy, cf. [`phy`](Self::phy)
+    pub fn with_phy(mut self, phy: u32) -> Self {
+        self.phy = Some(phy);
+        self
+    }
+
+    /// Set the [`iftype`](Self::iftype)
+    pub fn with_iftype(mut self, iftype: InterfaceType) -> Self {
+        self.iftype = Some(iftype);
+        self
+    }
+
+    /// [`ssid`](Self::ssid) as a string without trailing NULs, invalid UTF-8 is replaced with
+    /// U+FFFD
+    pub fn ssid_str(&self) -> Option<String> {
+        self.ssid.as_deref().map(ssid_to_string)
+    }
+
+    /// The band of [`frequency`](Self::frequency)
+    pub fn band(&self) -> Option<FrequencyBand> {
+        self.frequency.and_then(band_of_freq)
+    }
+
+    /// The channel number of [`frequency`](Self::frequency)
+    ///
+    /// `None` for interfaces that are not on a channel, e.g. because they are down.
+    pub fn channel_number(&self) -> Option<u32> {
+        self.frequency.and_then(channel_of_freq)
+    }
+
+    /// All known numeric values of the interface, for metric exporters
+    ///
+    /// Uses the same key naming as [`Station::metrics`](crate::Station::metrics).
+    pub fn metrics(&self) -> HashMap<&'static str, f64> {
+        let mut metrics = Metrics::new();
+        let m = &mut metrics;
+        insert_metric(m, "frequency_mhz", self.frequency.map(f64::from));
+        insert_metric(m, "channel", self.channel_number().map(f64::from));
+        // The level is signed, the kernel just reports it in an unsigned attribute
+        let power = self.power.map(|p| f64::from(p as i32) / 100.0);
+        insert_metric(m, "tx_power_dbm", power);
+        metrics
+    }
+}
+
+/// Decode an SSID for display, replacing invalid UTF-8 and dropping trailing NUL padding
+pub(crate) fn ssid_to_string(ssid: &[u8]) -> String {
+    let len = ssid.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+    String::from_utf8_lossy(&ssid[..len]).into_owned()
 }
 
 impl TryFrom<Attrs<'_, Nl80211Attr>> for Interface {
@@ -40,7 +266,7 @@ impl TryFrom<Attrs<'_, Nl80211Attr>> for Interface {
                     res.ssid = Some(attr.get_payload_as_with_len()?);
                 }
                 Nl80211Attr::AttrMac => {
-                    res.mac = Some(attr.get_payload_as_with_len()?);
+                    res.mac = Some(attr.nla_payload.as_ref().try_into()?);
                 }
                 Nl80211Attr::AttrIfname => {
                     res.name = Some(attr.get_payload_as_with_len()?);
@@ -56,6 +282,9 @@ impl TryFrom<Attrs<'_, Nl80211Attr>> for Interface {
                 }
                 Nl80211Attr::AttrWiphy => res.phy = Some(attr.get_payload_as()?),
                 Nl80211Attr::AttrWdev => res.device = Some(attr.get_payload_as()?),
+                Nl80211Attr::AttrIftype => {
+                    res.iftype = Some(attr.get_payload_as::<u32>()?.into());
+                }
                 _ => (),
             }
         }
@@ -104,15 +333,137 @@ mod test_interface {
         let expected_interface = Interface {
             index: Some(3),
             ssid: Some(vec![101, 100, 117, 114, 111, 97, 109]),
-            mac: Some(vec![255, 255, 255, 255, 255, 255]),
+            mac: Some(MacAddress([255; 6])),
             name: Some(vec![119, 108, 112, 53, 115, 48]),
             frequency: Some(u32::from_le_bytes([108, 9, 0, 0])),
             channel: Some(u32::from_le_bytes([1, 0, 0, 0])),
             power: Some(u32::from_le_bytes([164, 6, 0, 0])),
             phy: Some(u32::from_le_bytes([0, 0, 0, 0])),
             device: Some(u64::from_le_bytes([1, 0, 0, 0, 0, 0, 0, 0])),
+            iftype: Some(InterfaceType::Station),
         };
 
         assert_eq!(interface, expected_interface)
     }
+
+    #[test]
+    fn test_unknown_iftype() {
+        let handler = vec![
+            new_attr(AttrIfindex, vec![3, 0, 0, 0]),
+            new_attr(AttrIftype, vec![99, 0, 0, 0]),
+        ];
+
+        let interface: Interface = AttrHandle::new(handler.into_iter().collect())
+            .try_into()
+            .unwrap();
+
+        assert_eq!(interface.iftype, Some(InterfaceType::Unknown(99)));
+        assert_eq!(u32::from(InterfaceType::Unknown(99)), 99);
+    }
+
+    #[test]
+    fn test_down_interface() {
+        // A down interface has no channel, so the kernel omits frequency and width
+        let handler = vec![
+            new_attr(AttrIfindex, vec![4, 0, 0, 0]),
+            new_attr(AttrIfname, vec![119, 108, 97, 110, 49]),
+            new_attr(AttrWiphy, vec![1, 0, 0, 0]),
+            new_attr(AttrIftype, vec![2, 0, 0, 0]),
+            new_attr(AttrWdev, vec![1, 0, 0, 0, 1, 0, 0, 0]),
+            new_attr(AttrMac, vec![2, 0, 0, 0, 1, 0]),
+        ];
+
+        let interface: Interface = AttrHandle::new(handler.into_iter().collect())
+            .try_into()
+            .unwrap();
+
+        assert_eq!(interface.frequency, None);
+        assert_eq!(interface.channel, None);
+        assert_eq!(interface.power, None);
+        assert_eq!(interface.band(), None);
+        assert_eq!(interface.channel_number(), None);
+    }
+
+    #[test]
+    fn test_metrics() {
+        let interface = Interface {
+            frequency: Some(5180),
+            power: Some(2000),
+            ..Default::default()
+        };
+
+        let metrics = interface.metrics();
+        assert_eq!(metrics.len(), 3);
+        assert_eq!(metrics["frequency_mhz"], 5180.0);
+        assert_eq!(metrics["channel"], 36.0);
+        assert_eq!(metrics["tx_power_dbm"], 20.0);
+
+        // -5 dBm
+        let interface = Interface {
+            power: Some(-500i32 as u32),
+            ..Default::default()
+        };
+        assert_eq!(interface.metrics()["tx_power_dbm"], -5.0);
+    }
+
+    #[test]
+    fn test_channel_number_6ghz() {
+        let interface = |frequency| Interface {
+            frequency: Some(frequency),
+            ..Default::default()
+        };
+
+        // 6 GHz channels count from 5950 MHz, not 5000 MHz like 5 GHz channels
+        assert_eq!(interface(5975).channel_number(), Some(5));
+        assert_eq!(interface(5975).band(), Some(FrequencyBand::Band6G));
+        assert_eq!(interface(6115).channel_number(), Some(33));
+        assert_eq!(interface(5825).channel_number(), Some(165));
+    }
+
+    #[test]
+    fn test_ssid_str() {
+        let interface = |ssid: &[u8]| -> Interface {
+            AttrHandle::new(
+                vec![new_attr(AttrSsid, ssid.to_vec())]
+                    .into_iter()
+                    .collect(),
+            )
+            .try_into()
+            .unwrap()
+        };
+
+        let utf8 = interface("café".as_bytes());
+        assert_eq!(utf8.ssid.as_deref(), Some("café".as_bytes()));
+        assert_eq!(utf8.ssid_str().as_deref(), Some("café"));
+
+        let latin1 = interface(&[99, 97, 102, 233]);
+        assert_eq!(latin1.ssid.as_deref(), Some(&[99, 97, 102, 233][..]));
+        assert_eq!(latin1.ssid_str().as_deref(), Some("caf\u{fffd}"));
+
+        let padded = interface(b"home\0\0");
+        assert_eq!(padded.ssid.as_deref(), Some(&b"home\0\0"[..]));
+        assert_eq!(padded.ssid_str().as_deref(), Some("home"));
+    }
+
+    #[test]
+    fn test_builder() {
+        let interface = Interface::default()
+            .with_index(3)
+            .with_name("wlan0")
+            .with_ssid(b"home\0")
+            .with_mac(MacAddress([2, 0, 0, 0, 0, 1]))
+            .with_frequency(5180)
+            .with_phy(0)
+            .with_iftype(InterfaceType::Station);
+
+        assert_eq!(interface.index, Some(3));
+        assert_eq!(interface.name.as_deref(), Some(&b"wlan0"[..]));
+        assert_eq!(interface.ssid.as_deref(), Some(&b"home\0"[..]));
+        assert_eq!(interface.ssid_str().as_deref(), Some("home"));
+        assert_eq!(interface.mac, Some(MacAddress([2, 0, 0, 0, 0, 1])));
+        assert_eq!(interface.channel_number(), Some(36));
+        assert_eq!(interface.phy, Some(0));
+        assert_eq!(interface.iftype, Some(InterfaceType::Station));
+        assert_eq!(interface.power, None);
+    }
 }
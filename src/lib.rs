@@ -4,21 +4,67 @@ pub const NL_80211_GENL_VERSION: u8 = 1;
 mod cmd;
 pub use cmd::*;
 
+mod error;
+pub use error::*;
+
 mod attr;
 pub use attr::*;
 
+mod mac;
+pub use mac::*;
+
 mod bss;
 pub use bss::*;
 
+mod security;
+pub use security::*;
+
 mod station;
 pub use station::*;
 
 mod interface;
 pub use interface::*;
 
+mod wiphy;
+pub use wiphy::*;
+
+mod connect;
+pub use connect::*;
+
+mod scan;
+pub use scan::*;
+
+mod mesh;
+pub use mesh::*;
+
+mod channel;
+pub use channel::*;
+
+mod csa;
+pub use csa::*;
+
+mod power;
+pub use power::*;
+
+mod reg;
+pub use reg::*;
+
+mod survey;
+pub use survey::*;
+
+mod event;
+pub use event::*;
+
+mod metrics;
+
+mod request;
+
 mod socket;
 pub use socket::*;
 
+mod scanner;
+pub use scanner::*;
+
 #[cfg(feature = "async")]
 mod async_socket;
 #[cfg(feature = "async")]
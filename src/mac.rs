@@ -0,0 +1,161 @@
+use neli::err::DeError;
+
+use std::fmt;
+use std::str::FromStr;
+
+/// A 48-bit MAC address
+///
+/// Formats as the usual colon separated hex notation (`aa:bb:cc:dd:ee:ff`) and can be parsed
+/// back from it.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct MacAddress(pub [u8; 6]);
+
+impl MacAddress {
+    /// The address as bytes
+    pub fn octets(&self) -> [u8; 6] {
+        self.0
+    }
+}
+
+impl From<[u8; 6]> for MacAddress {
+    fn from(octets: [u8; 6]) -> Self {
+        Self(octets)
+    }
+}
+
+impl From<MacAddress> for [u8; 6] {
+    fn from(mac: MacAddress) -> Self {
+        mac.0
+    }
+}
+
+impl AsRef<[u8]> for MacAddress {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Decode a netlink payload, which must be exactly 6 bytes
+impl TryFrom<&[u8]> for MacAddress {
+    type Error = DeError;
+
+    fn try_from(payload: &[u8]) -> Result<Self, Self::Error> {
+        payload.try_into().map(Self).map_err(|_| {
+            DeError::new(format!(
+                "MAC address must be 6 bytes, got {}",
+                payload.len()
+            ))
+        })
+    }
+}
+
+impl fmt::Display for MacAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [a, b, c, d, e, g] = self.0;
+        write!(
+            f,
+            "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
+            a, b, c, d, e, g
+        )
+    }
+}
+
+impl fmt::Debug for MacAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MacAddress({})", self)
+    }
+}
+
+/// Error parsing a [`MacAddress`] from a string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseMacAddressError(String);
+
+impl fmt::Display for ParseMacAddressError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid MAC address {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseMacAddressError {}
+
+/// Parse six colon (or dash) separated hex octets like `aa:bb:cc:dd:ee:ff`
+impl FromStr for MacAddress {
+    type Err = ParseMacAddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseMacAddressError(s.to_string());
+        let mut octets = [0; 6];
+        let mut parts = s.split([':', '-']);
+        for octet in octets.iter_mut() {
+            let part = parts.next().ok_or_else(err)?;
+            if part.len() != 2 || !part.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(err());
+            }
+            *octet = u8::from_str_radix(part, 16).map_err(|_| err())?;
+        }
+        if parts.next().is_some() {
+            return Err(err());
+        }
+        Ok(Self(octets))
+    }
+}
+
+/// Serialized as a string like `aa:bb:cc:dd:ee:ff`
+#[cfg(feature = "serde")]
+impl serde::Serialize for MacAddress {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for MacAddress {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test_mac {
+    use super::*;
+
+    #[test]
+    fn test_display_parse() {
+        let mac = MacAddress([0x02, 0xab, 0, 0x10, 0xcd, 0xff]);
+        assert_eq!(mac.to_string(), "02:ab:00:10:cd:ff");
+        assert_eq!(format!("{:?}", mac), "MacAddress(02:ab:00:10:cd:ff)");
+        assert_eq!("02:ab:00:10:cd:ff".parse(), Ok(mac));
+        assert_eq!("02-AB-00-10-CD-FF".parse(), Ok(mac));
+
+        for invalid in [
+            "",
+            "02:ab:00:10:cd",
+            "02:ab:00:10:cd:ff:00",
+            "2:ab:00:10:cd:ff",
+        ] {
+            assert!(invalid.parse::<MacAddress>().is_err(), "{}", invalid);
+        }
+        assert!("02:ab:00:10:cd:+f".parse::<MacAddress>().is_err());
+    }
+
+    #[test]
+    fn test_payload() {
+        let mac = MacAddress::try_from(&[2, 0, 0, 0, 0, 1][..]).unwrap();
+        assert_eq!(mac, MacAddress::from([2, 0, 0, 0, 0, 1]));
+        assert!(MacAddress::try_from(&[2, 0, 0, 0, 0][..]).is_err());
+        assert!(MacAddress::try_from(&[0; 8][..]).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize() {
+        use serde::de::value::{Error, StrDeserializer};
+        use serde::Deserialize;
+
+        let mac = MacAddress::deserialize(StrDeserializer::<Error>::new("02:00:00:00:00:01"));
+        assert_eq!(mac, Ok(MacAddress([2, 0, 0, 0, 0, 1])));
+        let mac = MacAddress::deserialize(StrDeserializer::<Error>::new("02:00:00:00:00"));
+        assert!(mac.is_err());
+    }
+}
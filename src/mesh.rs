@@ -0,0 +1,146 @@
+use crate::attr::{Nl80211Attr, Nl80211MeshconfParams};
+
+use neli::err::SerError;
+use neli::genl::Nlattr;
+use neli::types::Buffer;
+
+/// A tunable parameter of a mesh interface
+///
+/// Times are given in the unit the kernel uses for the parameter, ms for timeouts and TUs
+/// (1024 µs) for HWMP intervals, cf. `iw dev <devname> get mesh_param`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MeshParam {
+    /// Initial retry timeout of peer link management frames (ms)
+    RetryTimeout,
+    /// Confirm timeout of peer link management (ms)
+    ConfirmTimeout,
+    /// Holding timeout of peer link management (ms)
+    HoldingTimeout,
+    /// Maximum number of peer links
+    MaxPeerLinks,
+    /// Maximum number of peer link open retries
+    MaxRetries,
+    /// Default TTL of mesh frames
+    Ttl,
+    /// Default TTL of path selection elements
+    ElementTtl,
+    /// Whether peer links are opened automatically (0 or 1)
+    AutoOpenPlinks,
+    /// Number of HWMP path request retries
+    HwmpMaxPreqRetries,
+    /// Interval after which paths are refreshed (ms)
+    PathRefreshTime,
+    /// Minimum path discovery timeout (ms)
+    MinDiscoveryTimeout,
+    /// Time paths found by HWMP stay valid (TUs)
+    HwmpActivePathTimeout,
+    /// Minimum interval between path requests (TUs)
+    HwmpPreqMinInterval,
+    /// Network diameter traversal time (TUs)
+    HwmpNetDiamTrvsTime,
+    /// HWMP root mode (0: not a root, 2 - 4: root announcement variants)
+    HwmpRootmode,
+    /// Interval between root announcements (TUs)
+    HwmpRannInterval,
+    /// Whether to announce being a mesh gate (0 or 1)
+    GateAnnouncements,
+    /// Minimum interval between path errors (TUs)
+    HwmpPerrMinInterval,
+    /// Whether to forward frames of other mesh stations (0 or 1)
+    Forwarding,
+    /// Proactive path request interval of a root (TUs)
+    HwmpRootInterval,
+    /// Interval between proactive path confirmations (TUs)
+    HwmpConfirmationInterval,
+    /// Default power mode for new peer links (1: active, 2: light sleep, 3: deep sleep)
+    PowerMode,
+    /// Awake window duration (TUs)
+    AwakeWindow,
+    /// Inactivity timeout after which peer links are closed (s)
+    PlinkTimeout,
+}
+
+/// Encoded size of a parameter's value
+enum Width {
+    U8,
+    U16,
+    U32,
+}
+
+impl MeshParam {
+    fn attr_type(self) -> (Nl80211MeshconfParams, Width) {
+        use Nl80211MeshconfParams::*;
+        match self {
+            MeshParam::RetryTimeout => (MeshconfRetryTimeout, Width::U16),
+            MeshParam::ConfirmTimeout => (MeshconfConfirmTimeout, Width::U16),
+            MeshParam::HoldingTimeout => (MeshconfHoldingTimeout, Width::U16),
+            MeshParam::MaxPeerLinks => (MeshconfMaxPeerLinks, Width::U16),
+            MeshParam::MaxRetries => (MeshconfMaxRetries, Width::U8),
+            MeshParam::Ttl => (MeshconfTtl, Width::U8),
+            MeshParam::ElementTtl => (MeshconfElementTtl, Width::U8),
+            MeshParam::AutoOpenPlinks => (MeshconfAutoOpenPlinks, Width::U8),
+            MeshParam::HwmpMaxPreqRetries => (MeshconfHwmpMaxPreqRetries, Width::U8),
+            MeshParam::PathRefreshTime => (MeshconfPathRefreshTime, Width::U32),
+            MeshParam::MinDiscoveryTimeout => (MeshconfMinDiscoveryTimeout, Width::U16),
+            MeshParam::HwmpActivePathTimeout => (MeshconfHwmpActivePathTimeout, Width::U32),
+            MeshParam::HwmpPreqMinInterval => (MeshconfHwmpPreqMinInterval, Width::U16),
+            MeshParam::HwmpNetDiamTrvsTime => (MeshconfHwmpNetDiamTrvsTime, Width::U16),
+            MeshParam::HwmpRootmode => (MeshconfHwmpRootmode, Width::U8),
+            MeshParam::HwmpRannInterval => (MeshconfHwmpRannInterval, Width::U16),
+            MeshParam::GateAnnouncements => (MeshconfGateAnnouncements, Width::U8),
+            MeshParam::HwmpPerrMinInterval => (MeshconfHwmpPerrMinInterval, Width::U16),
+            MeshParam::Forwarding => (MeshconfForwarding, Width::U8),
+            MeshParam::HwmpRootInterval => (MeshconfHwmpRootInterval, Width::U16),
+            MeshParam::HwmpConfirmationInterval => (MeshconfHwmpConfirmationInterval, Width::U16),
+            MeshParam::PowerMode => (MeshconfPowerMode, Width::U32),
+            MeshParam::AwakeWindow => (MeshconfAwakeWindow, Width::U16),
+            MeshParam::PlinkTimeout => (MeshconfPlinkTimeout, Width::U32),
+        }
+    }
+
+    /// The `AttrMeshConfig` attribute setting only this parameter to `value`
+    pub(crate) fn config_attr(self, value: u32) -> Result<Nlattr<Nl80211Attr, Buffer>, SerError> {
+        let (attr_type, width) = self.attr_type();
+        let out_of_range =
+            |_| SerError::new(format!("Value {} out of range for {:?}", value, self));
+        let param = match width {
+            Width::U8 => Nlattr::new(
+                false,
+                false,
+                attr_type,
+                u8::try_from(value).map_err(out_of_range)?,
+            ),
+            Width::U16 => Nlattr::new(
+                false,
+                false,
+                attr_type,
+                u16::try_from(value).map_err(out_of_range)?,
+            ),
+            Width::U32 => Nlattr::new(false, false, attr_type, value),
+        }?;
+
+        let mut config = Nlattr::new(true, false, Nl80211Attr::AttrMeshConfig, Vec::<u8>::new())?;
+        config.add_nested_attribute(&param)?;
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod test_mesh {
+    use super::*;
+
+    #[test]
+    fn test_rann_interval() {
+        let config = MeshParam::HwmpRannInterval.config_attr(5000).unwrap();
+        let params = config.get_attr_handle::<Nl80211MeshconfParams>().unwrap();
+
+        assert_eq!(params.get_attrs().len(), 1);
+        let param = params
+            .get_attribute(Nl80211MeshconfParams::MeshconfHwmpRannInterval)
+            .unwrap();
+        assert_eq!(param.nla_payload.as_ref(), &5000u16.to_le_bytes());
+
+        assert!(MeshParam::HwmpRannInterval.config_attr(70000).is_err());
+    }
+}
@@ -0,0 +1,11 @@
+use std::collections::HashMap;
+
+/// Flat map of numeric metrics, keyed by a stable metric name
+pub(crate) type Metrics = HashMap<&'static str, f64>;
+
+/// Add `value` to `metrics` under `key`, unless it is unknown
+pub(crate) fn insert_metric(metrics: &mut Metrics, key: &'static str, value: Option<f64>) {
+    if let Some(value) = value {
+        metrics.insert(key, value);
+    }
+}
@@ -0,0 +1,82 @@
+use crate::attr::Nl80211Attr;
+use crate::cmd::Nl80211Cmd;
+
+use neli::err::DeError;
+use neli::genl::Genlmsghdr;
+
+/// Transmit power configuration of an interface
+///
+/// Transmit power levels are given in mBm (100 * dBm), the unit the kernel uses and also the
+/// unit of [`Interface::power`](crate::Interface::power). The configured power is applied to a
+/// single interface with `set_tx_power`; the level actually used can be read back with
+/// `get_interfaces_info` and may be lower than requested due to regulatory or hardware limits.
+///
+/// Levels in dBm are converted by the `_dbm` constructors:
+///
+/// ```
+/// # use neli_wifi::TxPowerSetting;
+/// assert_eq!(TxPowerSetting::fixed_dbm(20), TxPowerSetting::Fixed(2000));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TxPowerSetting {
+    /// Let the driver pick the transmit power
+    Automatic,
+    /// Limit the transmit power to at most the given level (mBm)
+    Limit(i32),
+    /// Use a fixed transmit power level (mBm)
+    Fixed(i32),
+}
+
+impl TxPowerSetting {
+    /// Limit the transmit power to at most `dbm` dBm
+    pub fn limit_dbm(dbm: i32) -> Self {
+        Self::Limit(dbm_to_mbm(dbm))
+    }
+
+    /// Use a fixed transmit power level of `dbm` dBm
+    pub fn fixed_dbm(dbm: i32) -> Self {
+        Self::Fixed(dbm_to_mbm(dbm))
+    }
+}
+
+/// Convert a power level from dBm to mBm, the unit nl80211 uses for transmit power
+pub fn dbm_to_mbm(dbm: i32) -> i32 {
+    dbm.saturating_mul(100)
+}
+
+/// Whether power save is enabled according to the kernel's reply to `CmdGetPowerSave`
+pub(crate) fn power_save_enabled(
+    reply: &Genlmsghdr<Nl80211Cmd, Nl80211Attr>,
+) -> Result<bool, DeError> {
+    let state: u32 = reply
+        .get_attr_handle()
+        .get_attr_payload_as(Nl80211Attr::AttrPsState)?;
+    Ok(state != 0)
+}
+
+#[cfg(test)]
+mod test_power {
+    use super::*;
+    use crate::NL_80211_GENL_VERSION;
+    use neli::genl::Nlattr;
+    use neli::types::GenlBuffer;
+
+    #[test]
+    fn test_power_save_enabled() {
+        let reply = |attrs: &[(Nl80211Attr, u32)]| {
+            let mut buffer = GenlBuffer::new();
+            for (attr, payload) in attrs {
+                buffer.push(Nlattr::new(false, false, *attr, *payload).unwrap());
+            }
+            Genlmsghdr::new(Nl80211Cmd::CmdGetPowerSave, NL_80211_GENL_VERSION, buffer)
+        };
+
+        let ifindex = (Nl80211Attr::AttrIfindex, 3);
+        let enabled = reply(&[ifindex, (Nl80211Attr::AttrPsState, 1)]);
+        assert!(power_save_enabled(&enabled).unwrap());
+        let disabled = reply(&[ifindex, (Nl80211Attr::AttrPsState, 0)]);
+        assert!(!power_save_enabled(&disabled).unwrap());
+        assert!(power_save_enabled(&reply(&[ifindex])).is_err());
+    }
+}
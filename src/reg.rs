@@ -0,0 +1,226 @@
+use crate::attr::{Attrs, Nl80211Attr, Nl80211DfsRegions, Nl80211RegRuleAttr, Nl80211RegRuleFlags};
+use crate::channel::{center_freq, ChannelWidth};
+
+use neli::attr::Attribute;
+use neli::consts::genl::Index;
+use neli::err::DeError;
+use neli::genl::Nlattr;
+use neli::types::Buffer;
+
+/// A regulatory domain as reported by the kernel
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegDomain {
+    /// ISO 3166 country code of the domain, "00" for the world domain
+    pub alpha2: Option<String>,
+    /// Region whose DFS (radar detection) requirements apply, if the domain has DFS rules
+    pub dfs_region: Option<DfsRegion>,
+    /// Frequency ranges in which operation is permitted
+    pub rules: Vec<RegRule>,
+}
+
+/// The DFS (radar detection) requirements of a [`RegDomain`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum DfsRegion {
+    /// Federal Communications Commission (US)
+    Fcc,
+    /// European Telecommunications Standards Institute
+    Etsi,
+    /// Japan
+    Jp,
+}
+
+impl DfsRegion {
+    fn from_nl(region: u8) -> Option<Self> {
+        match Nl80211DfsRegions::from(u16::from(region)) {
+            Nl80211DfsRegions::DfsFcc => Some(Self::Fcc),
+            Nl80211DfsRegions::DfsEtsi => Some(Self::Etsi),
+            Nl80211DfsRegions::DfsJp => Some(Self::Jp),
+            _ => None,
+        }
+    }
+}
+
+/// A frequency range of a [`RegDomain`] and the restrictions that apply to it
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegRule {
+    /// Start of the frequency range (kHz)
+    pub start_freq_khz: u32,
+    /// End of the frequency range (kHz)
+    pub end_freq_khz: u32,
+    /// Maximum channel bandwidth (kHz)
+    pub max_bandwidth_khz: u32,
+    /// Maximum EIRP (mBm)
+    pub max_eirp: Option<u32>,
+    /// Restrictions of the rule, a bitmap of [`Nl80211RegRuleFlags`]
+    pub flags: u32,
+}
+
+impl RegRule {
+    fn has_flag(&self, flag: Nl80211RegRuleFlags) -> bool {
+        self.flags & u32::from(u16::from(flag)) != 0
+    }
+}
+
+impl RegDomain {
+    /// Whether the channel `freq`/`width` is permitted in this domain
+    ///
+    /// The whole channel has to lie within a single rule that allows its bandwidth. With
+    /// `active` set, rules which forbid initiating radiation (no-IR) are not accepted either, as
+    /// needed for e.g. access points. This is slightly stricter than the kernel, which can
+    /// combine adjacent rules for wide channels.
+    pub fn permits_channel(&self, freq: u32, width: ChannelWidth, active: bool) -> bool {
+        let Some(center) = center_freq(freq, width) else {
+            return false;
+        };
+        let half_width_khz = width.mhz() * 500;
+        let start = center * 1000 - half_width_khz;
+        let end = center * 1000 + half_width_khz;
+
+        self.rules.iter().any(|rule| {
+            rule.start_freq_khz <= start
+                && end <= rule.end_freq_khz
+                && (width.mhz() * 1000 <= rule.max_bandwidth_khz
+                    || rule.has_flag(Nl80211RegRuleFlags::RrfAutoBw))
+                && !(active && rule.has_flag(Nl80211RegRuleFlags::RrfNoIr))
+        })
+    }
+}
+
+impl TryFrom<Attrs<'_, Nl80211Attr>> for RegDomain {
+    type Error = DeError;
+
+    fn try_from(attrs: Attrs<'_, Nl80211Attr>) -> Result<Self, Self::Error> {
+        let mut res = Self::default();
+        for attr in attrs.iter() {
+            match attr.nla_type.nla_type {
+                Nl80211Attr::AttrRegAlpha2 => res.alpha2 = Some(attr.get_payload_as_with_len()?),
+                Nl80211Attr::AttrDfsRegion => {
+                    res.dfs_region = DfsRegion::from_nl(attr.get_payload_as()?)
+                }
+                Nl80211Attr::AttrRegRules => {
+                    for rule in attr.get_attr_handle::<Index>()?.iter() {
+                        res.rules.push(rule.try_into()?);
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(res)
+    }
+}
+
+impl TryFrom<&Nlattr<Index, Buffer>> for RegRule {
+    type Error = DeError;
+
+    fn try_from(attr: &Nlattr<Index, Buffer>) -> Result<Self, Self::Error> {
+        let mut res = Self::default();
+        for attr in attr.get_attr_handle::<Nl80211RegRuleAttr>()?.iter() {
+            match attr.nla_type.nla_type {
+                Nl80211RegRuleAttr::AttrRegRuleFlags => res.flags = attr.get_payload_as()?,
+                Nl80211RegRuleAttr::AttrFreqRangeStart => {
+                    res.start_freq_khz = attr.get_payload_as()?
+                }
+                Nl80211RegRuleAttr::AttrFreqRangeEnd => res.end_freq_khz = attr.get_payload_as()?,
+                Nl80211RegRuleAttr::AttrFreqRangeMaxBw => {
+                    res.max_bandwidth_khz = attr.get_payload_as()?
+                }
+                Nl80211RegRuleAttr::AttrPowerRuleMaxEirp => {
+                    res.max_eirp = Some(attr.get_payload_as()?)
+                }
+                _ => (),
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod test_reg {
+    use super::*;
+    use crate::attr::Nl80211Attr::*;
+    use neli::attr::AttrHandle;
+    use neli::genl::AttrType;
+
+    fn new_attr(t: Nl80211Attr, d: Vec<u8>) -> Nlattr<Nl80211Attr, Buffer> {
+        Nlattr {
+            nla_len: (4 + d.len()) as _,
+            nla_type: AttrType {
+                nla_nested: false,
+                nla_network_order: true,
+                nla_type: t,
+            },
+            nla_payload: d.into(),
+        }
+    }
+
+    #[test]
+    fn test_parser() {
+        // DE with a single 5490 - 5710 MHz rule, DFS and no-IR
+        let handler = vec![
+            new_attr(AttrRegAlpha2, vec![68, 69, 0]),
+            new_attr(AttrDfsRegion, vec![2]),
+            new_attr(
+                AttrRegRules,
+                vec![
+                    44, 0, 1, 0, 8, 0, 1, 0, 144, 0, 0, 0, 8, 0, 2, 0, 80, 197, 83, 0, 8, 0, 3, 0,
+                    176, 32, 87, 0, 8, 0, 4, 0, 0, 113, 2, 0, 8, 0, 6, 0, 160, 15, 0, 0,
+                ],
+            ),
+        ];
+
+        let reg: RegDomain = AttrHandle::new(handler.into_iter().collect())
+            .try_into()
+            .unwrap();
+
+        assert_eq!(
+            reg,
+            RegDomain {
+                alpha2: Some("DE".to_string()),
+                dfs_region: Some(DfsRegion::Etsi),
+                rules: vec![RegRule {
+                    start_freq_khz: 5490000,
+                    end_freq_khz: 5710000,
+                    max_bandwidth_khz: 160000,
+                    max_eirp: Some(4000),
+                    flags: 0x90,
+                }],
+            }
+        );
+    }
+
+    #[test]
+    fn test_permits_channel() {
+        let reg = RegDomain {
+            alpha2: Some("DE".to_string()),
+            dfs_region: Some(DfsRegion::Etsi),
+            rules: vec![
+                RegRule {
+                    start_freq_khz: 2400000,
+                    end_freq_khz: 2483500,
+                    max_bandwidth_khz: 40000,
+                    ..Default::default()
+                },
+                RegRule {
+                    start_freq_khz: 5490000,
+                    end_freq_khz: 5710000,
+                    max_bandwidth_khz: 160000,
+                    flags: 0x90,
+                    ..Default::default()
+                },
+            ],
+        };
+
+        assert!(reg.permits_channel(2412, ChannelWidth::Width40, true));
+        // Wider than permitted
+        assert!(!reg.permits_channel(2412, ChannelWidth::Width80, false));
+        // No-IR
+        assert!(reg.permits_channel(5500, ChannelWidth::Width80, false));
+        assert!(!reg.permits_channel(5500, ChannelWidth::Width80, true));
+        // Outside of all rules
+        assert!(!reg.permits_channel(5745, ChannelWidth::Width20, false));
+    }
+}
@@ -0,0 +1,1044 @@
+use crate::attr::{Nl80211Attr, Nl80211MntrFlags, Nl80211RekeyData};
+use crate::channel::{channel_of_freq, push_channel_attrs, ChannelWidth};
+use crate::cmd::Nl80211Cmd;
+use crate::connect::ConnectParams;
+use crate::csa::CsaIes;
+use crate::interface::{InterfaceType, MonitorFlags};
+use crate::mesh::MeshParam;
+use crate::power::TxPowerSetting;
+use crate::reg::RegDomain;
+use crate::scan::{ScanRequest, SchedScanRequest};
+use crate::wiphy::Wiphy;
+use crate::{NL_80211_GENL_NAME, NL_80211_GENL_VERSION};
+
+use neli::consts::genl::{CtrlAttr, CtrlCmd};
+use neli::consts::nl::{GenlId, NlmF, NlmFFlags};
+use neli::err::SerError;
+use neli::genl::{Genlmsghdr, Nlattr};
+use neli::nl::{NlPayload, Nlmsghdr};
+use neli::types::{Buffer, GenlBuffer};
+
+/// An nl80211 request as sent by [`Socket`](crate::Socket) and `AsyncSocket`
+pub(crate) type Request = Nlmsghdr<u16, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>;
+
+/// A request of the generic netlink controller for the description of nl80211
+pub(crate) type FamilyRequest = Nlmsghdr<GenlId, Genlmsghdr<CtrlCmd, CtrlAttr>>;
+
+/// Request the description of nl80211, which includes the ids of its multicast groups
+pub(crate) fn get_family() -> Result<FamilyRequest, SerError> {
+    let msghdr = Genlmsghdr::<CtrlCmd, CtrlAttr>::new(CtrlCmd::Getfamily, 2, {
+        let mut attrs = GenlBuffer::new();
+        attrs.push(Nlattr::new(
+            false,
+            false,
+            CtrlAttr::FamilyName,
+            NL_80211_GENL_NAME,
+        )?);
+        attrs
+    });
+
+    let len = None;
+    let nl_type = GenlId::Ctrl;
+    let flags = NlmFFlags::new(&[NlmF::Request]);
+    let seq = None;
+    let pid = None;
+    let payload = NlPayload::Payload(msghdr);
+    Ok(Nlmsghdr::new(len, nl_type, flags, seq, pid, payload))
+}
+
+/// Build a request with the given netlink flags
+fn request(
+    family_id: u16,
+    cmd: Nl80211Cmd,
+    flags: &[NlmF],
+    attrs: GenlBuffer<Nl80211Attr, Buffer>,
+) -> Request {
+    let msghdr = Genlmsghdr::<Nl80211Cmd, Nl80211Attr>::new(cmd, NL_80211_GENL_VERSION, attrs);
+
+    let len = None;
+    let nl_type = family_id;
+    let flags = NlmFFlags::new(flags);
+    let seq = None;
+    let pid = None;
+    let payload = NlPayload::Payload(msghdr);
+    Nlmsghdr::new(len, nl_type, flags, seq, pid, payload)
+}
+
+/// Build a request which expects an acknowledgement and no other answer
+fn acked(family_id: u16, cmd: Nl80211Cmd, attrs: GenlBuffer<Nl80211Attr, Buffer>) -> Request {
+    request(family_id, cmd, &[NlmF::Request, NlmF::Ack], attrs)
+}
+
+fn ifindex_attrs(interface_index: i32) -> Result<GenlBuffer<Nl80211Attr, Buffer>, SerError> {
+    let mut attrs = GenlBuffer::new();
+    attrs.push(Nlattr::new(
+        false,
+        false,
+        Nl80211Attr::AttrIfindex,
+        interface_index,
+    )?);
+    Ok(attrs)
+}
+
+pub(crate) fn connect(
+    family_id: u16,
+    interface_index: i32,
+    ssid: &[u8],
+    params: &ConnectParams,
+) -> Result<Request, SerError> {
+    let mut attrs = ifindex_attrs(interface_index)?;
+    attrs.push(Nlattr::new(false, false, Nl80211Attr::AttrSsid, ssid)?);
+    for attr in params.attrs()? {
+        attrs.push(attr);
+    }
+    Ok(acked(family_id, Nl80211Cmd::CmdConnect, attrs))
+}
+
+pub(crate) fn disconnect(family_id: u16, interface_index: i32) -> Result<Request, SerError> {
+    let attrs = ifindex_attrs(interface_index)?;
+    Ok(acked(family_id, Nl80211Cmd::CmdDisconnect, attrs))
+}
+
+pub(crate) fn deauthenticate(
+    family_id: u16,
+    interface_index: i32,
+    bssid: &[u8],
+    reason: u16,
+) -> Result<Request, SerError> {
+    validate_mac(bssid)?;
+    let mut attrs = ifindex_attrs(interface_index)?;
+    attrs.push(Nlattr::new(false, false, Nl80211Attr::AttrMac, bssid)?);
+    attrs.push(Nlattr::new(
+        false,
+        false,
+        Nl80211Attr::AttrReasonCode,
+        reason,
+    )?);
+    Ok(acked(family_id, Nl80211Cmd::CmdDeauthenticate, attrs))
+}
+
+pub(crate) fn trigger_scan(
+    family_id: u16,
+    interface_index: i32,
+    request: &ScanRequest,
+) -> Result<Request, SerError> {
+    let mut attrs = ifindex_attrs(interface_index)?;
+    for attr in request.attrs()? {
+        attrs.push(attr);
+    }
+    Ok(acked(family_id, Nl80211Cmd::CmdTriggerScan, attrs))
+}
+
+pub(crate) fn start_sched_scan(
+    family_id: u16,
+    interface_index: i32,
+    request: &SchedScanRequest,
+) -> Result<Request, SerError> {
+    let mut attrs = ifindex_attrs(interface_index)?;
+    for attr in request.attrs()? {
+        attrs.push(attr);
+    }
+    Ok(acked(family_id, Nl80211Cmd::CmdStartSchedScan, attrs))
+}
+
+pub(crate) fn stop_sched_scan(family_id: u16, interface_index: i32) -> Result<Request, SerError> {
+    let attrs = ifindex_attrs(interface_index)?;
+    Ok(acked(family_id, Nl80211Cmd::CmdStopSchedScan, attrs))
+}
+
+pub(crate) fn set_phy_channel(
+    family_id: u16,
+    phy: u32,
+    freq: u32,
+    width: ChannelWidth,
+) -> Result<Request, SerError> {
+    let mut attrs = GenlBuffer::new();
+    attrs.push(Nlattr::new(false, false, Nl80211Attr::AttrWiphy, phy)?);
+    push_channel_attrs(&mut attrs, freq, width)?;
+    Ok(acked(family_id, Nl80211Cmd::CmdSetWiphy, attrs))
+}
+
+pub(crate) fn get_station(
+    family_id: u16,
+    interface_index: i32,
+    mac: &[u8],
+) -> Result<Request, SerError> {
+    validate_mac(mac)?;
+    let mut attrs = ifindex_attrs(interface_index)?;
+    attrs.push(Nlattr::new(false, false, Nl80211Attr::AttrMac, mac)?);
+    Ok(request(
+        family_id,
+        Nl80211Cmd::CmdGetStation,
+        &[NlmF::Request],
+        attrs,
+    ))
+}
+
+/// Dump the stations of an interface
+pub(crate) fn get_stations(family_id: u16, interface_index: i32) -> Result<Request, SerError> {
+    let attrs = ifindex_attrs(interface_index)?;
+    Ok(request(
+        family_id,
+        Nl80211Cmd::CmdGetStation,
+        &[NlmF::Request, NlmF::Dump],
+        attrs,
+    ))
+}
+
+/// Dump the BSSes the kernel has cached from scans on an interface
+pub(crate) fn get_scan(family_id: u16, interface_index: i32) -> Result<Request, SerError> {
+    let attrs = ifindex_attrs(interface_index)?;
+    Ok(request(
+        family_id,
+        Nl80211Cmd::CmdGetScan,
+        &[NlmF::Request, NlmF::Dump],
+        attrs,
+    ))
+}
+
+/// Dump all wiphys, split over several messages as their descriptions don't fit into one
+pub(crate) fn get_wiphys(family_id: u16) -> Result<Request, SerError> {
+    let mut attrs = GenlBuffer::new();
+    attrs.push(Nlattr::new(
+        false,
+        false,
+        Nl80211Attr::AttrSplitWiphyDump,
+        (),
+    )?);
+    Ok(request(
+        family_id,
+        Nl80211Cmd::CmdGetWiphy,
+        &[NlmF::Request, NlmF::Dump],
+        attrs,
+    ))
+}
+
+fn validate_mac(mac: &[u8]) -> Result<(), SerError> {
+    if mac.len() != 6 {
+        return Err(SerError::new(format!(
+            "MAC address must be 6 bytes, got {}",
+            mac.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Dump all interfaces
+///
+/// Unlike the wiphy dump this needs no split dump flag or other attributes, interfaces are small.
+pub(crate) fn get_interfaces(family_id: u16) -> Request {
+    request(
+        family_id,
+        Nl80211Cmd::CmdGetInterface,
+        &[NlmF::Request, NlmF::Dump],
+        GenlBuffer::new(),
+    )
+}
+
+pub(crate) fn get_interface(family_id: u16, interface_index: i32) -> Result<Request, SerError> {
+    let attrs = ifindex_attrs(interface_index)?;
+    Ok(request(
+        family_id,
+        Nl80211Cmd::CmdGetInterface,
+        &[NlmF::Request],
+        attrs,
+    ))
+}
+
+pub(crate) fn get_survey(family_id: u16, interface_index: i32) -> Result<Request, SerError> {
+    let attrs = ifindex_attrs(interface_index)?;
+    Ok(request(
+        family_id,
+        Nl80211Cmd::CmdGetSurvey,
+        &[NlmF::Request, NlmF::Dump],
+        attrs,
+    ))
+}
+
+/// Request the global regulatory domain, or the one of the wiphy `phy`
+pub(crate) fn get_reg(family_id: u16, phy: Option<u32>) -> Result<Request, SerError> {
+    let mut attrs = GenlBuffer::new();
+    if let Some(phy) = phy {
+        attrs.push(Nlattr::new(false, false, Nl80211Attr::AttrWiphy, phy)?);
+    }
+    Ok(request(
+        family_id,
+        Nl80211Cmd::CmdGetReg,
+        &[NlmF::Request],
+        attrs,
+    ))
+}
+
+/// Request the kernel to use the regulatory domain of the country `alpha2`
+pub(crate) fn set_reg(family_id: u16, alpha2: &str) -> Result<Request, SerError> {
+    if alpha2.len() != 2 || !alpha2.bytes().all(|b| b.is_ascii_alphabetic()) {
+        return Err(SerError::new(format!(
+            "Country code must be two ASCII letters, got {:?}",
+            alpha2
+        )));
+    }
+
+    let mut attrs = GenlBuffer::new();
+    attrs.push(Nlattr::new(
+        false,
+        false,
+        Nl80211Attr::AttrRegAlpha2,
+        alpha2.to_ascii_uppercase(),
+    )?);
+    Ok(acked(family_id, Nl80211Cmd::CmdReqSetReg, attrs))
+}
+
+pub(crate) fn set_channel(
+    family_id: u16,
+    interface_index: i32,
+    freq: u32,
+    width: ChannelWidth,
+    reg_domain: Option<&RegDomain>,
+) -> Result<Request, SerError> {
+    if let Some(reg_domain) = reg_domain {
+        if !reg_domain.permits_channel(freq, width, false) {
+            return Err(SerError::new(format!(
+                "Channel {} ({} MHz) not permitted in domain {}",
+                channel_of_freq(freq).unwrap_or(freq),
+                width.mhz(),
+                reg_domain.alpha2.as_deref().unwrap_or("?"),
+            )));
+        }
+    }
+
+    let mut attrs = ifindex_attrs(interface_index)?;
+    push_channel_attrs(&mut attrs, freq, width)?;
+    Ok(acked(family_id, Nl80211Cmd::CmdSetChannel, attrs))
+}
+
+/// Hand the keys for GTK rekeying to the driver, which then handles rekeying while asleep
+///
+/// The KEK must be 16 or 32 bytes, the KCK 16, 24 or 32 bytes (the longer keys are only
+/// accepted by drivers supporting them) and the replay counter 8 bytes.
+pub(crate) fn set_rekey_data(
+    family_id: u16,
+    interface_index: i32,
+    kek: &[u8],
+    kck: &[u8],
+    replay_ctr: &[u8],
+) -> Result<Request, SerError> {
+    for (name, key, lengths) in [
+        ("KEK", kek, &[16, 32][..]),
+        ("KCK", kck, &[16, 24, 32][..]),
+        ("Replay counter", replay_ctr, &[8][..]),
+    ] {
+        if !lengths.contains(&key.len()) {
+            return Err(SerError::new(format!(
+                "{} must be {:?} bytes, got {}",
+                name,
+                lengths,
+                key.len()
+            )));
+        }
+    }
+
+    let mut rekey_data = Nlattr::new(true, false, Nl80211Attr::AttrRekeyData, Vec::<u8>::new())?;
+    for (attr, payload) in [
+        (Nl80211RekeyData::RekeyDataKek, kek),
+        (Nl80211RekeyData::RekeyDataKck, kck),
+        (Nl80211RekeyData::RekeyDataReplayCtr, replay_ctr),
+    ] {
+        rekey_data.add_nested_attribute(&Nlattr::new(false, false, attr, payload)?)?;
+    }
+
+    let mut attrs = ifindex_attrs(interface_index)?;
+    attrs.push(rekey_data);
+    Ok(acked(family_id, Nl80211Cmd::CmdSetRekeyOffload, attrs))
+}
+
+pub(crate) fn channel_switch(
+    family_id: u16,
+    interface_index: i32,
+    freq: u32,
+    width: ChannelWidth,
+    count: u8,
+    block_tx: bool,
+    csa_ies: &CsaIes,
+) -> Result<Request, SerError> {
+    let mut attrs = ifindex_attrs(interface_index)?;
+    push_channel_attrs(&mut attrs, freq, width)?;
+    attrs.push(Nlattr::new(
+        false,
+        false,
+        Nl80211Attr::AttrChSwitchCount,
+        u32::from(count),
+    )?);
+    if block_tx {
+        attrs.push(Nlattr::new(
+            false,
+            false,
+            Nl80211Attr::AttrChSwitchBlockTx,
+            (),
+        )?);
+    }
+    for attr in csa_ies.attrs()? {
+        attrs.push(attr);
+    }
+    Ok(acked(family_id, Nl80211Cmd::CmdChannelSwitch, attrs))
+}
+
+pub(crate) fn set_tx_power(
+    family_id: u16,
+    interface_index: i32,
+    setting: TxPowerSetting,
+) -> Result<Request, SerError> {
+    let (setting, level) = match setting {
+        TxPowerSetting::Automatic => (0u32, None),
+        TxPowerSetting::Limit(mbm) => (1, Some(mbm)),
+        TxPowerSetting::Fixed(mbm) => (2, Some(mbm)),
+    };
+
+    let mut attrs = ifindex_attrs(interface_index)?;
+    attrs.push(Nlattr::new(
+        false,
+        false,
+        Nl80211Attr::AttrWiphyTxPowerSetting,
+        setting,
+    )?);
+    if let Some(level) = level {
+        attrs.push(Nlattr::new(
+            false,
+            false,
+            Nl80211Attr::AttrWiphyTxPowerLevel,
+            level,
+        )?);
+    }
+    Ok(acked(family_id, Nl80211Cmd::CmdSetWiphy, attrs))
+}
+
+pub(crate) fn get_power_save(family_id: u16, interface_index: i32) -> Result<Request, SerError> {
+    let attrs = ifindex_attrs(interface_index)?;
+    Ok(request(
+        family_id,
+        Nl80211Cmd::CmdGetPowerSave,
+        &[NlmF::Request],
+        attrs,
+    ))
+}
+
+pub(crate) fn set_power_save(
+    family_id: u16,
+    interface_index: i32,
+    enabled: bool,
+) -> Result<Request, SerError> {
+    let mut attrs = ifindex_attrs(interface_index)?;
+    attrs.push(Nlattr::new(
+        false,
+        false,
+        Nl80211Attr::AttrPsState,
+        u32::from(enabled),
+    )?);
+    Ok(acked(family_id, Nl80211Cmd::CmdSetPowerSave, attrs))
+}
+
+pub(crate) fn set_mesh_param(
+    family_id: u16,
+    interface_index: i32,
+    param: MeshParam,
+    value: u32,
+) -> Result<Request, SerError> {
+    let mut attrs = ifindex_attrs(interface_index)?;
+    attrs.push(param.config_attr(value)?);
+    Ok(acked(family_id, Nl80211Cmd::CmdSetMeshConfig, attrs))
+}
+
+pub(crate) fn set_interface_type(
+    family_id: u16,
+    interface_index: i32,
+    iftype: InterfaceType,
+) -> Result<Request, SerError> {
+    let mut attrs = ifindex_attrs(interface_index)?;
+    attrs.push(Nlattr::new(
+        false,
+        false,
+        Nl80211Attr::AttrIftype,
+        u32::from(iftype),
+    )?);
+    Ok(acked(family_id, Nl80211Cmd::CmdSetInterface, attrs))
+}
+
+/// Maximum length of an interface name, including the terminating nul byte
+const IFNAMSIZ: usize = 16;
+
+/// Check `name` against the rules the kernel applies to interface names
+fn validate_interface_name(name: &str) -> Result<(), SerError> {
+    if name.is_empty() || name.len() > IFNAMSIZ - 1 {
+        return Err(SerError::new(format!(
+            "Interface name must be 1 to {} bytes long",
+            IFNAMSIZ - 1
+        )));
+    }
+    if name == "."
+        || name == ".."
+        || name.contains(|c: char| c == '/' || c == ':' || c.is_whitespace())
+    {
+        return Err(SerError::new(format!("Invalid interface name {:?}", name)));
+    }
+    Ok(())
+}
+
+pub(crate) fn set_monitor_flags(
+    family_id: u16,
+    interface_index: i32,
+    flags: MonitorFlags,
+) -> Result<Request, SerError> {
+    let mut attrs = ifindex_attrs(interface_index)?;
+    attrs.push(monitor_flags_attr(flags)?);
+    Ok(acked(family_id, Nl80211Cmd::CmdSetInterface, attrs))
+}
+
+fn monitor_flags_attr(flags: MonitorFlags) -> Result<Nlattr<Nl80211Attr, Buffer>, SerError> {
+    let mut mntr_flags = Nlattr::new(true, false, Nl80211Attr::AttrMntrFlags, Vec::<u8>::new())?;
+    for (set, flag) in [
+        (flags.fcsfail, Nl80211MntrFlags::MntrFlagFcsfail),
+        (flags.plcpfail, Nl80211MntrFlags::MntrFlagPlcpfail),
+        (flags.control, Nl80211MntrFlags::MntrFlagControl),
+        (flags.otherbss, Nl80211MntrFlags::MntrFlagOtherBss),
+        (flags.cook, Nl80211MntrFlags::MntrFlagCookFrames),
+        (flags.active, Nl80211MntrFlags::MntrFlagActive),
+    ] {
+        if set {
+            mntr_flags.add_nested_attribute(&Nlattr::new(false, false, flag, ())?)?;
+        }
+    }
+    Ok(mntr_flags)
+}
+
+pub(crate) fn set_interface_name(
+    family_id: u16,
+    interface_index: i32,
+    name: &str,
+) -> Result<Request, SerError> {
+    validate_interface_name(name)?;
+
+    let mut attrs = ifindex_attrs(interface_index)?;
+    attrs.push(Nlattr::new(false, false, Nl80211Attr::AttrIfname, name)?);
+    Ok(acked(family_id, Nl80211Cmd::CmdSetInterface, attrs))
+}
+
+pub(crate) fn create_interface(
+    family_id: u16,
+    wiphy: &Wiphy,
+    name: &str,
+    iftype: InterfaceType,
+) -> Result<Request, SerError> {
+    validate_interface_name(name)?;
+    let phy = wiphy
+        .index
+        .ok_or_else(|| SerError::new("Wiphy without index"))?;
+    if !wiphy.supports_iftype(iftype) {
+        return Err(SerError::new(format!(
+            "Interface type {:?} is not supported by phy {}",
+            iftype, phy
+        )));
+    }
+
+    Ok(acked(
+        family_id,
+        Nl80211Cmd::CmdNewInterface,
+        new_interface_attrs(phy, name, iftype)?,
+    ))
+}
+
+pub(crate) fn create_monitor(
+    family_id: u16,
+    phy: u32,
+    name: &str,
+    flags: MonitorFlags,
+) -> Result<Request, SerError> {
+    validate_interface_name(name)?;
+
+    let mut attrs = new_interface_attrs(phy, name, InterfaceType::Monitor)?;
+    attrs.push(monitor_flags_attr(flags)?);
+    Ok(acked(family_id, Nl80211Cmd::CmdNewInterface, attrs))
+}
+
+fn new_interface_attrs(
+    phy: u32,
+    name: &str,
+    iftype: InterfaceType,
+) -> Result<GenlBuffer<Nl80211Attr, Buffer>, SerError> {
+    let mut attrs = GenlBuffer::new();
+    attrs.push(Nlattr::new(false, false, Nl80211Attr::AttrWiphy, phy)?);
+    attrs.push(Nlattr::new(false, false, Nl80211Attr::AttrIfname, name)?);
+    attrs.push(Nlattr::new(
+        false,
+        false,
+        Nl80211Attr::AttrIftype,
+        u32::from(iftype),
+    )?);
+    Ok(attrs)
+}
+
+pub(crate) fn delete_interface(family_id: u16, interface_index: i32) -> Result<Request, SerError> {
+    let attrs = ifindex_attrs(interface_index)?;
+    Ok(acked(family_id, Nl80211Cmd::CmdDelInterface, attrs))
+}
+
+#[cfg(test)]
+mod test_request {
+    use super::*;
+    use neli::ToBytes;
+    use std::io::Cursor;
+
+    fn to_bytes(request: Request) -> Vec<u8> {
+        let mut buffer = Cursor::new(Vec::new());
+        request.to_bytes(&mut buffer).unwrap();
+        buffer.into_inner()
+    }
+
+    #[test]
+    fn test_set_tx_power() {
+        // Both sockets send exactly these bytes
+        let request = set_tx_power(28, 3, TxPowerSetting::Limit(1500)).unwrap();
+        assert_eq!(
+            to_bytes(request),
+            vec![
+                44, 0, 0, 0, 28, 0, 5, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 0, 0, 8, 0, 3, 0, 3, 0, 0,
+                0, 8, 0, 97, 0, 1, 0, 0, 0, 8, 0, 98, 0, 220, 5, 0, 0,
+            ]
+        );
+
+        let request = set_tx_power(28, 3, TxPowerSetting::Automatic).unwrap();
+        let handle = request.nl_payload.get_payload().unwrap().get_attr_handle();
+        assert_eq!(
+            handle
+                .get_attr_payload_as::<u32>(Nl80211Attr::AttrWiphyTxPowerSetting)
+                .unwrap(),
+            0
+        );
+        assert!(handle
+            .get_attribute(Nl80211Attr::AttrWiphyTxPowerLevel)
+            .is_none());
+    }
+
+    #[test]
+    fn test_get_interfaces() {
+        let request = get_interfaces(28);
+        assert_eq!(
+            request.nl_flags,
+            NlmFFlags::new(&[NlmF::Request, NlmF::Dump])
+        );
+
+        let payload = request.nl_payload.get_payload().unwrap();
+        assert_eq!(payload.cmd, Nl80211Cmd::CmdGetInterface);
+        assert!(payload.get_attr_handle().get_attrs().is_empty());
+    }
+
+    #[test]
+    fn test_get_interface() {
+        let request = get_interface(28, 3).unwrap();
+        assert_eq!(request.nl_flags, NlmFFlags::new(&[NlmF::Request]));
+
+        let payload = request.nl_payload.get_payload().unwrap();
+        assert_eq!(payload.cmd, Nl80211Cmd::CmdGetInterface);
+        assert_eq!(
+            payload
+                .get_attr_handle()
+                .get_attr_payload_as::<i32>(Nl80211Attr::AttrIfindex)
+                .unwrap(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_set_power_save() {
+        let request = set_power_save(28, 3, true).unwrap();
+        let payload = request.nl_payload.get_payload().unwrap();
+        assert_eq!(payload.cmd, Nl80211Cmd::CmdSetPowerSave);
+        let handle = payload.get_attr_handle();
+        assert_eq!(
+            handle
+                .get_attr_payload_as::<u32>(Nl80211Attr::AttrPsState)
+                .unwrap(),
+            1
+        );
+
+        let request = get_power_save(28, 3).unwrap();
+        assert!(!request.nl_flags.contains(&NlmF::Ack));
+        let payload = request.nl_payload.get_payload().unwrap();
+        assert_eq!(payload.cmd, Nl80211Cmd::CmdGetPowerSave);
+        assert_eq!(payload.get_attr_handle().get_attrs().len(), 1);
+    }
+
+    #[test]
+    fn test_get_survey() {
+        let request = get_survey(28, 3).unwrap();
+        assert!(request.nl_flags.contains(&NlmF::Dump));
+
+        let payload = request.nl_payload.get_payload().unwrap();
+        assert_eq!(payload.cmd, Nl80211Cmd::CmdGetSurvey);
+        assert_eq!(
+            payload
+                .get_attr_handle()
+                .get_attr_payload_as::<u32>(Nl80211Attr::AttrIfindex)
+                .unwrap(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_disconnect() {
+        let request = disconnect(28, 3).unwrap();
+        let payload = request.nl_payload.get_payload().unwrap();
+        assert_eq!(payload.cmd, Nl80211Cmd::CmdDisconnect);
+        assert_eq!(payload.get_attr_handle().get_attrs().len(), 1);
+
+        let bssid = [2, 0, 0, 0, 0, 1];
+        let request = deauthenticate(28, 3, &bssid, 3).unwrap();
+        let payload = request.nl_payload.get_payload().unwrap();
+        assert_eq!(payload.cmd, Nl80211Cmd::CmdDeauthenticate);
+        let handle = payload.get_attr_handle();
+        assert_eq!(
+            handle
+                .get_attribute(Nl80211Attr::AttrMac)
+                .unwrap()
+                .nla_payload
+                .as_ref(),
+            &bssid
+        );
+        assert_eq!(
+            handle
+                .get_attr_payload_as::<u16>(Nl80211Attr::AttrReasonCode)
+                .unwrap(),
+            3
+        );
+
+        assert!(deauthenticate(28, 3, &bssid[..4], 3).is_err());
+    }
+
+    #[test]
+    fn test_get_station() {
+        let mac = [2, 0, 0, 0, 0, 1];
+        let request = get_station(28, 3, &mac).unwrap();
+        assert!(!request.nl_flags.contains(&NlmF::Dump));
+
+        let handle = request.nl_payload.get_payload().unwrap().get_attr_handle();
+        assert_eq!(
+            handle
+                .get_attr_payload_as::<u32>(Nl80211Attr::AttrIfindex)
+                .unwrap(),
+            3
+        );
+        assert_eq!(
+            handle
+                .get_attribute(Nl80211Attr::AttrMac)
+                .unwrap()
+                .nla_payload
+                .as_ref(),
+            &mac
+        );
+
+        assert!(get_station(28, 3, &mac[..5]).is_err());
+    }
+
+    #[test]
+    fn test_dumps() {
+        assert_eq!(
+            to_bytes(get_stations(28, 3).unwrap()),
+            vec![
+                28, 0, 0, 0, 28, 0, 1, 3, 0, 0, 0, 0, 0, 0, 0, 0, 17, 1, 0, 0, 8, 0, 3, 0, 3, 0, 0,
+                0,
+            ]
+        );
+        assert_eq!(
+            to_bytes(get_scan(28, 3).unwrap()),
+            vec![
+                28, 0, 0, 0, 28, 0, 1, 3, 0, 0, 0, 0, 0, 0, 0, 0, 32, 1, 0, 0, 8, 0, 3, 0, 3, 0, 0,
+                0,
+            ]
+        );
+        assert_eq!(
+            to_bytes(get_wiphys(28).unwrap()),
+            vec![24, 0, 0, 0, 28, 0, 1, 3, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 0, 0, 4, 0, 174, 0,]
+        );
+    }
+
+    #[test]
+    fn test_set_phy_channel() {
+        let request = set_phy_channel(28, 1, 5200, ChannelWidth::Width80).unwrap();
+        let handle = request.nl_payload.get_payload().unwrap().get_attr_handle();
+
+        let attr = |t| handle.get_attr_payload_as::<u32>(t).unwrap();
+        assert_eq!(handle.get_attrs().len(), 4);
+        assert_eq!(attr(Nl80211Attr::AttrWiphy), 1);
+        assert_eq!(attr(Nl80211Attr::AttrWiphyFreq), 5200);
+        assert_eq!(attr(Nl80211Attr::AttrChannelWidth), 3);
+        assert_eq!(attr(Nl80211Attr::AttrCenterFreq1), 5210);
+        assert!(handle.get_attribute(Nl80211Attr::AttrIfindex).is_none());
+
+        assert!(set_phy_channel(28, 0, 2484, ChannelWidth::Width40).is_err());
+    }
+
+    #[test]
+    fn test_get_reg() {
+        let payload = get_reg(28, None).unwrap().nl_payload;
+        let payload = payload.get_payload().unwrap();
+        assert_eq!(payload.cmd, Nl80211Cmd::CmdGetReg);
+        assert!(payload.get_attr_handle().iter().next().is_none());
+
+        let payload = get_reg(28, Some(1)).unwrap().nl_payload;
+        assert_eq!(
+            payload
+                .get_payload()
+                .unwrap()
+                .get_attr_handle()
+                .get_attr_payload_as::<u32>(Nl80211Attr::AttrWiphy)
+                .unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_set_reg() {
+        let request = set_reg(28, "de").unwrap();
+        assert_eq!(
+            request.nl_payload.get_payload().unwrap().cmd,
+            Nl80211Cmd::CmdReqSetReg
+        );
+        // The country code is sent upper case and nul terminated, padded to 4 bytes
+        assert_eq!(&to_bytes(request)[20..], &[7, 0, 33, 0, 68, 69, 0, 0]);
+
+        for invalid in ["", "D", "DEU", "00", "D1", "Ä"] {
+            assert!(set_reg(28, invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_set_channel_reg_domain() {
+        let reg_domain = RegDomain {
+            alpha2: Some("DE".to_string()),
+            rules: vec![crate::RegRule {
+                start_freq_khz: 5150000,
+                end_freq_khz: 5350000,
+                max_bandwidth_khz: 80000,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        assert!(set_channel(28, 3, 5180, ChannelWidth::Width80, Some(&reg_domain)).is_ok());
+        let err = set_channel(28, 3, 5745, ChannelWidth::Width20, Some(&reg_domain)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Channel 149 (20 MHz) not permitted in domain DE"
+        );
+        // Without a known domain the kernel decides
+        assert!(set_channel(28, 3, 5745, ChannelWidth::Width20, None).is_ok());
+    }
+
+    #[test]
+    fn test_set_rekey_data() {
+        let kek = [1; 16];
+        let kck = [2; 16];
+        let replay_ctr = [0, 0, 0, 0, 0, 0, 0, 5];
+        let request = set_rekey_data(28, 3, &kek, &kck, &replay_ctr).unwrap();
+        assert!(request.nl_flags.contains(&NlmF::Ack));
+
+        let payload = request.nl_payload.get_payload().unwrap();
+        assert_eq!(payload.cmd, Nl80211Cmd::CmdSetRekeyOffload);
+        let handle = payload.get_attr_handle();
+        let rekey_data = handle
+            .get_attribute(Nl80211Attr::AttrRekeyData)
+            .unwrap()
+            .get_attr_handle::<Nl80211RekeyData>()
+            .unwrap();
+        let nested = |t| {
+            rekey_data
+                .get_attribute(t)
+                .unwrap()
+                .nla_payload
+                .as_ref()
+                .to_vec()
+        };
+        assert_eq!(nested(Nl80211RekeyData::RekeyDataKek), kek);
+        assert_eq!(nested(Nl80211RekeyData::RekeyDataKck), kck);
+        assert_eq!(nested(Nl80211RekeyData::RekeyDataReplayCtr), replay_ctr);
+
+        // The nested attribute follows the ifindex and holds 20 + 20 + 12 bytes of keys
+        let bytes = to_bytes(request);
+        assert_eq!(&bytes[28..32], &[56, 0, 122, 128]);
+        assert_eq!(bytes.len(), 28 + 56);
+
+        assert!(set_rekey_data(28, 3, &[1; 32], &[2; 24], &replay_ctr).is_ok());
+        assert!(set_rekey_data(28, 3, &[1; 15], &kck, &replay_ctr).is_err());
+        assert!(set_rekey_data(28, 3, &kek, &[2; 20], &replay_ctr).is_err());
+        assert!(set_rekey_data(28, 3, &kek, &kck, &replay_ctr[..6]).is_err());
+    }
+
+    #[test]
+    fn test_channel_switch() {
+        let mut csa_ies = CsaIes::new();
+        csa_ies.csa_beacon(&[0x80, 0], &[37, 3, 1, 36, 5], &[4]);
+        let request =
+            channel_switch(28, 3, 5180, ChannelWidth::Width20, 5, true, &csa_ies).unwrap();
+        assert!(request.nl_flags.contains(&NlmF::Ack));
+
+        let payload = request.nl_payload.get_payload().unwrap();
+        assert_eq!(payload.cmd, Nl80211Cmd::CmdChannelSwitch);
+        let handle = payload.get_attr_handle();
+        let attr = |t| handle.get_attr_payload_as::<u32>(t).unwrap();
+        assert_eq!(attr(Nl80211Attr::AttrIfindex), 3);
+        assert_eq!(attr(Nl80211Attr::AttrWiphyFreq), 5180);
+        assert_eq!(attr(Nl80211Attr::AttrChSwitchCount), 5);
+        assert!(handle
+            .get_attribute(Nl80211Attr::AttrChSwitchBlockTx)
+            .is_some());
+        assert!(handle.get_attribute(Nl80211Attr::AttrCsaIes).is_some());
+
+        let request =
+            channel_switch(28, 3, 5180, ChannelWidth::Width20, 0, false, &CsaIes::new()).unwrap();
+        let handle = request.nl_payload.get_payload().unwrap().get_attr_handle();
+        assert!(handle
+            .get_attribute(Nl80211Attr::AttrChSwitchBlockTx)
+            .is_none());
+        assert!(handle.get_attribute(Nl80211Attr::AttrCsaIes).is_none());
+    }
+
+    #[test]
+    fn test_set_interface_type() {
+        let request = set_interface_type(28, 3, InterfaceType::Monitor).unwrap();
+        let handle = request.nl_payload.get_payload().unwrap().get_attr_handle();
+
+        assert_eq!(
+            request.nl_payload.get_payload().unwrap().cmd,
+            Nl80211Cmd::CmdSetInterface
+        );
+        assert_eq!(
+            handle
+                .get_attr_payload_as::<u32>(Nl80211Attr::AttrIfindex)
+                .unwrap(),
+            3
+        );
+        assert_eq!(
+            handle
+                .get_attr_payload_as::<u32>(Nl80211Attr::AttrIftype)
+                .unwrap(),
+            6
+        );
+    }
+
+    #[test]
+    fn test_set_monitor_flags() {
+        let flags = MonitorFlags {
+            control: true,
+            otherbss: true,
+            ..Default::default()
+        };
+        let request = set_monitor_flags(28, 3, flags).unwrap();
+        assert_eq!(
+            request.nl_payload.get_payload().unwrap().cmd,
+            Nl80211Cmd::CmdSetInterface
+        );
+
+        // The nested attribute follows the ifindex and holds the two flags without payload
+        let bytes = to_bytes(request);
+        assert_eq!(&bytes[28..], &[12, 0, 23, 128, 4, 0, 3, 0, 4, 0, 4, 0]);
+
+        // Clearing all flags sends an empty set
+        let request = set_monitor_flags(28, 3, MonitorFlags::default()).unwrap();
+        assert_eq!(&to_bytes(request)[28..], &[4, 0, 23, 128]);
+    }
+
+    #[test]
+    fn test_set_interface_name() {
+        let request = set_interface_name(28, 3, "wlan-ap").unwrap();
+        let bytes = to_bytes(request);
+        // The name is sent nul terminated
+        assert_eq!(
+            &bytes[28..],
+            &[12, 0, 4, 0, 119, 108, 97, 110, 45, 97, 112, 0]
+        );
+
+        assert!(set_interface_name(28, 3, "").is_err());
+        assert!(set_interface_name(28, 3, "wlan0/ap").is_err());
+        assert!(set_interface_name(28, 3, "wlan 0").is_err());
+        assert!(set_interface_name(28, 3, "a-very-long-name").is_err());
+        assert!(set_interface_name(28, 3, "a-long-but-fine").is_ok());
+    }
+
+    #[test]
+    fn test_create_interface() {
+        let wiphy = Wiphy {
+            index: Some(0),
+            supported_iftypes: vec![InterfaceType::Station],
+            software_iftypes: vec![InterfaceType::Monitor],
+            ..Default::default()
+        };
+
+        let request = create_interface(28, &wiphy, "mon0", InterfaceType::Monitor).unwrap();
+        let handle = request.nl_payload.get_payload().unwrap().get_attr_handle();
+        assert_eq!(
+            handle
+                .get_attr_payload_as_with_len::<String>(Nl80211Attr::AttrIfname)
+                .unwrap(),
+            "mon0"
+        );
+        assert!(create_interface(28, &wiphy, "wlan1", InterfaceType::Station).is_ok());
+
+        // Rejected before anything is sent to the kernel
+        assert!(create_interface(28, &wiphy, "ap0", InterfaceType::Ap).is_err());
+        let wiphy = Wiphy {
+            index: None,
+            ..wiphy
+        };
+        assert!(create_interface(28, &wiphy, "mon0", InterfaceType::Monitor).is_err());
+    }
+
+    #[test]
+    fn test_create_monitor() {
+        let flags = MonitorFlags {
+            fcsfail: true,
+            ..Default::default()
+        };
+        let request = create_monitor(28, 1, "mon0", flags).unwrap();
+        let payload = request.nl_payload.get_payload().unwrap();
+        assert_eq!(payload.cmd, Nl80211Cmd::CmdNewInterface);
+
+        let handle = payload.get_attr_handle();
+        let u32_attr = |attr| handle.get_attr_payload_as::<u32>(attr).unwrap();
+        assert_eq!(u32_attr(Nl80211Attr::AttrWiphy), 1);
+        assert_eq!(u32_attr(Nl80211Attr::AttrIftype), 6);
+        assert_eq!(
+            handle
+                .get_attr_payload_as_with_len::<String>(Nl80211Attr::AttrIfname)
+                .unwrap(),
+            "mon0"
+        );
+        let mntr_flags = handle
+            .get_attribute(Nl80211Attr::AttrMntrFlags)
+            .unwrap()
+            .get_attr_handle::<Nl80211MntrFlags>()
+            .unwrap();
+        assert_eq!(mntr_flags.get_attrs().len(), 1);
+        assert!(mntr_flags
+            .get_attribute(Nl80211MntrFlags::MntrFlagFcsfail)
+            .is_some());
+
+        assert!(create_monitor(28, 1, "mon/0", flags).is_err());
+    }
+
+    #[test]
+    fn test_delete_interface() {
+        let request = delete_interface(28, 5).unwrap();
+        assert_eq!(
+            request.nl_flags,
+            NlmFFlags::new(&[NlmF::Request, NlmF::Ack])
+        );
+
+        let payload = request.nl_payload.get_payload().unwrap();
+        assert_eq!(payload.cmd, Nl80211Cmd::CmdDelInterface);
+        assert_eq!(
+            payload
+                .get_attr_handle()
+                .get_attr_payload_as::<i32>(Nl80211Attr::AttrIfindex)
+                .unwrap(),
+            5
+        );
+    }
+}
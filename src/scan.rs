@@ -0,0 +1,351 @@
+use crate::attr::{Nl80211Attr, Nl80211ScanFlags, Nl80211SchedScanMatchAttr};
+use crate::cmd::Nl80211Cmd;
+use crate::error::Error;
+use crate::wiphy::Wiphy;
+
+use neli::consts::genl::Index;
+use neli::err::SerError;
+use neli::genl::{Genlmsghdr, Nlattr};
+use neli::types::{Buffer, GenlBuffer};
+
+/// Parameters of a scan request
+///
+/// An empty `ScanRequest` triggers a regular scan on all supported channels. Without any SSIDs
+/// added with [`ScanRequest::ssid`] the scan is passive, only networks sending beacons are found.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ScanRequest {
+    ssids: Vec<Vec<u8>>,
+    duration_tu: Option<u16>,
+    duration_mandatory: bool,
+    flags: u32,
+}
+
+impl ScanRequest {
+    /// Create an empty scan request
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Actively probe for the network `ssid`
+    ///
+    /// An empty `ssid` probes for any network (wildcard), which also finds hidden networks that
+    /// only respond to probe requests.
+    pub fn ssid(&mut self, ssid: &[u8]) -> &mut Self {
+        self.ssids.push(ssid.to_vec());
+        self
+    }
+
+    /// Set the time to dwell on each channel
+    ///
+    /// The kernel expects the duration in TUs (1024 µs), so `ms` is rounded up to the next TU.
+    /// Scans with a duration fail with [`Error::Unsupported`] on wiphys which can't control the
+    /// dwell time, see [`Wiphy::scan_dwell`].
+    pub fn duration_ms(&mut self, ms: u32) -> &mut Self {
+        let tu = (u64::from(ms) * 1000).div_ceil(1024);
+        self.duration_tu = Some(tu.try_into().unwrap_or(u16::MAX));
+        self
+    }
+
+    /// Require the driver to dwell exactly for the configured duration
+    ///
+    /// Without this the duration is only a hint. Has no effect unless a duration is set with
+    /// [`ScanRequest::duration_ms`].
+    pub fn duration_mandatory(&mut self, mandatory: bool) -> &mut Self {
+        self.duration_mandatory = mandatory;
+        self
+    }
+
+    /// Ask the driver to favour accuracy over scan time and power consumption
+    pub fn high_accuracy(&mut self, high_accuracy: bool) -> &mut Self {
+        self.set_flag(Nl80211ScanFlags::ScanFlagHighAccuracy, high_accuracy);
+        self
+    }
+
+    fn set_flag(&mut self, flag: Nl80211ScanFlags, set: bool) {
+        let flag = u32::from(u16::from(flag));
+        if set {
+            self.flags |= flag;
+        } else {
+            self.flags &= !flag;
+        }
+    }
+
+    /// Check that the wiphy of the scanning interface supports the requested options
+    pub(crate) fn check(&self, wiphy: &Wiphy) -> Result<(), Error> {
+        if self.duration_tu.is_some() && wiphy.scan_dwell != Some(true) {
+            return Err(Error::Unsupported);
+        }
+        Ok(())
+    }
+
+    /// Whether the request has options which need to be checked with [`ScanRequest::check`]
+    pub(crate) fn needs_check(&self) -> bool {
+        self.duration_tu.is_some()
+    }
+
+    pub(crate) fn attrs(&self) -> Result<GenlBuffer<Nl80211Attr, Buffer>, SerError> {
+        let mut attrs = GenlBuffer::new();
+        if !self.ssids.is_empty() {
+            let mut ssids = Nlattr::new(true, false, Nl80211Attr::AttrScanSsids, Vec::<u8>::new())?;
+            for (i, ssid) in self.ssids.iter().enumerate() {
+                ssids.add_nested_attribute(&Nlattr::new(
+                    false,
+                    false,
+                    Index::from(i as u16),
+                    ssid.as_slice(),
+                )?)?;
+            }
+            attrs.push(ssids);
+        }
+        if let Some(duration) = self.duration_tu {
+            attrs.push(Nlattr::new(
+                false,
+                false,
+                Nl80211Attr::AttrMeasurementDuration,
+                duration,
+            )?);
+            if self.duration_mandatory {
+                attrs.push(Nlattr::new(
+                    false,
+                    false,
+                    Nl80211Attr::AttrMeasurementDurationMandatory,
+                    (),
+                )?);
+            }
+        }
+        if self.flags != 0 {
+            attrs.push(Nlattr::new(
+                false,
+                false,
+                Nl80211Attr::AttrScanFlags,
+                self.flags,
+            )?);
+        }
+        Ok(attrs)
+    }
+}
+
+/// Parameters of a scheduled (periodic, offloaded) scan
+///
+/// The kernel only reports results of a scheduled scan if they match at least one of the match
+/// sets, or always if none are given.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchedScanRequest {
+    interval_ms: u32,
+    matches: Vec<(Vec<u8>, Option<i32>)>,
+}
+
+impl SchedScanRequest {
+    /// Create a scheduled scan request repeating every `interval_ms` milliseconds
+    pub fn new(interval_ms: u32) -> Self {
+        Self {
+            interval_ms,
+            matches: Vec::new(),
+        }
+    }
+
+    /// Add a match set for the network `ssid`
+    ///
+    /// With `min_rssi_dbm` set, BSSes of the network that are received weaker than the given
+    /// signal strength (dBm) are not reported.
+    pub fn match_ssid(&mut self, ssid: &[u8], min_rssi_dbm: Option<i32>) -> &mut Self {
+        self.matches.push((ssid.to_vec(), min_rssi_dbm));
+        self
+    }
+
+    pub(crate) fn attrs(&self) -> Result<GenlBuffer<Nl80211Attr, Buffer>, SerError> {
+        let mut attrs = GenlBuffer::new();
+        attrs.push(Nlattr::new(
+            false,
+            false,
+            Nl80211Attr::AttrSchedScanInterval,
+            self.interval_ms,
+        )?);
+        if !self.matches.is_empty() {
+            let mut matches = Nlattr::new(
+                true,
+                false,
+                Nl80211Attr::AttrSchedScanMatch,
+                Vec::<u8>::new(),
+            )?;
+            for (i, (ssid, min_rssi_dbm)) in self.matches.iter().enumerate() {
+                let mut set = Nlattr::new(true, false, Index::from(i as u16), Vec::<u8>::new())?;
+                set.add_nested_attribute(&Nlattr::new(
+                    false,
+                    false,
+                    Nl80211SchedScanMatchAttr::SchedScanMatchAttrSsid,
+                    ssid.as_slice(),
+                )?)?;
+                if let Some(rssi) = min_rssi_dbm {
+                    set.add_nested_attribute(&Nlattr::new(
+                        false,
+                        false,
+                        Nl80211SchedScanMatchAttr::SchedScanMatchAttrRssi,
+                        *rssi,
+                    )?)?;
+                }
+                matches.add_nested_attribute(&set)?;
+            }
+            attrs.push(matches);
+        }
+        Ok(attrs)
+    }
+}
+
+/// The outcome of a scan on `interface_index` if `event` from the "scan" multicast group ends it
+pub(crate) fn scan_outcome(
+    event: &Genlmsghdr<Nl80211Cmd, Nl80211Attr>,
+    interface_index: i32,
+) -> Option<Result<(), Error>> {
+    let outcome = match event.cmd {
+        Nl80211Cmd::CmdNewScanResults => Ok(()),
+        Nl80211Cmd::CmdScanAborted => Err(Error::ScanAborted),
+        _ => return None,
+    };
+    let ifindex = event
+        .get_attr_handle()
+        .get_attr_payload_as::<i32>(Nl80211Attr::AttrIfindex)
+        .ok()?;
+    (ifindex == interface_index).then_some(outcome)
+}
+
+#[cfg(test)]
+mod test_scan {
+    use super::*;
+    use neli::attr::Attribute;
+
+    #[test]
+    fn test_empty() {
+        assert!(ScanRequest::new().attrs().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_ssids() {
+        let attrs = ScanRequest::new().ssid(b"home").ssid(b"").attrs().unwrap();
+        let handle = attrs.get_attr_handle();
+        let ssids = handle
+            .get_attribute(Nl80211Attr::AttrScanSsids)
+            .unwrap()
+            .get_attr_handle::<Index>()
+            .unwrap();
+        let ssids: Vec<_> = ssids
+            .iter()
+            .map(|ssid| ssid.nla_payload.as_ref().to_vec())
+            .collect();
+
+        assert_eq!(ssids, vec![b"home".to_vec(), Vec::new()]);
+    }
+
+    #[test]
+    fn test_scan_outcome() {
+        let event = |cmd, ifindex: i32| {
+            let mut attrs = GenlBuffer::new();
+            attrs.push(Nlattr::new(false, false, Nl80211Attr::AttrIfindex, ifindex).unwrap());
+            Genlmsghdr::new(cmd, 1, attrs)
+        };
+
+        assert!(matches!(
+            scan_outcome(&event(Nl80211Cmd::CmdNewScanResults, 3), 3),
+            Some(Ok(()))
+        ));
+        assert!(matches!(
+            scan_outcome(&event(Nl80211Cmd::CmdScanAborted, 3), 3),
+            Some(Err(Error::ScanAborted))
+        ));
+        assert!(scan_outcome(&event(Nl80211Cmd::CmdTriggerScan, 3), 3).is_none());
+        assert!(scan_outcome(&event(Nl80211Cmd::CmdNewScanResults, 4), 3).is_none());
+    }
+
+    #[test]
+    fn test_duration() {
+        let attrs = ScanRequest::new()
+            .duration_ms(50)
+            .duration_mandatory(true)
+            .high_accuracy(true)
+            .attrs()
+            .unwrap();
+        let handle = attrs.get_attr_handle();
+
+        assert_eq!(
+            handle
+                .get_attr_payload_as::<u16>(Nl80211Attr::AttrMeasurementDuration)
+                .unwrap(),
+            49
+        );
+        let mandatory = handle
+            .get_attribute(Nl80211Attr::AttrMeasurementDurationMandatory)
+            .unwrap();
+        assert!(mandatory.payload().as_ref().is_empty());
+        assert_eq!(
+            handle
+                .get_attr_payload_as::<u32>(Nl80211Attr::AttrScanFlags)
+                .unwrap(),
+            1 << 10
+        );
+    }
+
+    #[test]
+    fn test_duration_check() {
+        let mut request = ScanRequest::new();
+        assert!(!request.needs_check());
+        assert!(request.check(&Wiphy::default()).is_ok());
+
+        request.duration_ms(50);
+        assert!(request.needs_check());
+        let mut wiphy = Wiphy::default();
+        assert!(matches!(request.check(&wiphy), Err(Error::Unsupported)));
+        wiphy.scan_dwell = Some(false);
+        assert!(matches!(request.check(&wiphy), Err(Error::Unsupported)));
+        wiphy.scan_dwell = Some(true);
+        assert!(request.check(&wiphy).is_ok());
+    }
+
+    #[test]
+    fn test_mandatory_without_duration() {
+        let attrs = ScanRequest::new().duration_mandatory(true).attrs().unwrap();
+        assert!(attrs.is_empty());
+    }
+
+    #[test]
+    fn test_sched_scan_matches() {
+        let attrs = SchedScanRequest::new(30000)
+            .match_ssid(b"home", Some(-70))
+            .match_ssid(b"office", None)
+            .attrs()
+            .unwrap();
+        let handle = attrs.get_attr_handle();
+
+        assert_eq!(
+            handle
+                .get_attr_payload_as::<u32>(Nl80211Attr::AttrSchedScanInterval)
+                .unwrap(),
+            30000
+        );
+        let matches = handle
+            .get_attribute(Nl80211Attr::AttrSchedScanMatch)
+            .unwrap()
+            .get_attr_handle::<Index>()
+            .unwrap();
+        let sets: Vec<_> = matches
+            .iter()
+            .map(|set| {
+                let set = set.get_attr_handle::<Nl80211SchedScanMatchAttr>().unwrap();
+                let ssid = set
+                    .get_attribute(Nl80211SchedScanMatchAttr::SchedScanMatchAttrSsid)
+                    .unwrap()
+                    .nla_payload
+                    .as_ref()
+                    .to_vec();
+                let rssi = set
+                    .get_attr_payload_as::<i32>(Nl80211SchedScanMatchAttr::SchedScanMatchAttrRssi)
+                    .ok();
+                (ssid, rssi)
+            })
+            .collect();
+
+        assert_eq!(
+            sets,
+            vec![(b"home".to_vec(), Some(-70)), (b"office".to_vec(), None)]
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use crate::bss::Bss;
+use crate::error::Error;
+use crate::socket::Socket;
+
+use std::time::{Duration, Instant};
+
+/// Fetches the scan results of an interface, optionally throttling the dumps
+///
+/// With a minimum interval set, calls within the interval after a dump return the cached
+/// results of that dump. Only the dump of the kernel's scan results is cached: the scan results
+/// themselves are updated by the kernel whenever a scan completes, independently of this cache.
+pub struct Scanner {
+    socket: Socket,
+    interface_index: i32,
+    cache: BssCache,
+}
+
+impl Scanner {
+    /// Create a scanner for a specific interface, dumping on every call
+    pub fn new(socket: Socket, interface_index: i32) -> Self {
+        Self {
+            socket,
+            interface_index,
+            cache: BssCache::default(),
+        }
+    }
+
+    /// Reuse the results of a dump for calls within `min_interval` after it
+    pub fn min_interval(&mut self, min_interval: Duration) -> &mut Self {
+        self.cache.min_interval = Some(min_interval);
+        self
+    }
+
+    /// Get the scan results of the interface, see [`Socket::get_bss_info`]
+    pub fn get_bss_list(&mut self) -> Result<Vec<Bss>, Error> {
+        let socket = &mut self.socket;
+        let interface_index = self.interface_index;
+        self.cache
+            .get_or_dump(Instant::now(), || socket.get_bss_info(interface_index))
+    }
+
+    /// The underlying socket, e.g. to trigger a scan
+    pub fn socket(&mut self) -> &mut Socket {
+        &mut self.socket
+    }
+}
+
+#[derive(Debug, Default)]
+struct BssCache {
+    min_interval: Option<Duration>,
+    last: Option<(Instant, Vec<Bss>)>,
+}
+
+impl BssCache {
+    fn get_or_dump<E>(
+        &mut self,
+        now: Instant,
+        dump: impl FnOnce() -> Result<Vec<Bss>, E>,
+    ) -> Result<Vec<Bss>, E> {
+        if let (Some(min_interval), Some((at, bss_list))) = (self.min_interval, &self.last) {
+            if now.saturating_duration_since(*at) < min_interval {
+                return Ok(bss_list.clone());
+            }
+        }
+        let bss_list = dump()?;
+        if self.min_interval.is_some() {
+            self.last = Some((now, bss_list.clone()));
+        }
+        Ok(bss_list)
+    }
+}
+
+#[cfg(test)]
+mod test_scanner {
+    use super::*;
+
+    #[test]
+    fn test_throttle() {
+        let mut cache = BssCache {
+            min_interval: Some(Duration::from_secs(5)),
+            last: None,
+        };
+        let start = Instant::now();
+        let mut dumps = 0;
+        let mut dump = |frequency| {
+            dumps += 1;
+            Ok::<_, ()>(vec![Bss {
+                frequency: Some(frequency),
+                ..Default::default()
+            }])
+        };
+
+        let first = cache.get_or_dump(start, || dump(2412)).unwrap();
+        let second = cache
+            .get_or_dump(start + Duration::from_secs(1), || dump(2437))
+            .unwrap();
+        assert_eq!(second, first);
+        let third = cache
+            .get_or_dump(start + Duration::from_secs(5), || dump(2462))
+            .unwrap();
+        assert_eq!(third[0].frequency, Some(2462));
+        assert_eq!(dumps, 2);
+    }
+
+    #[test]
+    fn test_no_interval() {
+        let mut cache = BssCache::default();
+        let now = Instant::now();
+        let mut dumps = 0;
+
+        for _ in 0..3 {
+            cache
+                .get_or_dump(now, || {
+                    dumps += 1;
+                    Ok::<_, ()>(Vec::new())
+                })
+                .unwrap();
+        }
+        assert_eq!(dumps, 3);
+    }
+}
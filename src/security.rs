@@ -0,0 +1,538 @@
+use std::fmt;
+
+/// Element id of the RSN element
+const ELEMENT_RSN: u8 = 48;
+/// Element id of vendor specific elements
+const ELEMENT_VENDOR: u8 = 221;
+
+/// OUI of the suites defined by IEEE 802.11
+const OUI_IEEE: [u8; 3] = [0x00, 0x0f, 0xac];
+/// OUI of Microsoft, used by the WPA (version 1) element
+const OUI_MICROSOFT: [u8; 3] = [0x00, 0x50, 0xf2];
+/// OUI of the Wi-Fi Alliance, used by the OWE transition mode element
+const OUI_WFA: [u8; 3] = [0x50, 0x6f, 0x9a];
+
+/// Vendor specific type of the WPA element
+const VENDOR_TYPE_WPA: u8 = 1;
+/// Vendor specific type of the OWE transition mode element
+const VENDOR_TYPE_OWE_TRANSITION: u8 = 0x1c;
+
+/// An authentication and key management (AKM) suite
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AkmSuite {
+    /// IEEE 802.1X (WPA/WPA2-Enterprise)
+    Ieee8021x,
+    /// Pre-shared key (WPA/WPA2-Personal)
+    Psk,
+    /// Fast transition with IEEE 802.1X
+    FtIeee8021x,
+    /// Fast transition with a pre-shared key
+    FtPsk,
+    /// IEEE 802.1X with SHA-256
+    Ieee8021xSha256,
+    /// Pre-shared key with SHA-256
+    PskSha256,
+    /// Simultaneous authentication of equals (WPA3-Personal)
+    Sae,
+    /// Fast transition with SAE
+    FtSae,
+    /// Suite B IEEE 802.1X with SHA-256
+    SuiteB,
+    /// Suite B IEEE 802.1X with SHA-384 (WPA3-Enterprise 192-bit)
+    SuiteB192,
+    /// Opportunistic wireless encryption (Enhanced Open)
+    Owe,
+    /// SAE with group dependent hash (WPA3-Personal with H2E)
+    SaeExtKey,
+    /// Any other suite, as the OUI followed by the suite type
+    Unknown(u32),
+}
+
+/// A cipher suite for encrypting data frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CipherSuite {
+    Wep40,
+    Tkip,
+    Ccmp,
+    Wep104,
+    BipCmac128,
+    Gcmp,
+    Gcmp256,
+    Ccmp256,
+    /// Any other suite, as the OUI followed by the suite type
+    Unknown(u32),
+}
+
+/// The kind of security of a network, see [`SecurityInfo::security_type`]
+///
+/// The personal variants use a pre-shared key or SAE, the enterprise variants IEEE 802.1X.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SecurityType {
+    /// No encryption
+    Open,
+    /// No encryption, but an OWE network of the same AP takes over capable stations
+    OweTransition,
+    /// Opportunistic wireless encryption (Enhanced Open)
+    Owe,
+    /// WEP
+    Wep,
+    /// WPA (version 1) with a pre-shared key
+    Wpa,
+    /// WPA and WPA2 with a pre-shared key
+    WpaWpa2,
+    /// WPA2 with a pre-shared key
+    Wpa2,
+    /// WPA3 with SAE
+    Wpa3,
+    /// WPA2 with a pre-shared key and WPA3 with SAE (transition mode)
+    Wpa2Wpa3Mixed,
+    /// WPA (version 1) with IEEE 802.1X
+    WpaEnterprise,
+    /// WPA2 with IEEE 802.1X
+    Wpa2Enterprise,
+    /// WPA3 with IEEE 802.1X in 192-bit mode
+    Wpa3Enterprise,
+    /// RSN or WPA elements with AKM suites none of the above use
+    Unknown,
+}
+
+impl SecurityType {
+    /// A short label as shown by network managers, e.g. "WPA2-PSK" or "WPA2/3-Mixed"
+    pub fn label(&self) -> &'static str {
+        match self {
+            SecurityType::Open => "Open",
+            SecurityType::OweTransition => "OWE-Transition",
+            SecurityType::Owe => "OWE",
+            SecurityType::Wep => "WEP",
+            SecurityType::Wpa => "WPA-PSK",
+            SecurityType::WpaWpa2 => "WPA/WPA2-PSK",
+            SecurityType::Wpa2 => "WPA2-PSK",
+            SecurityType::Wpa3 => "WPA3-SAE",
+            SecurityType::Wpa2Wpa3Mixed => "WPA2/3-Mixed",
+            SecurityType::WpaEnterprise => "WPA-Enterprise",
+            SecurityType::Wpa2Enterprise => "WPA2-Enterprise",
+            SecurityType::Wpa3Enterprise => "WPA3-Enterprise",
+            SecurityType::Unknown => "Unknown",
+        }
+    }
+}
+
+impl fmt::Display for SecurityType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// The security configuration a BSS advertises in its RSN and WPA elements
+///
+/// WEP networks don't advertise their security in elements, only
+/// [`Bss::security`](crate::Bss::security) detects them from the capability field.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SecurityInfo {
+    /// Whether the BSS has an RSN element (WPA2/WPA3)
+    pub rsn: bool,
+    /// Whether the BSS has a WPA (version 1) element
+    pub wpa: bool,
+    /// AKM suites of the RSN and WPA elements, without duplicates
+    pub akm_suites: Vec<AkmSuite>,
+    /// Pairwise cipher suites of the RSN and WPA elements, without duplicates
+    pub pairwise_ciphers: Vec<CipherSuite>,
+    /// Group cipher suite of the RSN element, or of the WPA element if there is none
+    pub group_cipher: Option<CipherSuite>,
+    /// Whether the BSS is part of an OWE transition mode pair
+    pub owe_transition: bool,
+    /// Whether the BSS requires WEP, i.e. encryption without RSN or WPA element
+    pub wep: bool,
+}
+
+impl SecurityInfo {
+    /// Derive the security configuration from raw information elements
+    ///
+    /// The elements are expected as returned by [`Bss::ies_raw`](crate::Bss::ies_raw). Decoding
+    /// stops at the first truncated element.
+    pub fn from_ies(ies: &[u8]) -> Self {
+        let mut res = Self::default();
+        let mut wpa = None;
+        for (id, data) in elements(ies) {
+            match (id, data) {
+                (ELEMENT_RSN, [_, _, suites @ ..]) => {
+                    res.rsn = true;
+                    res.add_suites(&OUI_IEEE, suites);
+                }
+                (ELEMENT_VENDOR, [o1, o2, o3, VENDOR_TYPE_WPA, _, _, suites @ ..])
+                    if [*o1, *o2, *o3] == OUI_MICROSOFT =>
+                {
+                    wpa = Some(suites);
+                }
+                (ELEMENT_VENDOR, [o1, o2, o3, VENDOR_TYPE_OWE_TRANSITION, ..])
+                    if [*o1, *o2, *o3] == OUI_WFA =>
+                {
+                    res.owe_transition = true;
+                }
+                _ => (),
+            }
+        }
+        // Decoded last so the group cipher of the RSN element takes precedence
+        if let Some(suites) = wpa {
+            res.wpa = true;
+            res.add_suites(&OUI_MICROSOFT, suites);
+        }
+        res
+    }
+
+    /// Add the suites of an RSN or WPA element, starting after its version
+    fn add_suites(&mut self, oui: &[u8; 3], data: &[u8]) {
+        let mut data = data;
+        let Some(group) = take_suite(&mut data) else {
+            return;
+        };
+        self.group_cipher.get_or_insert(cipher_suite(oui, group));
+        for suite in take_suite_list(&mut data) {
+            let cipher = cipher_suite(oui, suite);
+            if !self.pairwise_ciphers.contains(&cipher) {
+                self.pairwise_ciphers.push(cipher);
+            }
+        }
+        for suite in take_suite_list(&mut data) {
+            let akm = akm_suite(oui, suite);
+            if !self.akm_suites.contains(&akm) {
+                self.akm_suites.push(akm);
+            }
+        }
+    }
+
+    fn has_akm(&self, akms: &[AkmSuite]) -> bool {
+        self.akm_suites.iter().any(|akm| akms.contains(akm))
+    }
+
+    /// Classify the security configuration, as network managers do in their scan lists
+    pub fn security_type(&self) -> SecurityType {
+        use AkmSuite::*;
+
+        if !self.rsn && !self.wpa {
+            return if self.wep {
+                SecurityType::Wep
+            } else if self.owe_transition {
+                SecurityType::OweTransition
+            } else {
+                SecurityType::Open
+            };
+        }
+        let psk = self.has_akm(&[Psk, FtPsk, PskSha256]);
+        let sae = self.has_akm(&[Sae, FtSae, SaeExtKey]);
+        let enterprise = self.has_akm(&[Ieee8021x, FtIeee8021x, Ieee8021xSha256, SuiteB]);
+        if self.has_akm(&[Owe]) {
+            SecurityType::Owe
+        } else if sae && psk {
+            SecurityType::Wpa2Wpa3Mixed
+        } else if sae {
+            SecurityType::Wpa3
+        } else if psk && self.rsn && self.wpa {
+            SecurityType::WpaWpa2
+        } else if psk && self.rsn {
+            SecurityType::Wpa2
+        } else if psk {
+            SecurityType::Wpa
+        } else if self.has_akm(&[SuiteB192]) {
+            SecurityType::Wpa3Enterprise
+        } else if enterprise && self.rsn {
+            SecurityType::Wpa2Enterprise
+        } else if enterprise {
+            SecurityType::WpaEnterprise
+        } else {
+            SecurityType::Unknown
+        }
+    }
+
+    /// A short label as shown by network managers, e.g. "WPA2-PSK" or "WPA2/3-Mixed"
+    pub fn label(&self) -> &'static str {
+        self.security_type().label()
+    }
+}
+
+impl fmt::Display for SecurityInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// Iterate over the (id, data) pairs of information elements, stopping at a truncated one
+pub(crate) fn elements(mut ies: &[u8]) -> impl Iterator<Item = (u8, &[u8])> {
+    std::iter::from_fn(move || match ies {
+        [id, len, rest @ ..] if rest.len() >= usize::from(*len) => {
+            let (data, rest) = rest.split_at(usize::from(*len));
+            ies = rest;
+            Some((*id, data))
+        }
+        _ => None,
+    })
+}
+
+fn take_suite(data: &mut &[u8]) -> Option<[u8; 4]> {
+    let (suite, rest) = data.split_first_chunk::<4>()?;
+    *data = rest;
+    Some(*suite)
+}
+
+/// Take a suite count followed by that many suites, as far as they are present
+fn take_suite_list(data: &mut &[u8]) -> Vec<[u8; 4]> {
+    let Some((count, rest)) = data.split_first_chunk::<2>() else {
+        return Vec::new();
+    };
+    *data = rest;
+    (0..u16::from_le_bytes(*count))
+        .map_while(|_| take_suite(data))
+        .collect()
+}
+
+fn selector(suite: [u8; 4]) -> u32 {
+    u32::from_be_bytes(suite)
+}
+
+fn cipher_suite(oui: &[u8; 3], suite: [u8; 4]) -> CipherSuite {
+    if suite[..3] != oui[..] {
+        return CipherSuite::Unknown(selector(suite));
+    }
+    match (*oui, suite[3]) {
+        (OUI_IEEE | OUI_MICROSOFT, 1) => CipherSuite::Wep40,
+        (OUI_IEEE | OUI_MICROSOFT, 2) => CipherSuite::Tkip,
+        (OUI_IEEE | OUI_MICROSOFT, 4) => CipherSuite::Ccmp,
+        (OUI_IEEE | OUI_MICROSOFT, 5) => CipherSuite::Wep104,
+        (OUI_IEEE, 6) => CipherSuite::BipCmac128,
+        (OUI_IEEE, 8) => CipherSuite::Gcmp,
+        (OUI_IEEE, 9) => CipherSuite::Gcmp256,
+        (OUI_IEEE, 10) => CipherSuite::Ccmp256,
+        _ => CipherSuite::Unknown(selector(suite)),
+    }
+}
+
+/// Decode a suite selector as used by nl80211, e.g. `0x000fac04` for CCMP
+impl From<u32> for CipherSuite {
+    fn from(selector: u32) -> Self {
+        cipher_suite(&OUI_IEEE, selector.to_be_bytes())
+    }
+}
+
+/// Encode the suite selector as used by nl80211, the IEEE OUI followed by the suite type
+impl From<CipherSuite> for u32 {
+    fn from(cipher: CipherSuite) -> Self {
+        let suite_type = match cipher {
+            CipherSuite::Wep40 => 1,
+            CipherSuite::Tkip => 2,
+            CipherSuite::Ccmp => 4,
+            CipherSuite::Wep104 => 5,
+            CipherSuite::BipCmac128 => 6,
+            CipherSuite::Gcmp => 8,
+            CipherSuite::Gcmp256 => 9,
+            CipherSuite::Ccmp256 => 10,
+            CipherSuite::Unknown(selector) => return selector,
+        };
+        selector([OUI_IEEE[0], OUI_IEEE[1], OUI_IEEE[2], suite_type])
+    }
+}
+
+fn akm_suite(oui: &[u8; 3], suite: [u8; 4]) -> AkmSuite {
+    if suite[..3] != oui[..] {
+        return AkmSuite::Unknown(selector(suite));
+    }
+    match (*oui, suite[3]) {
+        (OUI_IEEE | OUI_MICROSOFT, 1) => AkmSuite::Ieee8021x,
+        (OUI_IEEE | OUI_MICROSOFT, 2) => AkmSuite::Psk,
+        (OUI_IEEE, 3) => AkmSuite::FtIeee8021x,
+        (OUI_IEEE, 4) => AkmSuite::FtPsk,
+        (OUI_IEEE, 5) => AkmSuite::Ieee8021xSha256,
+        (OUI_IEEE, 6) => AkmSuite::PskSha256,
+        (OUI_IEEE, 8) => AkmSuite::Sae,
+        (OUI_IEEE, 9) => AkmSuite::FtSae,
+        (OUI_IEEE, 11) => AkmSuite::SuiteB,
+        (OUI_IEEE, 12) => AkmSuite::SuiteB192,
+        (OUI_IEEE, 18) => AkmSuite::Owe,
+        (OUI_IEEE, 24) => AkmSuite::SaeExtKey,
+        _ => AkmSuite::Unknown(selector(suite)),
+    }
+}
+
+/// Decode a suite selector as used by nl80211, e.g. `0x000fac08` for SAE
+impl From<u32> for AkmSuite {
+    fn from(selector: u32) -> Self {
+        akm_suite(&OUI_IEEE, selector.to_be_bytes())
+    }
+}
+
+/// Encode the suite selector as used by nl80211, the IEEE OUI followed by the suite type
+impl From<AkmSuite> for u32 {
+    fn from(akm: AkmSuite) -> Self {
+        let suite_type = match akm {
+            AkmSuite::Ieee8021x => 1,
+            AkmSuite::Psk => 2,
+            AkmSuite::FtIeee8021x => 3,
+            AkmSuite::FtPsk => 4,
+            AkmSuite::Ieee8021xSha256 => 5,
+            AkmSuite::PskSha256 => 6,
+            AkmSuite::Sae => 8,
+            AkmSuite::FtSae => 9,
+            AkmSuite::SuiteB => 11,
+            AkmSuite::SuiteB192 => 12,
+            AkmSuite::Owe => 18,
+            AkmSuite::SaeExtKey => 24,
+            AkmSuite::Unknown(selector) => return selector,
+        };
+        selector([OUI_IEEE[0], OUI_IEEE[1], OUI_IEEE[2], suite_type])
+    }
+}
+
+#[cfg(test)]
+mod test_security {
+    use super::*;
+
+    // SSID "cafe" and supported rates
+    const OPEN: [u8; 16] = [
+        0, 4, 99, 97, 102, 101, 1, 8, 130, 132, 139, 150, 12, 18, 24, 36,
+    ];
+
+    fn beacon(elements: &[&[u8]]) -> Vec<u8> {
+        let mut ies = OPEN.to_vec();
+        for element in elements {
+            ies.extend_from_slice(element);
+        }
+        ies
+    }
+
+    #[test]
+    fn test_open() {
+        let security = SecurityInfo::from_ies(&OPEN);
+        assert_eq!(security, SecurityInfo::default());
+        assert_eq!(security.to_string(), "Open");
+        assert_eq!(security.security_type(), SecurityType::Open);
+    }
+
+    #[test]
+    fn test_wpa2_psk() {
+        let security = SecurityInfo::from_ies(&beacon(&[&[
+            48, 20, 1, 0, 0, 15, 172, 4, 1, 0, 0, 15, 172, 4, 1, 0, 0, 15, 172, 2, 12, 0,
+        ]]));
+
+        assert!(security.rsn);
+        assert!(!security.wpa);
+        assert_eq!(security.akm_suites, vec![AkmSuite::Psk]);
+        assert_eq!(security.pairwise_ciphers, vec![CipherSuite::Ccmp]);
+        assert_eq!(security.group_cipher, Some(CipherSuite::Ccmp));
+        assert_eq!(security.to_string(), "WPA2-PSK");
+        assert_eq!(security.security_type(), SecurityType::Wpa2);
+    }
+
+    #[test]
+    fn test_wpa3_sae() {
+        // Management frame protection required
+        let security = SecurityInfo::from_ies(&beacon(&[&[
+            48, 20, 1, 0, 0, 15, 172, 4, 1, 0, 0, 15, 172, 4, 1, 0, 0, 15, 172, 8, 192, 0,
+        ]]));
+
+        assert_eq!(security.akm_suites, vec![AkmSuite::Sae]);
+        assert_eq!(security.to_string(), "WPA3-SAE");
+        assert_eq!(security.security_type(), SecurityType::Wpa3);
+    }
+
+    #[test]
+    fn test_mixed() {
+        let mixed = SecurityInfo::from_ies(&beacon(&[&[
+            48, 24, 1, 0, 0, 15, 172, 4, 1, 0, 0, 15, 172, 4, 2, 0, 0, 15, 172, 2, 0, 15, 172, 8,
+            128, 0,
+        ]]));
+        assert_eq!(mixed.akm_suites, vec![AkmSuite::Psk, AkmSuite::Sae]);
+        assert_eq!(mixed.to_string(), "WPA2/3-Mixed");
+        assert_eq!(mixed.security_type(), SecurityType::Wpa2Wpa3Mixed);
+
+        // WPA element with TKIP next to an RSN element with TKIP as group cipher
+        let wpa = SecurityInfo::from_ies(&beacon(&[
+            &[
+                221, 22, 0, 80, 242, 1, 1, 0, 0, 80, 242, 2, 1, 0, 0, 80, 242, 2, 1, 0, 0, 80, 242,
+                2,
+            ],
+            &[
+                48, 24, 1, 0, 0, 15, 172, 2, 2, 0, 0, 15, 172, 4, 0, 15, 172, 2, 1, 0, 0, 15, 172,
+                2, 12, 0,
+            ],
+        ]));
+        assert!(wpa.rsn && wpa.wpa);
+        assert_eq!(wpa.akm_suites, vec![AkmSuite::Psk]);
+        assert_eq!(
+            wpa.pairwise_ciphers,
+            vec![CipherSuite::Ccmp, CipherSuite::Tkip]
+        );
+        assert_eq!(wpa.group_cipher, Some(CipherSuite::Tkip));
+        assert_eq!(wpa.to_string(), "WPA/WPA2-PSK");
+        assert_eq!(wpa.security_type(), SecurityType::WpaWpa2);
+    }
+
+    #[test]
+    fn test_owe() {
+        // Open BSS pointing to the hidden OWE BSS 02:00:00:00:00:01 "owe"
+        let open = SecurityInfo::from_ies(&beacon(&[&[
+            221, 14, 80, 111, 154, 28, 2, 0, 0, 0, 0, 1, 3, 111, 119, 101,
+        ]]));
+        assert!(open.owe_transition);
+        assert_eq!(open.to_string(), "OWE-Transition");
+
+        let owe = SecurityInfo::from_ies(&beacon(&[&[
+            48, 20, 1, 0, 0, 15, 172, 4, 1, 0, 0, 15, 172, 4, 1, 0, 0, 15, 172, 18, 192, 0,
+        ]]));
+        assert_eq!(owe.akm_suites, vec![AkmSuite::Owe]);
+        assert_eq!(owe.to_string(), "OWE");
+        assert_eq!(owe.security_type(), SecurityType::Owe);
+    }
+
+    #[test]
+    fn test_truncated() {
+        // The AKM list claims two suites but only has one, the next element is cut off
+        let security = SecurityInfo::from_ies(&beacon(&[
+            &[
+                48, 18, 1, 0, 0, 15, 172, 4, 1, 0, 0, 15, 172, 4, 2, 0, 0, 15, 172, 2,
+            ],
+            &[221, 10, 0, 80],
+        ]));
+
+        assert_eq!(security.akm_suites, vec![AkmSuite::Psk]);
+        assert_eq!(security.to_string(), "WPA2-PSK");
+    }
+
+    #[test]
+    fn test_cipher_selector() {
+        assert_eq!(CipherSuite::from(0x000fac04), CipherSuite::Ccmp);
+        assert_eq!(CipherSuite::from(0x000fac09), CipherSuite::Gcmp256);
+        // The suite type alone is not enough, the OUI has to match as well
+        assert_eq!(
+            CipherSuite::from(0x0050f204),
+            CipherSuite::Unknown(0x0050f204)
+        );
+        assert_eq!(
+            CipherSuite::from(0x000fac0d),
+            CipherSuite::Unknown(0x000fac0d)
+        );
+        for cipher in [
+            CipherSuite::Ccmp,
+            CipherSuite::Ccmp256,
+            CipherSuite::Unknown(0x00147201),
+        ] {
+            assert_eq!(CipherSuite::from(u32::from(cipher)), cipher);
+        }
+    }
+
+    #[test]
+    fn test_akm_selector() {
+        assert_eq!(AkmSuite::from(0x000fac08), AkmSuite::Sae);
+        assert_eq!(AkmSuite::from(0x0050f202), AkmSuite::Unknown(0x0050f202));
+        for akm in [
+            AkmSuite::Psk,
+            AkmSuite::SaeExtKey,
+            AkmSuite::Unknown(0x00147201),
+        ] {
+            assert_eq!(AkmSuite::from(u32::from(akm)), akm);
+        }
+        assert_eq!(u32::from(AkmSuite::Owe), 0x000fac12);
+    }
+}
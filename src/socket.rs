@@ -1,30 +1,209 @@
 use crate::attr::Nl80211Attr;
-use crate::bss::Bss;
+use crate::bss::{bss_in_band, dedup_bss, Bss};
+use crate::channel::{ChannelWidth, FrequencyBand};
 use crate::cmd::Nl80211Cmd;
-use crate::interface::Interface;
+use crate::connect::{connect_outcome, disconnect_result, ConnectParams};
+use crate::csa::CsaIes;
+use crate::error::{response_error, Error, ENODEV, ENOENT, EOPNOTSUPP};
+use crate::event::{McastGroup, WifiEvent, EVENT_GROUPS};
+use crate::interface::{Interface, InterfaceType, MonitorFlags};
+use crate::mesh::MeshParam;
+use crate::power::{power_save_enabled, TxPowerSetting};
+use crate::reg::RegDomain;
+use crate::request::{self, Request};
+use crate::scan::{scan_outcome, ScanRequest, SchedScanRequest};
 use crate::station::Station;
-use crate::{NL_80211_GENL_NAME, NL_80211_GENL_VERSION};
+use crate::survey::Survey;
+use crate::wiphy::Wiphy;
+use crate::NL_80211_GENL_NAME;
 
-use neli::consts::genl::{CtrlAttr, CtrlCmd};
-use neli::consts::{nl::GenlId, nl::NlmF, nl::NlmFFlags, nl::Nlmsg, socket::NlFamily};
+use neli::consts::genl::{CtrlAttr, CtrlAttrMcastGrp, CtrlCmd, Index};
+use neli::consts::nl::{GenlId, NlType, NlmF, Nlmsg};
+use neli::consts::socket::NlFamily;
 use neli::err::NlError;
-use neli::genl::{Genlmsghdr, Nlattr};
+use neli::genl::Genlmsghdr;
 use neli::nl::{NlPayload, Nlmsghdr};
 use neli::socket::NlSocketHandle;
-use neli::types::GenlBuffer;
+use neli::types::Buffer;
+use neli::ToBytes;
+
+use std::fmt::Debug;
 
 /// A generic netlink socket to send commands and receive messages
+///
+/// Dropping the socket closes it, which also ends all of its multicast group memberships.
 pub struct Socket {
     pub(crate) sock: NlSocketHandle,
     pub(crate) family_id: u16,
+    mcast_groups: Vec<u32>,
+    pub(crate) validate_channels: bool,
+    pub(crate) seq: u32,
 }
 
 impl Socket {
     /// Create a new nl80211 socket with netlink
-    pub fn connect() -> Result<Self, NlError<GenlId, Genlmsghdr<CtrlCmd, CtrlAttr>>> {
+    pub fn connect() -> Result<Self, Error> {
         let mut sock = NlSocketHandle::connect(NlFamily::Generic, None, &[])?;
         let family_id = sock.resolve_genl_family(NL_80211_GENL_NAME)?;
-        Ok(Self { sock, family_id })
+        Ok(Self {
+            sock,
+            family_id,
+            mcast_groups: Vec::new(),
+            validate_channels: false,
+            seq: 0,
+        })
+    }
+
+    /// The resolved generic netlink family id of nl80211
+    pub fn family_id(&self) -> u16 {
+        self.family_id
+    }
+
+    /// Resolve the id of a named nl80211 multicast group (e.g. `"scan"`, `"mlme"`, `"config"`)
+    ///
+    /// The returned id can be used to join the group on a netlink socket to receive events.
+    pub fn resolve_mcast_group(&mut self, name: &str) -> Result<u32, Error> {
+        self.resolve_mcast_groups(&[name])?[0].ok_or_else(|| unresolved_group(name))
+    }
+
+    /// Resolve the ids of several named multicast groups, `None` for groups nl80211 doesn't offer
+    fn resolve_mcast_groups(&mut self, names: &[&str]) -> Result<Vec<Option<u32>>, Error> {
+        let seq = self.send(request::get_family()?)?;
+
+        let mut retval = vec![None; names.len()];
+        for response in responses(
+            self.sock
+                .iter::<GenlId, Genlmsghdr<CtrlCmd, CtrlAttr>>(true),
+            seq,
+            false,
+        ) {
+            if let NlPayload::Payload(family) = response?.nl_payload {
+                for (id, name) in retval.iter_mut().zip(names) {
+                    *id = id.or_else(|| mcast_group_id(&family, name));
+                }
+            }
+        }
+
+        Ok(retval)
+    }
+
+    /// Join the named nl80211 multicast group to receive its events on this socket
+    ///
+    /// Joining a group the socket is already a member of does nothing. Returns the group id.
+    pub fn join_mcast_group(&mut self, name: &str) -> Result<u32, Error> {
+        let id = self.resolve_mcast_group(name)?;
+        self.join_mcast_group_id(id)?;
+        Ok(id)
+    }
+
+    /// Join every multicast group carrying [`WifiEvent`](crate::WifiEvent)s, returns their ids
+    ///
+    /// These are the "config", "scan", "mlme", "regulatory" and "vendor" groups. The "vendor"
+    /// group is skipped if nl80211 doesn't offer it, all other groups are required. The events
+    /// are received with `next_event`.
+    pub fn join_event_groups(&mut self) -> Result<Vec<u32>, Error> {
+        let ids = event_group_ids(&self.resolve_mcast_groups(&EVENT_GROUPS)?)?;
+        for id in &ids {
+            self.join_mcast_group_id(*id)?;
+        }
+        Ok(ids)
+    }
+
+    /// Join the given multicast groups to receive their events with `next_event`
+    ///
+    /// The ids of the groups are assigned by the kernel and resolved by name, they are returned
+    /// in the order of `groups`. Fails without joining any group if nl80211 doesn't offer one
+    /// of them, e.g. [`McastGroup::Vendor`].
+    pub fn subscribe_events(&mut self, groups: &[McastGroup]) -> Result<Vec<u32>, Error> {
+        let names: Vec<_> = groups.iter().map(|group| group.name()).collect();
+        let ids = self
+            .resolve_mcast_groups(&names)?
+            .into_iter()
+            .zip(&names)
+            .map(|(id, name)| id.ok_or_else(|| unresolved_group(name)))
+            .collect::<Result<Vec<_>, _>>()?;
+        for id in &ids {
+            self.join_mcast_group_id(*id)?;
+        }
+        Ok(ids)
+    }
+
+    /// Wait for the next event of the multicast groups the socket has joined
+    ///
+    /// This blocks until an event arrives. Events which arrive while the socket waits for the
+    /// response to a request are dropped, so events are best received on a socket of their own.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use neli_wifi::{McastGroup, Socket, WifiEvent};
+    /// # use std::error::Error;
+    ///
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    ///     let mut socket = Socket::connect()?;
+    ///     socket.subscribe_events(&[McastGroup::Mlme])?;
+    ///     loop {
+    ///         if let WifiEvent::Disconnect { interface_index, .. } = socket.next_event()? {
+    ///             println!("{} disconnected", interface_index);
+    ///         }
+    ///     }
+    /// # }
+    /// ```
+    pub fn next_event(&mut self) -> Result<WifiEvent, Error> {
+        if self.mcast_groups.is_empty() {
+            return Err(Error::Netlink(NlError::new("Not subscribed to any events")));
+        }
+
+        loop {
+            match self.sock.recv::<u16, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>() {
+                // Events are numbered 0, other numbers belong to responses to requests
+                Ok(Some(event)) if event.nl_seq != 0 => (),
+                Ok(Some(event)) => {
+                    if let NlPayload::Payload(event) = event.nl_payload {
+                        return Ok((&event).try_into()?);
+                    }
+                }
+                Ok(None) => return Err(Error::Netlink(NlError::new("No event received"))),
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    fn join_mcast_group_id(&mut self, id: u32) -> Result<(), Error> {
+        if insert_group(&mut self.mcast_groups, id) {
+            if let Err(err) = self.sock.add_mcast_membership(&[id]) {
+                self.mcast_groups.pop();
+                return Err(err.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Ids of the multicast groups the socket has joined, in the order they were joined
+    ///
+    /// See [`Socket::resolve_mcast_group`] for the ids of the named groups.
+    pub fn joined_groups(&self) -> &[u32] {
+        &self.mcast_groups
+    }
+
+    /// Leave all multicast groups joined with [`Socket::join_mcast_group`]
+    pub fn leave_all_mcast(&mut self) -> Result<(), Error> {
+        self.sock.drop_mcast_membership(&self.mcast_groups)?;
+        self.mcast_groups.clear();
+        Ok(())
+    }
+
+    /// Close the socket, leaving all multicast groups and releasing its file descriptor
+    ///
+    /// This is what dropping the socket does as well, but at a well defined point and with
+    /// errors reported. The socket is consumed, so it can't be used afterwards.
+    pub fn close(mut self) -> Result<(), Error> {
+        self.leave_all_mcast()
+    }
+
+    /// Returns the underlying generic netlink socket, see also `From<Socket>`
+    pub fn into_inner(self) -> NlSocketHandle {
+        self.sock
     }
 
     /// Get information for all your wifi interfaces
@@ -43,34 +222,25 @@ impl Socket {
     /// #   Ok(())
     /// # }
     ///```
-    pub fn get_interfaces_info(&mut self) -> Result<Vec<Interface>, NlError> {
-        let msghdr = Genlmsghdr::<Nl80211Cmd, Nl80211Attr>::new(
-            Nl80211Cmd::CmdGetInterface,
-            NL_80211_GENL_VERSION,
-            GenlBuffer::new(),
-        );
+    pub fn get_interfaces_info(&mut self) -> Result<Vec<Interface>, Error> {
+        let seq = self.send(request::get_interfaces(self.family_id))?;
 
-        let nlhdr = {
-            let len = None;
-            let nl_type = self.family_id;
-            let flags = NlmFFlags::new(&[NlmF::Request, NlmF::Dump]);
-            let seq = None;
-            let pid = None;
-            let payload = NlPayload::Payload(msghdr);
-            Nlmsghdr::new(len, nl_type, flags, seq, pid, payload)
-        };
-
-        self.sock.send(nlhdr)?;
-
-        let iter = self
-            .sock
-            .iter::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(false);
+        let iter = responses(
+            self.sock
+                .iter::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(true),
+            seq,
+            false,
+        );
         let mut interfaces = Vec::new();
         for response in iter {
-            let response = response.unwrap();
+            let response = response?;
             match response.nl_type {
                 Nlmsg::Noop => (),
-                Nlmsg::Error => panic!("Error"),
+                Nlmsg::Error => {
+                    if let Some(err) = response_error(response.nl_payload) {
+                        return Err(err);
+                    }
+                }
                 Nlmsg::Done => break,
                 _ => {
                     let handle = response.nl_payload.get_payload().unwrap().get_attr_handle();
@@ -82,8 +252,42 @@ impl Socket {
         Ok(interfaces)
     }
 
+    /// Get information about a single interface by its index
+    ///
+    /// Unlike `get_interfaces_info` this doesn't dump all interfaces but has the kernel look up
+    /// just this one. Returns `None` if there is no interface with this index (`ENODEV`).
+    pub fn get_interface_info(&mut self, interface_index: i32) -> Result<Option<Interface>, Error> {
+        let seq = self.send(request::get_interface(self.family_id, interface_index)?)?;
+
+        let iter = responses(
+            self.sock
+                .iter::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(true),
+            seq,
+            false,
+        );
+
+        let mut retval = None;
+        for response in iter {
+            let response = match response {
+                Ok(response) => response,
+                Err(NlError::Nlmsgerr(err)) if err.error == -ENODEV => return Ok(None),
+                Err(err) => return Err(err.into()),
+            };
+            if let NlPayload::Payload(msg) = response.nl_payload {
+                retval = Some(msg.get_attr_handle().try_into()?);
+            }
+        }
+
+        Ok(retval)
+    }
+
     /// Get access point information for a specific interface
     ///
+    /// An interface in station mode usually has a single station, the access point it is
+    /// connected to, while access point and mesh interfaces report one station per peer.
+    /// Monitor interfaces, which have no stations, and interfaces whose driver doesn't support
+    /// station queries (`EOPNOTSUPP`) report no stations instead of an error.
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -104,93 +308,114 @@ impl Socket {
     /// #   Ok(())
     /// # }
     ///```
-    pub fn get_station_info(&mut self, interface_index: i32) -> Result<Vec<Station>, NlError> {
-        let msghdr = Genlmsghdr::<Nl80211Cmd, Nl80211Attr>::new(
-            Nl80211Cmd::CmdGetStation,
-            NL_80211_GENL_VERSION,
-            {
-                let mut attrs = GenlBuffer::new();
-                attrs.push(
-                    Nlattr::new(false, false, Nl80211Attr::AttrIfindex, interface_index).unwrap(),
-                );
-                attrs
-            },
+    pub fn get_station_info(&mut self, interface_index: i32) -> Result<Vec<Station>, Error> {
+        let seq = self.send(request::get_stations(self.family_id, interface_index)?)?;
+
+        collect_stations(responses(
+            self.sock
+                .iter::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(true),
+            seq,
+            false,
+        ))
+    }
+
+    /// Get information about a single station of a specific interface by its MAC address
+    ///
+    /// Unlike `get_station_info` this doesn't dump all stations but has the kernel look up just
+    /// this one. Returns `None` if no station with this address is associated (`ENOENT`).
+    pub fn get_station_info_for(
+        &mut self,
+        interface_index: i32,
+        mac: &[u8],
+    ) -> Result<Option<Station>, Error> {
+        let request = request::get_station(self.family_id, interface_index, mac)?;
+        let seq = self.send(request)?;
+
+        let iter = responses(
+            self.sock
+                .iter::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(true),
+            seq,
+            false,
         );
 
-        let nlhdr = {
-            let len = None;
-            let nl_type = self.family_id;
-            let flags = NlmFFlags::new(&[NlmF::Request, NlmF::Dump]);
-            let seq = None;
-            let pid = None;
-            let payload = NlPayload::Payload(msghdr);
-            Nlmsghdr::new(len, nl_type, flags, seq, pid, payload)
-        };
+        let mut retval = None;
+        for response in iter {
+            let response = match response {
+                Ok(response) => response,
+                Err(NlError::Nlmsgerr(err)) if err.error == -ENOENT => return Ok(None),
+                Err(err) => return Err(err.into()),
+            };
+            if let NlPayload::Payload(msg) = response.nl_payload {
+                retval = Some(msg.get_attr_handle().try_into()?);
+            }
+        }
 
-        self.sock.send(nlhdr)?;
+        Ok(retval)
+    }
+
+    /// Get all BSSes the kernel has cached from scans on a specific interface
+    ///
+    /// Every BSS in the dump is returned in the order the kernel reports them. The cache is only
+    /// filled by scans, see `trigger_scan`.
+    pub fn get_bss_info(&mut self, interface_index: i32) -> Result<Vec<Bss>, Error> {
+        let seq = self.send(request::get_scan(self.family_id, interface_index)?)?;
+
+        let iter = responses(
+            self.sock
+                .iter::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(true),
+            seq,
+            false,
+        );
 
-        let iter = self
-            .sock
-            .iter::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(false);
         let mut retval = Vec::new();
+
         for response in iter {
-            let response = response.unwrap();
+            let response = response?;
             match response.nl_type {
                 Nlmsg::Noop => (),
-                Nlmsg::Error => panic!("Error"),
-                Nlmsg::Done => break,
-                _ => {
-                    retval.push(
-                        response
-                            .nl_payload
-                            .get_payload()
-                            .unwrap()
-                            .get_attr_handle()
-                            .try_into()?,
-                    );
+                Nlmsg::Error => {
+                    if let Some(err) = response_error(response.nl_payload) {
+                        return Err(err);
+                    }
                 }
-            };
+                Nlmsg::Done => break,
+                _ => retval.push(
+                    response
+                        .nl_payload
+                        .get_payload()
+                        .unwrap()
+                        .get_attr_handle()
+                        .try_into()?,
+                ),
+            }
         }
 
         Ok(retval)
     }
 
-    pub fn get_bss_info(&mut self, interface_index: i32) -> Result<Vec<Bss>, NlError> {
-        let msghdr = Genlmsghdr::<Nl80211Cmd, Nl80211Attr>::new(
-            Nl80211Cmd::CmdGetScan,
-            NL_80211_GENL_VERSION,
-            {
-                let mut attrs = GenlBuffer::new();
-                attrs.push(
-                    Nlattr::new(false, false, Nl80211Attr::AttrIfindex, interface_index).unwrap(),
-                );
-                attrs
-            },
-        );
-
-        let nlhdr = {
-            let len = None;
-            let nl_type = self.family_id;
-            let flags = NlmFFlags::new(&[NlmF::Request, NlmF::Dump]);
-            let seq = None;
-            let pid = None;
-            let payload = NlPayload::Payload(msghdr);
-            Nlmsghdr::new(len, nl_type, flags, seq, pid, payload)
-        };
-
-        self.sock.send(nlhdr)?;
+    /// Get the channel surveys of a specific interface, one per channel the driver reports
+    ///
+    /// Many drivers only survey the channel in use unless a scan visited the others.
+    pub fn get_survey_info(&mut self, interface_index: i32) -> Result<Vec<Survey>, Error> {
+        let seq = self.send(request::get_survey(self.family_id, interface_index)?)?;
 
-        let iter = self
-            .sock
-            .iter::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(false);
+        let iter = responses(
+            self.sock
+                .iter::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(true),
+            seq,
+            false,
+        );
 
         let mut retval = Vec::new();
-
         for response in iter {
-            let response = response.unwrap();
+            let response = response?;
             match response.nl_type {
                 Nlmsg::Noop => (),
-                Nlmsg::Error => panic!("Error"),
+                Nlmsg::Error => {
+                    if let Some(err) = response_error(response.nl_payload) {
+                        return Err(err);
+                    }
+                }
                 Nlmsg::Done => break,
                 _ => retval.push(
                     response
@@ -205,6 +430,764 @@ impl Socket {
 
         Ok(retval)
     }
+
+    /// Get information about all wireless physical devices (phys) and their capabilities
+    pub fn get_wiphy_info(&mut self) -> Result<Vec<Wiphy>, Error> {
+        let seq = self.send(request::get_wiphys(self.family_id)?)?;
+
+        let iter = responses(
+            self.sock
+                .iter::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(true),
+            seq,
+            false,
+        );
+
+        let mut retval: Vec<Wiphy> = Vec::new();
+
+        for response in iter {
+            let response = response?;
+            match response.nl_type {
+                Nlmsg::Noop => (),
+                Nlmsg::Error => {
+                    if let Some(err) = response_error(response.nl_payload) {
+                        return Err(err);
+                    }
+                }
+                Nlmsg::Done => break,
+                _ => {
+                    // A split dump describes every wiphy in several consecutive messages
+                    let wiphy: Wiphy = response
+                        .nl_payload
+                        .get_payload()
+                        .unwrap()
+                        .get_attr_handle()
+                        .try_into()?;
+                    match retval.last_mut() {
+                        Some(last) if last.index == wiphy.index => last.merge(wiphy),
+                        _ => retval.push(wiphy),
+                    }
+                }
+            }
+        }
+
+        Ok(retval)
+    }
+
+    /// Get the current regulatory domain
+    pub fn get_reg_domain(&mut self) -> Result<RegDomain, Error> {
+        self.request_reg_domain(None)
+    }
+
+    /// Request the kernel to use the regulatory domain of a country
+    ///
+    /// `alpha2` is the ISO 3166 country code of two ASCII letters, like `"DE"`, anything else is
+    /// rejected before it is sent. The kernel applies the domain asynchronously, a change is
+    /// reported as [`WifiEvent::RegChange`](crate::WifiEvent::RegChange). Without the
+    /// `CAP_NET_ADMIN` capability this fails with `Error::KernelErrno(EPERM)`.
+    pub fn set_reg_domain(&mut self, alpha2: &str) -> Result<(), Error> {
+        let request = request::set_reg(self.family_id, alpha2)?;
+        self.send_with_ack(request)
+    }
+
+    /// Get the regulatory domain a wiphy operates under
+    ///
+    /// Wiphys whose driver manages the regulatory domain itself may operate under a different
+    /// domain than the global one, for all others the kernel reports the global domain.
+    pub fn get_wiphy_reg_domain(&mut self, phy: u32) -> Result<RegDomain, Error> {
+        self.request_reg_domain(Some(phy))
+    }
+
+    fn request_reg_domain(&mut self, phy: Option<u32>) -> Result<RegDomain, Error> {
+        let seq = self.send(request::get_reg(self.family_id, phy)?)?;
+
+        let iter = responses(
+            self.sock
+                .iter::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(true),
+            seq,
+            false,
+        );
+
+        let mut retval = None;
+        for response in iter {
+            let response = response?;
+            match response.nl_type {
+                Nlmsg::Noop => (),
+                Nlmsg::Error => {
+                    if let Some(err) = response_error(response.nl_payload) {
+                        return Err(err);
+                    }
+                }
+                Nlmsg::Done => break,
+                _ => {
+                    retval = Some(
+                        response
+                            .nl_payload
+                            .get_payload()
+                            .unwrap()
+                            .get_attr_handle()
+                            .try_into()?,
+                    );
+                }
+            }
+        }
+
+        Ok(retval.unwrap_or_default())
+    }
+
+    /// Check channels against the regulatory domain in `set_channel`, off by default
+    ///
+    /// When enabled, [`set_channel`](Socket::set_channel) fetches the current regulatory domain
+    /// of the interface's wiphy and rejects channels it doesn't permit before they are sent to
+    /// the kernel, see [`RegDomain::permits_channel`]. This costs a few extra requests per call.
+    pub fn validate_channels(&mut self, validate: bool) {
+        self.validate_channels = validate;
+    }
+
+    /// Get access point information for a specific interface, with one entry per BSSID
+    ///
+    /// See [`dedup_bss`] for how duplicate entries are resolved.
+    pub fn get_bss_list_deduped(&mut self, interface_index: i32) -> Result<Vec<Bss>, Error> {
+        Ok(dedup_bss(self.get_bss_info(interface_index)?))
+    }
+
+    /// Get access point information for a specific interface, only of the BSSes in `band`
+    ///
+    /// See [`bss_in_band`].
+    pub fn get_bss_list_for_band(
+        &mut self,
+        interface_index: i32,
+        band: FrequencyBand,
+    ) -> Result<Vec<Bss>, Error> {
+        Ok(bss_in_band(self.get_bss_info(interface_index)?, band))
+    }
+
+    /// Connect to the network `ssid` on a specific interface
+    ///
+    /// This only initiates the connection, the outcome is reported asynchronously by the
+    /// kernel, see `connect_and_wait` for the AP's status code if it rejects the connection. An
+    /// empty [`ConnectParams`] connects to an open network.
+    pub fn connect_network(
+        &mut self,
+        interface_index: i32,
+        ssid: &[u8],
+        params: &ConnectParams,
+    ) -> Result<(), Error> {
+        self.check_connect_params(interface_index, params)?;
+        let request = request::connect(self.family_id, interface_index, ssid, params)?;
+        self.send_with_ack(request)
+    }
+
+    /// Connect to the network `ssid` on a specific interface and wait for the outcome
+    ///
+    /// This joins the "mlme" multicast group on a separate socket before connecting and blocks
+    /// until the kernel reports the result of the connection attempt. If the AP rejects the
+    /// connection or doesn't answer, this fails with [`Error::ConnectFailed`] carrying the AP's
+    /// status code.
+    pub fn connect_and_wait(
+        &mut self,
+        interface_index: i32,
+        ssid: &[u8],
+        params: &ConnectParams,
+    ) -> Result<(), Error> {
+        self.check_connect_params(interface_index, params)?;
+        let request = request::connect(self.family_id, interface_index, ssid, params)?;
+        self.send_and_wait_for_event("mlme", request, |event| {
+            connect_outcome(event, interface_index)
+        })
+    }
+
+    /// Disconnect a specific interface from its network
+    ///
+    /// Disconnecting an interface which isn't connected does nothing. The kernel reports the
+    /// disconnection as an event of the "mlme" group.
+    pub fn disconnect(&mut self, interface_index: i32) -> Result<(), Error> {
+        let request = request::disconnect(self.family_id, interface_index)?;
+        disconnect_result(self.send_with_ack(request))
+    }
+
+    /// Deauthenticate a specific interface from the BSS `bssid` with the given reason code
+    ///
+    /// This is the lower level counterpart of `disconnect`, for interfaces which authenticate and
+    /// associate step by step. Reason code 3 means the station is leaving.
+    pub fn deauthenticate(
+        &mut self,
+        interface_index: i32,
+        bssid: &[u8],
+        reason: u16,
+    ) -> Result<(), Error> {
+        let request = request::deauthenticate(self.family_id, interface_index, bssid, reason)?;
+        self.send_with_ack(request)
+    }
+
+    /// Trigger a scan on a specific interface
+    ///
+    /// This only starts the scan, the results can be fetched once the kernel has completed it.
+    /// Options the wiphy of the interface doesn't support, like a dwell time, fail with
+    /// [`Error::Unsupported`] before anything is sent.
+    pub fn trigger_scan(
+        &mut self,
+        interface_index: i32,
+        request: &ScanRequest,
+    ) -> Result<(), Error> {
+        self.check_scan_request(interface_index, request)?;
+        let request = request::trigger_scan(self.family_id, interface_index, request)?;
+        self.send_with_ack(request)
+    }
+
+    /// Scan on a specific interface and wait until the scan is complete
+    ///
+    /// This joins the "scan" multicast group on a separate socket before triggering the scan and
+    /// blocks until the kernel reports new results, which can then be fetched with
+    /// `get_bss_info`. Scans aborted by the kernel fail with [`Error::ScanAborted`], scans
+    /// rejected because another scan is still running with `EBUSY`. Both can be retried, see
+    /// [`Error::is_retriable`].
+    pub fn scan(&mut self, interface_index: i32, request: &ScanRequest) -> Result<(), Error> {
+        self.check_scan_request(interface_index, request)?;
+        let request = request::trigger_scan(self.family_id, interface_index, request)?;
+        self.send_and_wait_for_event("scan", request, |event| {
+            scan_outcome(event, interface_index)
+        })
+    }
+
+    /// Start a scheduled scan on a specific interface
+    ///
+    /// The kernel scans periodically until the scan is stopped with `stop_sched_scan` and
+    /// reports matching results with `CmdSchedScanResults` on the "scan" multicast group.
+    pub fn start_sched_scan(
+        &mut self,
+        interface_index: i32,
+        request: &SchedScanRequest,
+    ) -> Result<(), Error> {
+        let request = request::start_sched_scan(self.family_id, interface_index, request)?;
+        self.send_with_ack(request)
+    }
+
+    /// Stop the scheduled scan running on a specific interface
+    pub fn stop_sched_scan(&mut self, interface_index: i32) -> Result<(), Error> {
+        let request = request::stop_sched_scan(self.family_id, interface_index)?;
+        self.send_with_ack(request)
+    }
+
+    /// Set the operating channel of a phy
+    ///
+    /// This tunes the phy itself rather than one of its interfaces, which is what monitor
+    /// interfaces without a channel of their own follow. `phy` is the wiphy index as reported in
+    /// [`Interface::phy`](crate::Interface::phy), the center frequency of wide channels is derived
+    /// with [`center_freq`](crate::center_freq).
+    pub fn set_phy_channel(
+        &mut self,
+        phy: u32,
+        freq: u32,
+        width: ChannelWidth,
+    ) -> Result<(), Error> {
+        let request = request::set_phy_channel(self.family_id, phy, freq, width)?;
+        self.send_with_ack(request)
+    }
+
+    /// Set the operating channel of a specific interface
+    ///
+    /// If enabled with [`validate_channels`](Socket::validate_channels), channels the current
+    /// regulatory domain of the interface's wiphy doesn't permit are rejected before the channel
+    /// is set. The domain is fetched again for every call, as it changes with the country and
+    /// may be managed by the driver for some wiphys.
+    /// The center frequency of wide channels is derived with [`center_freq`](crate::center_freq).
+    pub fn set_channel(
+        &mut self,
+        interface_index: i32,
+        freq: u32,
+        width: ChannelWidth,
+    ) -> Result<(), Error> {
+        let reg_domain = if self.validate_channels {
+            self.interface_reg_domain(interface_index)?
+        } else {
+            None
+        };
+        let request = request::set_channel(
+            self.family_id,
+            interface_index,
+            freq,
+            width,
+            reg_domain.as_ref(),
+        )?;
+        self.send_with_ack(request)
+    }
+
+    /// Check a scan request against the wiphy of the scanning interface
+    fn check_scan_request(
+        &mut self,
+        interface_index: i32,
+        request: &ScanRequest,
+    ) -> Result<(), Error> {
+        if request.needs_check() {
+            if let Some(wiphy) = self.interface_wiphy(interface_index)? {
+                request.check(&wiphy)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check connect parameters against the wiphy of the interface the connection is made on
+    fn check_connect_params(
+        &mut self,
+        interface_index: i32,
+        params: &ConnectParams,
+    ) -> Result<(), Error> {
+        if params.needs_check() {
+            if let Some(wiphy) = self.interface_wiphy(interface_index)? {
+                params.check(&wiphy)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The wiphy of an interface, `None` if the interface doesn't exist
+    fn interface_wiphy(&mut self, interface_index: i32) -> Result<Option<Wiphy>, Error> {
+        let interface = self.get_interface_info(interface_index)?;
+        match interface.and_then(|interface| interface.phy) {
+            Some(phy) => Ok(self
+                .get_wiphy_info()?
+                .into_iter()
+                .find(|wiphy| wiphy.index == Some(phy))),
+            None => Ok(None),
+        }
+    }
+
+    /// The current regulatory domain of the wiphy of an interface, `None` if the interface
+    /// doesn't exist
+    fn interface_reg_domain(&mut self, interface_index: i32) -> Result<Option<RegDomain>, Error> {
+        let interface = self.get_interface_info(interface_index)?;
+        match interface.and_then(|interface| interface.phy) {
+            Some(phy) => Ok(Some(self.get_wiphy_reg_domain(phy)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Set the transmit power of a specific interface
+    ///
+    /// Power is configured per interface, but drivers that only support a single power level
+    /// per phy apply it to all interfaces of the phy. See [`TxPowerSetting`] for the units.
+    pub fn set_tx_power(
+        &mut self,
+        interface_index: i32,
+        setting: TxPowerSetting,
+    ) -> Result<(), Error> {
+        let request = request::set_tx_power(self.family_id, interface_index, setting)?;
+        self.send_with_ack(request)
+    }
+
+    /// Change the type of a specific interface, e.g. to [`InterfaceType::Monitor`] for capturing
+    ///
+    /// Most drivers require the interface to be down for this, otherwise the kernel rejects the
+    /// change with `Error::KernelErrno(EBUSY)`.
+    pub fn set_interface_type(
+        &mut self,
+        interface_index: i32,
+        iftype: InterfaceType,
+    ) -> Result<(), Error> {
+        let request = request::set_interface_type(self.family_id, interface_index, iftype)?;
+        self.send_with_ack(request)
+    }
+
+    /// Set which frames a specific monitor interface passes on
+    ///
+    /// The flags replace the interface's current ones. `cook` and `active` can't be changed while
+    /// the interface is up (`EBUSY`), and `active` requires driver support (`EOPNOTSUPP`).
+    pub fn set_monitor_flags(
+        &mut self,
+        interface_index: i32,
+        flags: MonitorFlags,
+    ) -> Result<(), Error> {
+        let request = request::set_monitor_flags(self.family_id, interface_index, flags)?;
+        self.send_with_ack(request)
+    }
+
+    /// Rename a specific interface
+    ///
+    /// `name` must be a valid interface name of at most 15 bytes without slashes, colons or
+    /// whitespace, otherwise the request is rejected before it is sent.
+    pub fn set_interface_name(&mut self, interface_index: i32, name: &str) -> Result<(), Error> {
+        let request = request::set_interface_name(self.family_id, interface_index, name)?;
+        self.send_with_ack(request)
+    }
+
+    /// Create a new interface `name` of type `iftype` on a wiphy and return it
+    ///
+    /// Fails without contacting the kernel if the wiphy does not support `iftype`, see
+    /// [`Wiphy::supports_iftype`]. The kernel answers with the new interface, including its
+    /// index, so it doesn't have to be looked up with `get_interfaces_info`.
+    pub fn create_interface(
+        &mut self,
+        wiphy: &Wiphy,
+        name: &str,
+        iftype: InterfaceType,
+    ) -> Result<Interface, Error> {
+        let request = request::create_interface(self.family_id, wiphy, name, iftype)?;
+        self.send_new_interface(request)
+    }
+
+    /// Create a new monitor interface `name` on a phy with the given flags and return it
+    ///
+    /// Unlike `create_interface` followed by `set_monitor_flags`, the interface never exists
+    /// with other flags. `phy` is the wiphy index as reported in
+    /// [`Interface::phy`](crate::Interface::phy).
+    pub fn create_monitor(
+        &mut self,
+        phy: u32,
+        name: &str,
+        flags: MonitorFlags,
+    ) -> Result<Interface, Error> {
+        let request = request::create_monitor(self.family_id, phy, name, flags)?;
+        self.send_new_interface(request)
+    }
+
+    /// Delete a specific interface, usually one created with `create_interface`
+    ///
+    /// Deleting an interface the driver can't remove, like the primary interface of many wiphys,
+    /// fails with the kernel's error, usually [`Error::Unsupported`]. An index without interface
+    /// fails with `KernelErrno(ENODEV)`, whose [`std::io::ErrorKind`] is `NotFound`.
+    pub fn delete_interface(&mut self, interface_index: i32) -> Result<(), Error> {
+        let request = request::delete_interface(self.family_id, interface_index)?;
+        self.send_with_ack(request)
+    }
+
+    /// Announce a switch of an interface to another channel and perform it after `count` beacons
+    ///
+    /// APs announce the switch with the beacons in `csa_ies`, `block_tx` asks the associated
+    /// stations to stop transmitting until the switch. With a `count` of 0 the channel is
+    /// switched immediately.
+    pub fn channel_switch(
+        &mut self,
+        interface_index: i32,
+        freq: u32,
+        width: ChannelWidth,
+        count: u8,
+        block_tx: bool,
+        csa_ies: &CsaIes,
+    ) -> Result<(), Error> {
+        let request = request::channel_switch(
+            self.family_id,
+            interface_index,
+            freq,
+            width,
+            count,
+            block_tx,
+            csa_ies,
+        )?;
+        self.send_with_ack(request)
+    }
+
+    /// Whether power save is enabled on a specific interface
+    pub fn get_power_save(&mut self, interface_index: i32) -> Result<bool, Error> {
+        let seq = self.send(request::get_power_save(self.family_id, interface_index)?)?;
+
+        for response in responses(
+            self.sock
+                .iter::<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(true),
+            seq,
+            false,
+        ) {
+            if let NlPayload::Payload(reply) = response?.nl_payload {
+                return Ok(power_save_enabled(&reply)?);
+            }
+        }
+
+        Err(Error::Netlink(NlError::new("No power save state received")))
+    }
+
+    /// Enable or disable power save of a specific interface
+    ///
+    /// Without the `CAP_NET_ADMIN` capability this fails with `Error::KernelErrno(EPERM)`.
+    pub fn set_power_save(&mut self, interface_index: i32, enabled: bool) -> Result<(), Error> {
+        let request = request::set_power_save(self.family_id, interface_index, enabled)?;
+        self.send_with_ack(request)
+    }
+
+    /// Enable or disable power save of all interfaces of a phy
+    ///
+    /// The setting is applied to every interface of the phy, even if it fails for some of them,
+    /// and is kept on the interfaces where it succeeded. Returns the interfaces where it failed
+    /// with their errors, so an empty list means power save is set on all interfaces. Only
+    /// failing to list the interfaces is an error.
+    pub fn set_power_save_all(
+        &mut self,
+        phy: u32,
+        enabled: bool,
+    ) -> Result<Vec<(i32, Error)>, Error> {
+        let interfaces = self.get_interfaces_info()?;
+        Ok(for_each_phy_interface(&interfaces, phy, |index| {
+            self.set_power_save(index, enabled)
+        }))
+    }
+
+    /// Hand the keys for GTK rekeying to the driver, e.g. before suspending with WoWLAN
+    ///
+    /// `kek` and `kck` are the key encryption and confirmation keys of the PTK, `replay_ctr` the
+    /// EAPOL-Key replay counter. The KEK must be 16 or 32 bytes, the KCK 16, 24 or 32 bytes and
+    /// the replay counter 8 bytes, other lengths are rejected before the request is sent.
+    pub fn set_rekey_data(
+        &mut self,
+        interface_index: i32,
+        kek: &[u8],
+        kck: &[u8],
+        replay_ctr: &[u8],
+    ) -> Result<(), Error> {
+        let request =
+            request::set_rekey_data(self.family_id, interface_index, kek, kck, replay_ctr)?;
+        self.send_with_ack(request)
+    }
+
+    /// Set a single parameter of a mesh interface
+    ///
+    /// `value` is rejected without contacting the kernel if it doesn't fit the parameter.
+    pub fn set_mesh_param(
+        &mut self,
+        interface_index: i32,
+        param: MeshParam,
+        value: u32,
+    ) -> Result<(), Error> {
+        let request = request::set_mesh_param(self.family_id, interface_index, param, value)?;
+        self.send_with_ack(request)
+    }
+
+    /// Send a request numbered with the next sequence number of the socket, which is returned
+    fn send<T, P>(&mut self, mut request: Nlmsghdr<T, P>) -> Result<u32, Error>
+    where
+        T: NlType + Debug,
+        P: ToBytes + Debug,
+    {
+        // Events of multicast groups are numbered 0
+        self.seq = self.seq.checked_add(1).unwrap_or(1);
+        request.nl_seq = self.seq;
+        self.sock.send(request)?;
+        Ok(self.seq)
+    }
+
+    /// Send a request and wait for the kernel to acknowledge it
+    fn send_with_ack(&mut self, request: Request) -> Result<(), Error> {
+        let seq = self.send(request)?;
+
+        for response in responses(self.sock.iter::<u16, Buffer>(true), seq, true) {
+            response?;
+        }
+
+        Ok(())
+    }
+
+    /// Send a request and wait for its ack and the event of `group` that `outcome` returns the
+    /// result for
+    ///
+    /// The event is received on a separate socket, which joins `group` before the request is
+    /// sent so the event can't be missed. The request socket itself doesn't join any group.
+    fn send_and_wait_for_event<F>(
+        &mut self,
+        group: &str,
+        request: Request,
+        outcome: F,
+    ) -> Result<(), Error>
+    where
+        F: Fn(&Genlmsghdr<Nl80211Cmd, Nl80211Attr>) -> Option<Result<(), Error>>,
+    {
+        let mut events = Socket::connect()?;
+        events.join_mcast_group(group)?;
+        self.send_with_ack(request)?;
+        events.wait_for_event(outcome)
+    }
+
+    /// Receive events until `outcome` returns a result for one of them
+    fn wait_for_event<F>(&mut self, outcome: F) -> Result<(), Error>
+    where
+        F: Fn(&Genlmsghdr<Nl80211Cmd, Nl80211Attr>) -> Option<Result<(), Error>>,
+    {
+        loop {
+            match self.sock.recv::<u16, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>() {
+                Ok(Some(event)) => {
+                    if let NlPayload::Payload(event) = event.nl_payload {
+                        if let Some(result) = outcome(&event) {
+                            return result;
+                        }
+                    }
+                }
+                Ok(None) => return Err(Error::Netlink(NlError::new("No event received"))),
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    /// Send a request creating an interface and return the interface the kernel answers with
+    fn send_new_interface(&mut self, request: Request) -> Result<Interface, Error> {
+        let seq = self.send(request)?;
+
+        let mut retval = None;
+        for response in responses(
+            self.sock
+                .iter::<u16, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>(true),
+            seq,
+            true,
+        ) {
+            if let NlPayload::Payload(interface) = response?.nl_payload {
+                if retval.is_none() && interface.cmd == Nl80211Cmd::CmdNewInterface {
+                    retval = Some(interface.get_attr_handle().try_into()?);
+                }
+            }
+        }
+
+        retval.ok_or_else(|| Error::Netlink(NlError::new("No interface received")))
+    }
+}
+
+/// Apply `f` to each interface of `phy`, collecting the interfaces where it failed
+fn for_each_phy_interface<F>(interfaces: &[Interface], phy: u32, mut f: F) -> Vec<(i32, Error)>
+where
+    F: FnMut(i32) -> Result<(), Error>,
+{
+    failed_interfaces(phy_interfaces(interfaces, phy).map(|index| (index, f(index))))
+}
+
+/// The indices of the interfaces of `phy`
+pub(crate) fn phy_interfaces(interfaces: &[Interface], phy: u32) -> impl Iterator<Item = i32> + '_ {
+    interfaces
+        .iter()
+        .filter(move |interface| interface.phy == Some(phy))
+        .filter_map(|interface| interface.index)
+}
+
+/// The interfaces among the `results` of applying a setting per interface where it failed
+pub(crate) fn failed_interfaces<I>(results: I) -> Vec<(i32, Error)>
+where
+    I: IntoIterator<Item = (i32, Result<(), Error>)>,
+{
+    results
+        .into_iter()
+        .filter_map(|(index, result)| result.err().map(|err| (index, err)))
+        .collect()
+}
+
+/// The responses to the request numbered `seq` among the received `messages`
+///
+/// Stray messages, like leftovers of an earlier request or events of joined multicast groups,
+/// are skipped instead of being taken for responses or ending them early. The responses end
+/// with the ack if the request asked for one, otherwise with the end of a dump or the single
+/// response to a request that isn't a dump.
+fn responses<T, P, I>(
+    messages: I,
+    seq: u32,
+    acked: bool,
+) -> impl Iterator<Item = Result<Nlmsghdr<T, P>, NlError<T, P>>>
+where
+    T: NlType,
+    I: IntoIterator<Item = Result<Nlmsghdr<T, P>, NlError<T, P>>>,
+{
+    let mut messages = messages.into_iter();
+    let mut end = false;
+    // Checked before receiving, the socket blocks when asked for a message after the last one
+    std::iter::from_fn(move || {
+        while !end {
+            let message = messages.next()?;
+            let matches = match &message {
+                Ok(response) => response.nl_seq == seq,
+                Err(NlError::Nlmsgerr(err)) => err.nlmsg.nl_seq == seq,
+                Err(_) => true,
+            };
+            if !matches {
+                continue;
+            }
+            end = match &message {
+                Ok(response) => {
+                    let nl_type: u16 = response.nl_type.into();
+                    matches!(response.nl_payload, NlPayload::Ack(_))
+                        || nl_type == u16::from(Nlmsg::Done)
+                        || !acked && !response.nl_flags.contains(&NlmF::Multi)
+                }
+                Err(_) => true,
+            };
+            return Some(message);
+        }
+        None
+    })
+}
+
+/// Collect the stations of a `CmdGetStation` dump, treating `EOPNOTSUPP` as no stations
+fn collect_stations<I>(responses: I) -> Result<Vec<Station>, Error>
+where
+    I: IntoIterator<
+        Item = Result<
+            Nlmsghdr<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>,
+            NlError<Nlmsg, Genlmsghdr<Nl80211Cmd, Nl80211Attr>>,
+        >,
+    >,
+{
+    let mut retval = Vec::new();
+    for response in responses {
+        let response = match response {
+            Ok(response) => response,
+            Err(NlError::Nlmsgerr(err)) if err.error == -EOPNOTSUPP => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+        match response.nl_type {
+            Nlmsg::Noop => (),
+            Nlmsg::Error => {
+                if let Some(err) = response_error(response.nl_payload) {
+                    return Err(err);
+                }
+            }
+            Nlmsg::Done => break,
+            _ => {
+                if let Some(payload) = response.nl_payload.get_payload() {
+                    retval.push(payload.get_attr_handle().try_into()?);
+                }
+            }
+        };
+    }
+
+    Ok(retval)
+}
+
+/// Remember that the multicast group `id` was joined, returns whether it was new
+fn insert_group(groups: &mut Vec<u32>, id: u32) -> bool {
+    if groups.contains(&id) {
+        return false;
+    }
+    groups.push(id);
+    true
+}
+
+/// The ids of the resolved [`EVENT_GROUPS`], only "vendor" may be missing
+pub(crate) fn event_group_ids(resolved: &[Option<u32>]) -> Result<Vec<u32>, Error> {
+    let mut ids = Vec::new();
+    for (id, name) in resolved.iter().zip(EVENT_GROUPS) {
+        match id {
+            Some(id) => ids.push(*id),
+            None if name == "vendor" => (),
+            None => return Err(unresolved_group(name)),
+        }
+    }
+    Ok(ids)
+}
+
+pub(crate) fn unresolved_group(name: &str) -> Error {
+    Error::Netlink(NlError::new(format!(
+        "Failed to resolve multicast group {} of family {}",
+        name, NL_80211_GENL_NAME
+    )))
+}
+
+/// Look up the id of the multicast group `name` in a generic netlink family description
+pub(crate) fn mcast_group_id(family: &Genlmsghdr<CtrlCmd, CtrlAttr>, name: &str) -> Option<u32> {
+    let mut handle = family.get_attr_handle();
+    let groups = handle
+        .get_nested_attributes::<Index>(CtrlAttr::McastGroups)
+        .ok()?;
+    groups.iter().find_map(|group| {
+        let attrs = group.get_attr_handle::<CtrlAttrMcastGrp>().ok()?;
+        let group_name = attrs
+            .get_attr_payload_as_with_len::<String>(CtrlAttrMcastGrp::Name)
+            .ok()?;
+        if group_name == name {
+            attrs.get_attr_payload_as(CtrlAttrMcastGrp::Id).ok()
+        } else {
+            None
+        }
+    })
 }
 
 impl From<Socket> for NlSocketHandle {
@@ -213,3 +1196,612 @@ impl From<Socket> for NlSocketHandle {
         sock.sock
     }
 }
+
+#[cfg(test)]
+mod test_socket {
+    use super::*;
+    use crate::error::ENODEV;
+    use crate::mac::MacAddress;
+    use crate::security::AkmSuite;
+    use crate::NL_80211_GENL_VERSION;
+    use neli::consts::nl::NlmFFlags;
+    use neli::err::{Nlmsgerr, NlmsghdrErr};
+    use neli::genl::Nlattr;
+    use neli::types::GenlBuffer;
+    use neli::ToBytes;
+    use std::io::Cursor;
+    use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
+    use std::os::unix::net::UnixDatagram;
+
+    fn new_group(index: u16, name: &str, id: u32) -> Nlattr<Index, neli::types::Buffer> {
+        let mut group = Nlattr::new(true, false, index.into(), Vec::<u8>::new()).unwrap();
+        group
+            .add_nested_attribute(&Nlattr::new(false, false, CtrlAttrMcastGrp::Id, id).unwrap())
+            .unwrap();
+        group
+            .add_nested_attribute(&Nlattr::new(false, false, CtrlAttrMcastGrp::Name, name).unwrap())
+            .unwrap();
+        group
+    }
+
+    /// A socket connected to a fake kernel, which answers with what is sent to the other end
+    fn fake_socket() -> (Socket, UnixDatagram) {
+        let (sock, kernel) = UnixDatagram::pair().unwrap();
+        let socket = Socket {
+            sock: unsafe { NlSocketHandle::from_raw_fd(sock.into_raw_fd()) },
+            family_id: 28,
+            mcast_groups: Vec::new(),
+            validate_channels: false,
+            seq: 0,
+        };
+        (socket, kernel)
+    }
+
+    /// Answer the request with the sequence number `seq` with a single message
+    fn respond(
+        kernel: &UnixDatagram,
+        seq: u32,
+        cmd: Nl80211Cmd,
+        attrs: GenlBuffer<Nl80211Attr, Buffer>,
+    ) {
+        let message = Nlmsghdr::new(
+            None,
+            28u16,
+            NlmFFlags::empty(),
+            Some(seq),
+            None,
+            NlPayload::Payload(Genlmsghdr::new(cmd, NL_80211_GENL_VERSION, attrs)),
+        );
+        send_message(kernel, message);
+    }
+
+    /// Answer the dump request with the sequence number `seq` with one message per entry
+    fn respond_dump(
+        kernel: &UnixDatagram,
+        seq: u32,
+        cmd: Nl80211Cmd,
+        entries: Vec<GenlBuffer<Nl80211Attr, Buffer>>,
+    ) {
+        let multi = || NlmFFlags::new(&[NlmF::Multi]);
+        for attrs in entries {
+            let payload = NlPayload::Payload(Genlmsghdr::new(cmd, NL_80211_GENL_VERSION, attrs));
+            let message = Nlmsghdr::new(None, 28u16, multi(), Some(seq), None, payload);
+            send_message(kernel, message);
+        }
+        let done = Nlmsghdr::new(
+            None,
+            u16::from(Nlmsg::Done),
+            multi(),
+            Some(seq),
+            None,
+            NlPayload::Empty,
+        );
+        send_message(kernel, done);
+    }
+
+    fn send_message(kernel: &UnixDatagram, message: Request) {
+        let mut buffer = Cursor::new(Vec::new());
+        message.to_bytes(&mut buffer).unwrap();
+        kernel.send(buffer.get_ref()).unwrap();
+    }
+
+    /// Answer the request with the sequence number `seq` with interface `index` of `phy`
+    fn respond_interface(kernel: &UnixDatagram, seq: u32, index: i32, phy: u32) {
+        let mut attrs = GenlBuffer::new();
+        attrs.push(Nlattr::new(false, false, Nl80211Attr::AttrIfindex, index).unwrap());
+        attrs.push(Nlattr::new(false, false, Nl80211Attr::AttrWiphy, phy).unwrap());
+        respond(kernel, seq, Nl80211Cmd::CmdNewInterface, attrs);
+    }
+
+    /// The number of requests the fake kernel received since the last call
+    fn requests(kernel: &UnixDatagram) -> usize {
+        kernel.set_nonblocking(true).unwrap();
+        let mut buffer = [0; 256];
+        std::iter::from_fn(|| kernel.recv(&mut buffer).ok()).count()
+    }
+
+    /// The ack of the request with the sequence number `seq`
+    fn ack(seq: u32) -> Vec<u8> {
+        let header = |len: u32, nl_type: u16| {
+            [
+                &len.to_ne_bytes()[..],
+                &nl_type.to_ne_bytes(),
+                &[0, 0],
+                &seq.to_ne_bytes(),
+                &[0; 4],
+            ]
+            .concat()
+        };
+        [header(36, 2), vec![0; 4], header(16, 28)].concat()
+    }
+
+    #[test]
+    fn test_stations_not_supported() {
+        let error = |error| {
+            Err(NlError::Nlmsgerr(Nlmsgerr {
+                error,
+                nlmsg: NlmsghdrErr {
+                    nl_len: 36,
+                    nl_type: Nlmsg::Error,
+                    nl_flags: NlmFFlags::empty(),
+                    nl_seq: 1,
+                    nl_pid: 0,
+                    nl_payload: Genlmsghdr::new(
+                        Nl80211Cmd::CmdGetStation,
+                        NL_80211_GENL_VERSION,
+                        GenlBuffer::new(),
+                    ),
+                },
+            }))
+        };
+
+        // Monitor interfaces either don't support station dumps or have no stations
+        assert_eq!(collect_stations(vec![error(-EOPNOTSUPP)]).unwrap(), vec![]);
+        let done = Ok(Nlmsghdr::new(
+            None,
+            Nlmsg::Done,
+            NlmFFlags::new(&[NlmF::Multi]),
+            None,
+            None,
+            NlPayload::Empty,
+        ));
+        assert_eq!(collect_stations(vec![done]).unwrap(), vec![]);
+        // Permission denied
+        assert!(collect_stations(vec![error(-1)]).is_err());
+    }
+
+    #[test]
+    fn test_all_stations() {
+        let station = |mac: Option<&[u8]>| {
+            let mut attrs = GenlBuffer::new();
+            if let Some(mac) = mac {
+                attrs.push(Nlattr::new(false, false, Nl80211Attr::AttrMac, mac).unwrap());
+            }
+            Ok(Nlmsghdr::new(
+                None,
+                Nlmsg::UnrecognizedConst(28),
+                NlmFFlags::new(&[NlmF::Multi]),
+                None,
+                None,
+                NlPayload::Payload(Genlmsghdr::new(
+                    Nl80211Cmd::CmdNewStation,
+                    NL_80211_GENL_VERSION,
+                    attrs,
+                )),
+            ))
+        };
+        let done = Ok(Nlmsghdr::new(
+            None,
+            Nlmsg::Done,
+            NlmFFlags::new(&[NlmF::Multi]),
+            None,
+            None,
+            NlPayload::Empty,
+        ));
+
+        // Peers of an access point, without any station info
+        let stations = collect_stations(vec![
+            station(Some(&[2, 0, 0, 0, 0, 1])),
+            station(Some(&[2, 0, 0, 0, 0, 2])),
+            done,
+        ])
+        .unwrap();
+
+        assert_eq!(stations.len(), 2);
+        assert_eq!(stations[0].bssid, Some(MacAddress([2, 0, 0, 0, 0, 1])));
+        assert_eq!(stations[1].bssid, Some(MacAddress([2, 0, 0, 0, 0, 2])));
+        assert_eq!(stations[1].signal, None);
+    }
+
+    #[test]
+    fn test_stray_responses() {
+        let message = |seq, nl_type, multi, mac: &[u8]| {
+            let mut attrs = GenlBuffer::new();
+            attrs.push(Nlattr::new(false, false, Nl80211Attr::AttrMac, mac).unwrap());
+            let flags = match multi {
+                true => NlmFFlags::new(&[NlmF::Multi]),
+                false => NlmFFlags::empty(),
+            };
+            Ok(Nlmsghdr::new(
+                None,
+                nl_type,
+                flags,
+                Some(seq),
+                None,
+                NlPayload::Payload(Genlmsghdr::new(
+                    Nl80211Cmd::CmdNewStation,
+                    NL_80211_GENL_VERSION,
+                    attrs,
+                )),
+            ))
+        };
+        let station = Nlmsg::UnrecognizedConst(28);
+
+        // The dump of request 2 is interleaved with the single response to an earlier request
+        // and an event, and followed by the response to the next request
+        let stations = collect_stations(responses(
+            vec![
+                message(1, station, false, &[2, 0, 0, 0, 0, 1]),
+                message(2, station, true, &[2, 0, 0, 0, 0, 2]),
+                message(0, station, false, &[2, 0, 0, 0, 0, 3]),
+                message(2, station, true, &[2, 0, 0, 0, 0, 4]),
+                message(2, Nlmsg::Done, true, &[]),
+                message(3, station, false, &[2, 0, 0, 0, 0, 5]),
+            ],
+            2,
+            false,
+        ))
+        .unwrap();
+
+        let bssids: Vec<_> = stations.iter().map(|station| station.bssid).collect();
+        assert_eq!(
+            bssids,
+            vec![
+                Some(MacAddress([2, 0, 0, 0, 0, 2])),
+                Some(MacAddress([2, 0, 0, 0, 0, 4]))
+            ]
+        );
+
+        // The socket is not read past the end, it blocks until the next message arrives
+        let ended: Vec<_> = responses(
+            vec![message(5, Nlmsg::Done, true, &[])]
+                .into_iter()
+                .chain(std::iter::from_fn(|| panic!("received past the end"))),
+            5,
+            false,
+        )
+        .collect();
+        assert_eq!(ended.len(), 1);
+
+        // A request that isn't a dump ends with its single response
+        let responses: Vec<_> = responses(
+            vec![
+                message(0, station, false, &[2, 0, 0, 0, 0, 1]),
+                message(4, station, false, &[2, 0, 0, 0, 0, 2]),
+                message(4, station, false, &[2, 0, 0, 0, 0, 3]),
+            ],
+            4,
+            false,
+        )
+        .collect();
+        assert_eq!(responses.len(), 1);
+    }
+
+    #[test]
+    fn test_for_each_phy_interface() {
+        let interface = |index, phy| Interface {
+            index: Some(index),
+            phy: Some(phy),
+            ..Default::default()
+        };
+        let interfaces = [interface(3, 0), interface(4, 1), interface(5, 0)];
+
+        // The second interface of phy 0 is gone, the first one is still configured
+        let mut applied = Vec::new();
+        let failed = for_each_phy_interface(&interfaces, 0, |index| {
+            applied.push(index);
+            match index {
+                5 => Err(Error::KernelErrno(ENODEV)),
+                _ => Ok(()),
+            }
+        });
+
+        assert_eq!(applied, vec![3, 5]);
+        assert_eq!(failed.len(), 1);
+        assert!(matches!(failed[0], (5, Error::KernelErrno(ENODEV))));
+    }
+
+    #[test]
+    fn test_close() {
+        let sock = Socket {
+            sock: NlSocketHandle::connect(NlFamily::Generic, None, &[]).unwrap(),
+            family_id: 0,
+            mcast_groups: Vec::new(),
+            validate_channels: false,
+            seq: 0,
+        };
+        let fd_path = format!("/proc/self/fd/{}", sock.sock.as_raw_fd());
+        let target = std::fs::read_link(&fd_path).unwrap();
+
+        sock.close().unwrap();
+
+        // Other tests may have reused the fd number for a file of their own by now
+        assert_ne!(std::fs::read_link(&fd_path).ok(), Some(target));
+    }
+
+    /// A socket of the generic netlink controller and the id of its "notify" group
+    fn nlctrl_socket() -> (Socket, u32) {
+        let mut sock = Socket {
+            sock: NlSocketHandle::connect(NlFamily::Generic, None, &[]).unwrap(),
+            family_id: 0,
+            mcast_groups: Vec::new(),
+            validate_channels: false,
+            seq: 0,
+        };
+        // The "notify" group of the generic netlink controller always exists
+        let notify = sock
+            .sock
+            .resolve_nl_mcast_group("nlctrl", "notify")
+            .unwrap();
+        (sock, notify)
+    }
+
+    #[test]
+    fn test_joined_groups() {
+        let (mut sock, notify) = nlctrl_socket();
+        assert!(sock.joined_groups().is_empty());
+
+        sock.join_mcast_group_id(notify).unwrap();
+        sock.join_mcast_group_id(notify).unwrap();
+        assert_eq!(sock.joined_groups(), &[notify]);
+        sock.leave_all_mcast().unwrap();
+        assert!(sock.joined_groups().is_empty());
+
+        // Groups can be joined again after leaving them
+        sock.join_mcast_group_id(notify).unwrap();
+        assert_eq!(sock.joined_groups(), &[notify]);
+    }
+
+    /// Depends on the generic netlink families of the host
+    #[test]
+    #[ignore]
+    fn test_joined_groups_order() {
+        let (mut sock, notify) = nlctrl_socket();
+        sock.join_mcast_group_id(notify).unwrap();
+
+        // The controller has only one group, so take one of another common family
+        let second = [
+            ("ethtool", "monitor"),
+            ("thermal", "event"),
+            ("VFS_DQUOT", "events"),
+            (NL_80211_GENL_NAME, "config"),
+        ]
+        .into_iter()
+        .find_map(|(family, group)| sock.sock.resolve_nl_mcast_group(family, group).ok())
+        .expect("no generic netlink family with a multicast group besides nlctrl");
+        sock.join_mcast_group_id(second).unwrap();
+        assert_eq!(sock.joined_groups(), &[notify, second]);
+        sock.leave_all_mcast().unwrap();
+        assert!(sock.joined_groups().is_empty());
+    }
+
+    #[test]
+    fn test_power_per_interface() {
+        let (mut socket, kernel) = fake_socket();
+
+        // Two access points on the same phy with their own power settings
+        let entries = [(5, 2000u32), (6, 1500)]
+            .into_iter()
+            .map(|(index, power)| {
+                let mut attrs = GenlBuffer::new();
+                attrs.push(Nlattr::new(false, false, Nl80211Attr::AttrIfindex, index).unwrap());
+                attrs.push(Nlattr::new(false, false, Nl80211Attr::AttrWiphy, 0u32).unwrap());
+                attrs.push(Nlattr::new(false, false, Nl80211Attr::AttrIftype, 3u32).unwrap());
+                attrs.push(
+                    Nlattr::new(false, false, Nl80211Attr::AttrWiphyTxPowerLevel, power).unwrap(),
+                );
+                attrs
+            })
+            .collect();
+        respond_dump(&kernel, 1, Nl80211Cmd::CmdNewInterface, entries);
+
+        let interfaces = socket.get_interfaces_info().unwrap();
+        assert_eq!(interfaces.len(), 2);
+        assert_eq!(interfaces[0].phy, interfaces[1].phy);
+        assert_eq!(interfaces[0].index, Some(5));
+        assert_eq!(interfaces[0].power, Some(2000));
+        assert_eq!(interfaces[1].index, Some(6));
+        assert_eq!(interfaces[1].power, Some(1500));
+    }
+
+    #[test]
+    fn test_set_reg_domain() {
+        let (mut socket, kernel) = fake_socket();
+
+        kernel.send(&ack(1)).unwrap();
+        socket.set_reg_domain("DE").unwrap();
+        assert!(kernel.recv(&mut [0; 64]).is_ok());
+    }
+
+    #[test]
+    fn test_set_channel_reg_domain() {
+        let (mut socket, kernel) = fake_socket();
+
+        // Without validation the channel is sent right away
+        kernel.send(&ack(1)).unwrap();
+        socket.set_channel(3, 5180, ChannelWidth::Width20).unwrap();
+        assert_eq!(requests(&kernel), 1);
+        kernel.set_nonblocking(false).unwrap();
+
+        socket.validate_channels(true);
+        // Interface 3 belongs to phy 1, whose driver manages a domain without any rules
+        respond_interface(&kernel, 2, 3, 1);
+        let mut reg_domain = GenlBuffer::new();
+        reg_domain.push(Nlattr::new(false, false, Nl80211Attr::AttrRegAlpha2, "DE").unwrap());
+        reg_domain.push(Nlattr::new(false, false, Nl80211Attr::AttrWiphy, 1).unwrap());
+        respond(&kernel, 3, Nl80211Cmd::CmdGetReg, reg_domain);
+
+        let err = socket
+            .set_channel(3, 5180, ChannelWidth::Width20)
+            .unwrap_err();
+        assert!(err.to_string().contains("domain DE"), "{}", err);
+
+        // The interface and domain lookups were sent, but not the channel
+        assert_eq!(requests(&kernel), 2);
+    }
+
+    #[test]
+    fn test_scan_checks_dwell() {
+        let (mut socket, kernel) = fake_socket();
+        let mut request = ScanRequest::new();
+        request.duration_ms(50);
+
+        // Interface 3 belongs to phy 1, which reports extended features without scan dwell
+        for (seq, wait) in [(1, false), (3, true)] {
+            respond_interface(&kernel, seq, 3, 1);
+            let mut wiphy = GenlBuffer::new();
+            wiphy.push(Nlattr::new(false, false, Nl80211Attr::AttrWiphy, 1u32).unwrap());
+            wiphy
+                .push(Nlattr::new(false, false, Nl80211Attr::AttrExtFeatures, &[0u8][..]).unwrap());
+            respond(&kernel, seq + 1, Nl80211Cmd::CmdNewWiphy, wiphy);
+
+            let result = if wait {
+                socket.scan(3, &request)
+            } else {
+                socket.trigger_scan(3, &request)
+            };
+            assert!(matches!(result, Err(Error::Unsupported)));
+            // The interface and wiphy lookups were sent, but not the scan
+            assert_eq!(requests(&kernel), 2);
+            kernel.set_nonblocking(false).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_connect_checks_akm() {
+        let (mut socket, kernel) = fake_socket();
+        let mut params = ConnectParams::new();
+        params.akm_suite(AkmSuite::Sae);
+
+        // Interface 3 belongs to phy 1, which only supports PSK
+        for (seq, connect) in [(1, false), (3, true)] {
+            respond_interface(&kernel, seq, 3, 1);
+            let mut wiphy = GenlBuffer::new();
+            wiphy.push(Nlattr::new(false, false, Nl80211Attr::AttrWiphy, 1u32).unwrap());
+            wiphy.push(
+                Nlattr::new(false, false, Nl80211Attr::AttrAkmSuites, 0x000fac02u32).unwrap(),
+            );
+            respond(&kernel, seq + 1, Nl80211Cmd::CmdNewWiphy, wiphy);
+
+            let result = if connect {
+                socket.connect_and_wait(3, b"home", &params)
+            } else {
+                socket.connect_network(3, b"home", &params)
+            };
+            assert!(matches!(result, Err(Error::Unsupported)));
+            // The interface and wiphy lookups were sent, but not the connect request
+            assert_eq!(requests(&kernel), 2);
+            kernel.set_nonblocking(false).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_wait_for_event() {
+        let (mut socket, kernel) = fake_socket();
+        // A scan of another interface, and the aborted scan of interface 3
+        for (interface_index, cmd) in [
+            (4, Nl80211Cmd::CmdNewScanResults),
+            (3, Nl80211Cmd::CmdScanAborted),
+        ] {
+            let mut attrs = GenlBuffer::new();
+            attrs.push(
+                Nlattr::new(false, false, Nl80211Attr::AttrIfindex, interface_index).unwrap(),
+            );
+            let message = Nlmsghdr::new(
+                None,
+                28u16,
+                NlmFFlags::empty(),
+                None,
+                None,
+                NlPayload::Payload(Genlmsghdr::new(cmd, NL_80211_GENL_VERSION, attrs)),
+            );
+            let mut buffer = Cursor::new(Vec::new());
+            message.to_bytes(&mut buffer).unwrap();
+            kernel.send(buffer.get_ref()).unwrap();
+        }
+
+        let result = socket.wait_for_event(|event| scan_outcome(event, 3));
+        assert!(matches!(result, Err(Error::ScanAborted)));
+    }
+
+    #[test]
+    fn test_next_event() {
+        let (mut socket, kernel) = fake_socket();
+        assert!(socket.next_event().is_err());
+
+        // A scan event, a late response to a request and a scan abort
+        for (seq, cmd) in [
+            (0, Nl80211Cmd::CmdTriggerScan),
+            (5, Nl80211Cmd::CmdNewStation),
+            (0, Nl80211Cmd::CmdScanAborted),
+        ] {
+            let mut attrs = GenlBuffer::new();
+            attrs.push(Nlattr::new(false, false, Nl80211Attr::AttrIfindex, 3).unwrap());
+            let message = Nlmsghdr::new(
+                None,
+                28u16,
+                NlmFFlags::empty(),
+                Some(seq),
+                None,
+                NlPayload::Payload(Genlmsghdr::new(cmd, NL_80211_GENL_VERSION, attrs)),
+            );
+            let mut buffer = Cursor::new(Vec::new());
+            message.to_bytes(&mut buffer).unwrap();
+            kernel.send(buffer.get_ref()).unwrap();
+        }
+
+        insert_group(&mut socket.mcast_groups, 4);
+        assert_eq!(
+            socket.next_event().unwrap(),
+            WifiEvent::ScanStarted { interface_index: 3 }
+        );
+        assert_eq!(
+            socket.next_event().unwrap(),
+            WifiEvent::ScanAborted { interface_index: 3 }
+        );
+    }
+
+    #[test]
+    fn test_insert_group() {
+        let mut groups = Vec::new();
+        assert!(insert_group(&mut groups, 5));
+        assert!(!insert_group(&mut groups, 5));
+        assert!(insert_group(&mut groups, 7));
+        assert_eq!(groups, vec![5, 7]);
+    }
+
+    #[test]
+    fn test_mcast_group_id() {
+        let mut groups = Nlattr::new(true, false, CtrlAttr::McastGroups, Vec::<u8>::new()).unwrap();
+        for (index, (name, id)) in [("config", 4), ("scan", 5), ("regulatory", 6), ("mlme", 7)]
+            .into_iter()
+            .enumerate()
+        {
+            groups
+                .add_nested_attribute(&new_group(index as u16 + 1, name, id))
+                .unwrap();
+        }
+
+        let mut attrs = GenlBuffer::new();
+        attrs.push(Nlattr::new(false, false, CtrlAttr::FamilyName, NL_80211_GENL_NAME).unwrap());
+        attrs.push(Nlattr::new(false, false, CtrlAttr::FamilyId, 28u16).unwrap());
+        attrs.push(groups);
+        let family = Genlmsghdr::new(CtrlCmd::Newfamily, 2, attrs);
+
+        assert_eq!(mcast_group_id(&family, "config"), Some(4));
+        assert_eq!(mcast_group_id(&family, "scan"), Some(5));
+        assert_eq!(mcast_group_id(&family, "mlme"), Some(7));
+        assert_eq!(mcast_group_id(&family, "vendor"), None);
+
+        let resolved: Vec<_> = EVENT_GROUPS
+            .iter()
+            .map(|name| mcast_group_id(&family, name))
+            .collect();
+        assert_eq!(event_group_ids(&resolved).unwrap(), vec![4, 5, 7, 6]);
+    }
+
+    #[test]
+    fn test_event_group_ids() {
+        let ids = event_group_ids(&[Some(4), Some(5), Some(7), Some(6), Some(9)]).unwrap();
+        assert_eq!(ids, vec![4, 5, 7, 6, 9]);
+
+        let mut groups = Vec::new();
+        for id in ids {
+            assert!(insert_group(&mut groups, id));
+        }
+        assert_eq!(groups.len(), 5);
+
+        // Without the "mlme" group connection events would silently be missed
+        assert!(event_group_ids(&[Some(4), Some(5), None, Some(6), Some(9)]).is_err());
+    }
+}
@@ -1,13 +1,36 @@
-use crate::attr::{Attrs, Nl80211Attr, Nl80211RateInfo, Nl80211StaInfo};
+use crate::attr::{
+    Attrs, Nl80211Attr, Nl80211RateInfo, Nl80211StaBssParam, Nl80211StaInfo, Nl80211TidStats,
+};
+use crate::mac::MacAddress;
+use crate::metrics::{insert_metric, Metrics};
 
 use neli::attr::Attribute;
 use neli::err::DeError;
+use neli::genl::Nlattr;
+use neli::types::Buffer;
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::time::Duration;
 
 /// A struct representing a remote station (Access Point)
+///
+/// Most statistics are optional since drivers report different subsets of them. A station for
+/// e.g. testing a monitor is built up from the `with_*` methods, leaving the rest unreported:
+///
+/// ```
+/// # use neli_wifi::{MacAddress, Station};
+/// let station = Station::default()
+///     .with_bssid(MacAddress([2, 0, 0, 0, 0, 1]))
+///     .with_signal(-42);
+/// assert_eq!(station.metrics()["signal_dbm"], -42.0);
+/// ```
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub struct Station {
-    /// Station bssid (u8)
-    pub bssid: Option<Vec<u8>>,
+    /// Station bssid
+    pub bssid: Option<MacAddress>,
     pub inactive_time: Option<u32>,
     pub rx_bytes: Option<u64>,
     /// Total received packets (MSDUs and MMPDUs) from this station
@@ -25,20 +48,434 @@ pub struct Station {
     pub rx_drop_misc: Option<u64>,
     /// Signal strength of last received PPDU (dBm)
     pub signal: Option<i8>,
-    /// Signal strength average (dBm)
+    /// Signal strength average of data frames (dBm)
     pub average_signal: Option<i8>,
+    /// Signal strength average of beacons (dBm), see [`Station::beacon_signal_dbm`]
     pub beacon_signal_avg: Option<i8>,
+    /// Signal strength of last received PPDU per antenna chain (dBm), empty if not reported
+    pub chain_signal: Vec<i8>,
+    /// Signal strength average per antenna chain (dBm), empty if not reported
+    pub chain_signal_avg: Vec<i8>,
     pub t_offset: Option<u64>,
-    /// Transmission bitrate
-    pub tx_bitrate: Option<u32>,
-    /// Reception bitrate
-    pub rx_bitrate: Option<u32>,
+    /// Rate of the last transmission to this station
+    pub tx_bitrate: Option<RateInfo>,
+    /// Rate of the last reception from this station
+    pub rx_bitrate: Option<RateInfo>,
+    /// Throughput to this station expected by rate control in kbit/s, without the overhead of
+    /// retries and management frames, so usually well below [`tx_bitrate`](Self::tx_bitrate)
+    pub expected_throughput: Option<u32>,
     pub rx_duration: Option<u64>,
     pub tx_duration: Option<u64>,
     pub ack_signal: Option<i8>,
     pub ack_signal_avg: Option<i8>,
     /// Time since the station is last connected in seconds
     pub connected_time: Option<u32>,
+    /// Airtime weight of the station, only effective if the wiphy uses
+    /// [airtime fairness](crate::Wiphy::airtime_fairness)
+    pub airtime_weight: Option<u16>,
+    /// Parameters of the BSS the station is associated to, only reported for stations of
+    /// managed (client) interfaces
+    pub bss_param: Option<StationBssParam>,
+    /// Total received MPDUs from this station
+    pub rx_mpdus: Option<u32>,
+    /// Received MPDUs from this station with an FCS error
+    pub fcs_error_count: Option<u32>,
+    /// Time of the association in ns of `CLOCK_BOOTTIME`
+    pub assoc_at_boottime: Option<u64>,
+    /// Mesh peering information, only reported for mesh peers
+    pub mesh: Option<MeshStationInfo>,
+    /// Per traffic identifier MSDU counters, ordered by TID
+    pub tid_stats: Vec<TidStats>,
+}
+
+/// The modulation (PHY) of a [`RateInfo`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RateMode {
+    /// Legacy (non-HT) rates of 802.11a/b/g
+    #[default]
+    Legacy,
+    /// HT (802.11n)
+    Ht,
+    /// VHT (802.11ac)
+    Vht,
+    /// HE (802.11ax)
+    He,
+    /// EHT (802.11be)
+    Eht,
+}
+
+/// The rate of the last frame sent to or received from a station
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RateInfo {
+    /// Bitrate in 100 kbit/s
+    pub bitrate: u32,
+    /// The modulation, which determines the meaning of [`mcs`](Self::mcs)
+    pub mode: RateMode,
+    /// MCS index, for HT rates this includes the number of spatial streams (0 to 76)
+    pub mcs: Option<u8>,
+    /// Number of spatial streams, only reported for VHT, HE and EHT rates
+    pub nss: Option<u8>,
+    /// Channel width in MHz
+    pub bandwidth: Option<u32>,
+    /// Whether the short (400 ns) guard interval of HT and VHT was used
+    pub short_gi: bool,
+}
+
+impl RateInfo {
+    /// The bitrate in Mbit/s
+    pub fn mbps(&self) -> f64 {
+        f64::from(self.bitrate) / 10.0
+    }
+}
+
+/// Parameters of the BSS a station is associated to
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StationBssParam {
+    /// Whether CTS protection is enabled
+    pub cts_protection: bool,
+    /// Whether short preambles are used
+    pub short_preamble: bool,
+    /// Whether the short slot time is used
+    pub short_slot_time: bool,
+    /// DTIM period of the beacons
+    pub dtim_period: Option<u8>,
+    /// Beacon interval in TU (1024 µs)
+    pub beacon_interval: Option<u16>,
+}
+
+impl TryFrom<&Nlattr<Nl80211StaInfo, Buffer>> for StationBssParam {
+    type Error = DeError;
+
+    fn try_from(attr: &Nlattr<Nl80211StaInfo, Buffer>) -> Result<Self, Self::Error> {
+        let mut res = Self::default();
+        for attr in attr.get_attr_handle::<Nl80211StaBssParam>()?.iter() {
+            match attr.nla_type.nla_type {
+                Nl80211StaBssParam::StaBssParamCtsProt => res.cts_protection = true,
+                Nl80211StaBssParam::StaBssParamShortPreamble => res.short_preamble = true,
+                Nl80211StaBssParam::StaBssParamShortSlotTime => res.short_slot_time = true,
+                Nl80211StaBssParam::StaBssParamDtimPeriod => {
+                    res.dtim_period = Some(attr.get_payload_as()?)
+                }
+                Nl80211StaBssParam::StaBssParamBeaconInterval => {
+                    res.beacon_interval = Some(attr.get_payload_as()?)
+                }
+                _ => (),
+            }
+        }
+        Ok(res)
+    }
+}
+
+/// MSDU counters of a single traffic identifier (TID) of a station
+///
+/// Counters the driver doesn't track are `None`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TidStats {
+    /// Traffic identifier, 0 to 15 for QoS data and 16 for non-QoS traffic
+    pub tid: u8,
+    /// Received MSDUs
+    pub rx_msdu: Option<u64>,
+    /// Transmitted MSDUs
+    pub tx_msdu: Option<u64>,
+    /// Retries of transmitted MSDUs
+    pub tx_msdu_retries: Option<u64>,
+    /// MSDUs which failed to be transmitted
+    pub tx_msdu_failed: Option<u64>,
+}
+
+/// Mesh specific information about a peer station
+///
+/// Link ids and the peer link state are reported by all kernels with mesh support, the power
+/// modes since Linux 3.9. The airtime link metric and the gate/authentication server flags are
+/// only provided by newer (5.x) kernels and drivers using mac80211's mesh implementation.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MeshStationInfo {
+    /// Local mesh peer link id
+    pub llid: Option<u16>,
+    /// Peer mesh peer link id
+    pub plid: Option<u16>,
+    /// Peer link state (0: listen, 1: open sent, 2: open received, 3: confirm received,
+    /// 4: established, 5: holding, 6: blocked)
+    pub plink_state: Option<u8>,
+    /// Local power mode towards the peer (1: active, 2: light sleep, 3: deep sleep)
+    pub local_pm: Option<u32>,
+    /// Power mode of the peer towards us, same values as `local_pm`
+    pub peer_pm: Option<u32>,
+    /// Power mode towards non-peer stations, same values as `local_pm`
+    pub nonpeer_pm: Option<u32>,
+    /// HWMP airtime link metric of the link to the peer
+    pub airtime_link_metric: Option<u32>,
+    /// Whether the peer announces a connection to a mesh gate
+    pub connected_to_gate: Option<bool>,
+    /// Whether the peer announces a connection to an authentication server
+    pub connected_to_as: Option<bool>,
+}
+
+impl Station {
+    /// Set the [`bssid`](Self::bssid)
+    pub fn with_bssid(mut self, bssid: MacAddress) -> Self {
+        self.bssid = Some(bssid);
+        self
+    }
+
+    /// Set the [`signal`](Self::signal) strength (dBm)
+    pub fn with_signal(mut self, signal: i8) -> Self {
+        self.signal = Some(signal);
+        self
+    }
+
+    /// Set the byte counters [`rx_bytes`](Self::rx_bytes) and [`tx_bytes`](Self::tx_bytes)
+    pub fn with_bytes(mut self, rx_bytes: u64, tx_bytes: u64) -> Self {
+        self.rx_bytes = Some(rx_bytes);
+        self.tx_bytes = Some(tx_bytes);
+        self
+    }
+
+    /// Set the [`tx_bitrate`](Self::tx_bitrate)
+    pub fn with_tx_bitrate(mut self, rate: RateInfo) -> Self {
+        self.tx_bitrate = Some(rate);
+        self
+    }
+
+    /// Set the [`connected_time`](Self::connected_time) in seconds
+    pub fn with_connected_time(mut self, seconds: u32) -> Self {
+        self.connected_time = Some(seconds);
+        self
+    }
+
+    /// Average signal strength of the beacons received from this station (dBm)
+    ///
+    /// Unlike [`Station::average_signal`], which averages over data frames, this only takes
+    /// beacons into account and is therefore only reported for stations sending beacons (e.g.
+    /// the access point of a managed interface).
+    pub fn beacon_signal_dbm(&self) -> Option<i8> {
+        self.beacon_signal_avg
+    }
+
+    /// Compare two stations by signal strength, stronger stations first
+    ///
+    /// Stations without a signal are ordered last. This is meant to be used with
+    /// [`slice::sort_by`] to pick the best peer:
+    ///
+    /// ```
+    /// # use neli_wifi::Station;
+    /// # let mut stations: Vec<Station> = Vec::new();
+    /// stations.sort_by(Station::cmp_signal);
+    /// let best = stations.first();
+    /// ```
+    pub fn cmp_signal(&self, other: &Self) -> Ordering {
+        match (self.signal, other.signal) {
+            (Some(a), Some(b)) => b.cmp(&a),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+
+    /// All known numeric values of the station, for metric exporters
+    ///
+    /// Keys are stable and carry the unit where it isn't a plain count, e.g. `"rx_bytes"`,
+    /// `"signal_dbm"` or `"tx_bitrate_mbps"`. Unknown values are left out.
+    pub fn metrics(&self) -> HashMap<&'static str, f64> {
+        let mut metrics = Metrics::new();
+        let m = &mut metrics;
+        insert_metric(m, "inactive_time_ms", self.inactive_time.map(f64::from));
+        insert_metric(m, "rx_bytes", self.rx_bytes.map(|v| v as f64));
+        insert_metric(m, "rx_packets", self.rx_packets.map(f64::from));
+        insert_metric(m, "tx_bytes", self.tx_bytes.map(|v| v as f64));
+        insert_metric(m, "tx_packets", self.tx_packets.map(f64::from));
+        insert_metric(m, "tx_retries", self.tx_retries.map(f64::from));
+        insert_metric(m, "tx_failed", self.tx_failed.map(f64::from));
+        insert_metric(m, "beacon_rx", self.beacon_rx.map(|v| v as f64));
+        insert_metric(m, "beacon_loss", self.beacon_loss.map(f64::from));
+        insert_metric(m, "rx_drop_misc", self.rx_drop_misc.map(|v| v as f64));
+        insert_metric(m, "signal_dbm", self.signal.map(f64::from));
+        insert_metric(m, "average_signal_dbm", self.average_signal.map(f64::from));
+        insert_metric(
+            m,
+            "beacon_signal_avg_dbm",
+            self.beacon_signal_avg.map(f64::from),
+        );
+        insert_metric(m, "t_offset_us", self.t_offset.map(|v| v as f64));
+        // Bitrates are reported in units of 100 kbit/s
+        insert_metric(
+            m,
+            "tx_bitrate_mbps",
+            self.tx_bitrate.as_ref().map(RateInfo::mbps),
+        );
+        insert_metric(
+            m,
+            "rx_bitrate_mbps",
+            self.rx_bitrate.as_ref().map(RateInfo::mbps),
+        );
+        insert_metric(
+            m,
+            "expected_throughput_mbps",
+            self.expected_throughput.map(|v| f64::from(v) / 1000.0),
+        );
+        insert_metric(m, "rx_duration_us", self.rx_duration.map(|v| v as f64));
+        insert_metric(m, "tx_duration_us", self.tx_duration.map(|v| v as f64));
+        insert_metric(m, "ack_signal_dbm", self.ack_signal.map(f64::from));
+        insert_metric(m, "ack_signal_avg_dbm", self.ack_signal_avg.map(f64::from));
+        insert_metric(m, "connected_time_s", self.connected_time.map(f64::from));
+        insert_metric(m, "airtime_weight", self.airtime_weight.map(f64::from));
+        metrics
+    }
+
+    fn mesh_mut(&mut self) -> &mut MeshStationInfo {
+        self.mesh.get_or_insert_with(Default::default)
+    }
+}
+
+/// Change of the traffic counters of a station between two snapshots
+///
+/// Counters that are unknown in either snapshot, or went backwards (e.g. because the station
+/// reassociated), have no delta.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct StationDelta {
+    pub rx_bytes: Option<u64>,
+    pub tx_bytes: Option<u64>,
+    pub rx_packets: Option<u32>,
+    pub tx_packets: Option<u32>,
+    /// Time between the two snapshots
+    pub elapsed: Duration,
+}
+
+impl StationDelta {
+    /// Compute the delta from snapshot `prev` to snapshot `cur`, taken `elapsed` apart
+    pub fn between(prev: &Station, cur: &Station, elapsed: Duration) -> Self {
+        Self {
+            rx_bytes: cur
+                .rx_bytes
+                .zip(prev.rx_bytes)
+                .and_then(|(c, p)| c.checked_sub(p)),
+            tx_bytes: cur
+                .tx_bytes
+                .zip(prev.tx_bytes)
+                .and_then(|(c, p)| c.checked_sub(p)),
+            rx_packets: cur
+                .rx_packets
+                .zip(prev.rx_packets)
+                .and_then(|(c, p)| c.checked_sub(p)),
+            tx_packets: cur
+                .tx_packets
+                .zip(prev.tx_packets)
+                .and_then(|(c, p)| c.checked_sub(p)),
+            elapsed,
+        }
+    }
+
+    fn per_sec(&self, delta: Option<f64>) -> Option<f64> {
+        let secs = self.elapsed.as_secs_f64();
+        delta.filter(|_| secs > 0.0).map(|delta| delta / secs)
+    }
+
+    /// Received bytes per second, `None` if unknown or no time elapsed
+    pub fn rx_bytes_per_sec(&self) -> Option<f64> {
+        self.per_sec(self.rx_bytes.map(|v| v as f64))
+    }
+
+    /// Transmitted bytes per second, `None` if unknown or no time elapsed
+    pub fn tx_bytes_per_sec(&self) -> Option<f64> {
+        self.per_sec(self.tx_bytes.map(|v| v as f64))
+    }
+
+    /// Received packets per second, `None` if unknown or no time elapsed
+    pub fn rx_packets_per_sec(&self) -> Option<f64> {
+        self.per_sec(self.rx_packets.map(f64::from))
+    }
+
+    /// Transmitted packets per second, `None` if unknown or no time elapsed
+    pub fn tx_packets_per_sec(&self) -> Option<f64> {
+        self.per_sec(self.tx_packets.map(f64::from))
+    }
+}
+
+/// Minimum, maximum and average of a signal strength (dBm) over several snapshots
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SignalStats {
+    pub min: i8,
+    pub max: i8,
+    pub avg: f64,
+    /// Number of snapshots which reported the signal
+    pub count: usize,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct SignalAccumulator {
+    min: i8,
+    max: i8,
+    sum: i64,
+    count: usize,
+}
+
+impl SignalAccumulator {
+    fn push(&mut self, signal: Option<i8>) {
+        let Some(signal) = signal else {
+            return;
+        };
+        if self.count == 0 {
+            self.min = signal;
+            self.max = signal;
+        } else {
+            self.min = self.min.min(signal);
+            self.max = self.max.max(signal);
+        }
+        self.sum += i64::from(signal);
+        self.count += 1;
+    }
+
+    fn stats(&self) -> Option<SignalStats> {
+        (self.count > 0).then(|| SignalStats {
+            min: self.min,
+            max: self.max,
+            avg: self.sum as f64 / self.count as f64,
+            count: self.count,
+        })
+    }
+}
+
+/// Tracks the signal and ACK signal of a station across several [`Station`] snapshots
+///
+/// Snapshots are typically taken by polling `get_station_info` periodically. Snapshots which
+/// don't report a value are skipped for that value.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StationWindow {
+    signal: SignalAccumulator,
+    ack_signal: SignalAccumulator,
+}
+
+impl StationWindow {
+    /// Create an empty window
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a snapshot of the station
+    pub fn push(&mut self, station: &Station) {
+        self.signal.push(station.signal);
+        self.ack_signal.push(station.ack_signal);
+    }
+
+    /// Statistics of [`Station::signal`], `None` if no snapshot reported it
+    pub fn signal(&self) -> Option<SignalStats> {
+        self.signal.stats()
+    }
+
+    /// Statistics of [`Station::ack_signal`], `None` if no snapshot reported it
+    pub fn ack_signal(&self) -> Option<SignalStats> {
+        self.ack_signal.stats()
+    }
+
+    /// Forget all snapshots
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
 }
 
 impl TryFrom<Attrs<'_, Nl80211Attr>> for Station {
@@ -47,7 +484,7 @@ impl TryFrom<Attrs<'_, Nl80211Attr>> for Station {
     fn try_from(attrs: Attrs<'_, Nl80211Attr>) -> Result<Self, Self::Error> {
         let mut res = Self::default();
         if let Some(bssid) = attrs.get_attribute(Nl80211Attr::AttrMac) {
-            res.bssid = Some(Vec::from(bssid.nla_payload.as_ref()));
+            res.bssid = Some(bssid.nla_payload.as_ref().try_into()?);
         }
 
         if let Some(info) = attrs.get_attribute(Nl80211Attr::AttrStaInfo) {
@@ -58,19 +495,15 @@ impl TryFrom<Attrs<'_, Nl80211Attr>> for Station {
                         res.inactive_time = Some(attr.get_payload_as()?)
                     }
                     Nl80211StaInfo::StaInfoRxBytes64 => res.rx_bytes = Some(attr.get_payload_as()?),
-                    Nl80211StaInfo::StaInfoRxBytes => {
-                        if res.rx_bytes.is_none() {
-                            res.rx_bytes = Some(attr.get_payload_as::<u32>()? as u64)
-                        }
+                    Nl80211StaInfo::StaInfoRxBytes if res.rx_bytes.is_none() => {
+                        res.rx_bytes = Some(attr.get_payload_as::<u32>()? as u64)
                     }
                     Nl80211StaInfo::StaInfoRxPackets => {
                         res.rx_packets = Some(attr.get_payload_as()?)
                     }
                     Nl80211StaInfo::StaInfoTxBytes64 => res.tx_bytes = Some(attr.get_payload_as()?),
-                    Nl80211StaInfo::StaInfoTxBytes => {
-                        if res.tx_bytes.is_none() {
-                            res.tx_bytes = Some(attr.get_payload_as::<u32>()? as u64)
-                        }
+                    Nl80211StaInfo::StaInfoTxBytes if res.tx_bytes.is_none() => {
+                        res.tx_bytes = Some(attr.get_payload_as::<u32>()? as u64)
                     }
                     Nl80211StaInfo::StaInfoTxPackets => {
                         res.tx_packets = Some(attr.get_payload_as()?)
@@ -93,25 +526,22 @@ impl TryFrom<Attrs<'_, Nl80211Attr>> for Station {
                     Nl80211StaInfo::StaInfoBeaconSignalAvg => {
                         res.beacon_signal_avg = Some(attr.get_payload_as()?)
                     }
+                    Nl80211StaInfo::StaInfoChainSignal => res.chain_signal = chain_signal(attr)?,
+                    Nl80211StaInfo::StaInfoChainSignalAvg => {
+                        res.chain_signal_avg = chain_signal(attr)?
+                    }
                     Nl80211StaInfo::StaInfoTOffset => res.t_offset = Some(attr.get_payload_as()?),
                     Nl80211StaInfo::StaInfoTxBitrate => {
-                        if let Some(rate) = attr
-                            .get_attr_handle::<Nl80211RateInfo>()?
-                            .get_attribute(Nl80211RateInfo::RateInfoBitrate32)
-                        {
-                            res.tx_bitrate = Some(rate.get_payload_as()?);
-                        }
+                        res.tx_bitrate = rate_info(attr)?;
+                    }
+                    Nl80211StaInfo::StaInfoExpectedThroughput => {
+                        res.expected_throughput = Some(attr.get_payload_as()?)
                     }
                     Nl80211StaInfo::StaInfoTxDuration => {
                         res.tx_duration = Some(attr.get_payload_as()?)
                     }
                     Nl80211StaInfo::StaInfoRxBitrate => {
-                        if let Some(rate) = attr
-                            .get_attr_handle::<Nl80211RateInfo>()?
-                            .get_attribute(Nl80211RateInfo::RateInfoBitrate32)
-                        {
-                            res.rx_bitrate = Some(rate.get_payload_as()?);
-                        }
+                        res.rx_bitrate = rate_info(attr)?;
                     }
                     Nl80211StaInfo::StaInfoRxDuration => {
                         res.rx_duration = Some(attr.get_payload_as()?)
@@ -125,6 +555,45 @@ impl TryFrom<Attrs<'_, Nl80211Attr>> for Station {
                     Nl80211StaInfo::StaInfoConnectedTime => {
                         res.connected_time = Some(attr.get_payload_as()?)
                     }
+                    Nl80211StaInfo::StaInfoAirtimeWeight => {
+                        res.airtime_weight = Some(attr.get_payload_as()?)
+                    }
+                    Nl80211StaInfo::StaInfoBssParam => res.bss_param = Some(attr.try_into()?),
+                    Nl80211StaInfo::StaInfoTidStats => res.tid_stats = tid_stats(attr)?,
+                    Nl80211StaInfo::StaInfoRxMpdus => res.rx_mpdus = Some(attr.get_payload_as()?),
+                    Nl80211StaInfo::StaInfoFcsErrorCount => {
+                        res.fcs_error_count = Some(attr.get_payload_as()?)
+                    }
+                    Nl80211StaInfo::StaInfoAssocAtBoottime => {
+                        res.assoc_at_boottime = Some(attr.get_payload_as()?)
+                    }
+                    Nl80211StaInfo::StaInfoLlid => {
+                        res.mesh_mut().llid = Some(attr.get_payload_as()?)
+                    }
+                    Nl80211StaInfo::StaInfoPlid => {
+                        res.mesh_mut().plid = Some(attr.get_payload_as()?)
+                    }
+                    Nl80211StaInfo::StaInfoPlinkState => {
+                        res.mesh_mut().plink_state = Some(attr.get_payload_as()?)
+                    }
+                    Nl80211StaInfo::StaInfoLocalPm => {
+                        res.mesh_mut().local_pm = Some(attr.get_payload_as()?)
+                    }
+                    Nl80211StaInfo::StaInfoPeerPm => {
+                        res.mesh_mut().peer_pm = Some(attr.get_payload_as()?)
+                    }
+                    Nl80211StaInfo::StaInfoNonpeerPm => {
+                        res.mesh_mut().nonpeer_pm = Some(attr.get_payload_as()?)
+                    }
+                    Nl80211StaInfo::StaInfoAirtimeLinkMetric => {
+                        res.mesh_mut().airtime_link_metric = Some(attr.get_payload_as()?)
+                    }
+                    Nl80211StaInfo::StaInfoConnectedToGate => {
+                        res.mesh_mut().connected_to_gate = Some(attr.get_payload_as::<u8>()? != 0)
+                    }
+                    Nl80211StaInfo::StaInfoConnectedToAs => {
+                        res.mesh_mut().connected_to_as = Some(attr.get_payload_as::<u8>()? != 0)
+                    }
                     _ => (),
                 }
             }
@@ -133,6 +602,96 @@ impl TryFrom<Attrs<'_, Nl80211Attr>> for Station {
     }
 }
 
+/// Decode a nested array of signal strengths, one per antenna chain
+fn chain_signal(attr: &Nlattr<Nl80211StaInfo, Buffer>) -> Result<Vec<i8>, DeError> {
+    attr.get_attr_handle::<u16>()?
+        .iter()
+        .map(|chain| chain.get_payload_as())
+        .collect()
+}
+
+/// Decode a nested array of TID statistics, the kernel indexes it by TID + 1
+fn tid_stats(attr: &Nlattr<Nl80211StaInfo, Buffer>) -> Result<Vec<TidStats>, DeError> {
+    let mut res = Vec::new();
+    for tid in attr.get_attr_handle::<u16>()?.iter() {
+        let Some(index) = tid.nla_type.nla_type.checked_sub(1) else {
+            continue;
+        };
+        let mut stats = TidStats {
+            tid: u8::try_from(index).map_err(|_| DeError::new("TID out of range"))?,
+            ..Default::default()
+        };
+        for attr in tid.get_attr_handle::<Nl80211TidStats>()?.iter() {
+            match attr.nla_type.nla_type {
+                Nl80211TidStats::TidStatsRxMsdu => stats.rx_msdu = Some(attr.get_payload_as()?),
+                Nl80211TidStats::TidStatsTxMsdu => stats.tx_msdu = Some(attr.get_payload_as()?),
+                Nl80211TidStats::TidStatsTxMsduRetries => {
+                    stats.tx_msdu_retries = Some(attr.get_payload_as()?)
+                }
+                Nl80211TidStats::TidStatsTxMsduFailed => {
+                    stats.tx_msdu_failed = Some(attr.get_payload_as()?)
+                }
+                _ => (),
+            }
+        }
+        res.push(stats);
+    }
+    Ok(res)
+}
+
+/// Decode a rate info attribute, `None` if the kernel couldn't calculate the bitrate
+///
+/// Old kernels only report the 16-bit bitrate, which can't represent rates above 6.5 Gbit/s.
+/// The kernel leaves out the width for 20 MHz channels.
+fn rate_info(attr: &Nlattr<Nl80211StaInfo, Buffer>) -> Result<Option<RateInfo>, DeError> {
+    let mut res = RateInfo {
+        bandwidth: Some(20),
+        ..Default::default()
+    };
+    let mut bitrate = None;
+    let mut bitrate16 = None;
+    for attr in attr.get_attr_handle::<Nl80211RateInfo>()?.iter() {
+        match attr.nla_type.nla_type {
+            Nl80211RateInfo::RateInfoBitrate32 => bitrate = Some(attr.get_payload_as()?),
+            Nl80211RateInfo::RateInfoBitrate => {
+                bitrate16 = Some(u32::from(attr.get_payload_as::<u16>()?))
+            }
+            Nl80211RateInfo::RateInfoMcs => {
+                res.mode = RateMode::Ht;
+                res.mcs = Some(attr.get_payload_as()?);
+            }
+            Nl80211RateInfo::RateInfoVhtMcs => {
+                res.mode = RateMode::Vht;
+                res.mcs = Some(attr.get_payload_as()?);
+            }
+            Nl80211RateInfo::RateInfoHeMcs => {
+                res.mode = RateMode::He;
+                res.mcs = Some(attr.get_payload_as()?);
+            }
+            Nl80211RateInfo::RateInfoEhtMcs => {
+                res.mode = RateMode::Eht;
+                res.mcs = Some(attr.get_payload_as()?);
+            }
+            Nl80211RateInfo::RateInfoVhtNss
+            | Nl80211RateInfo::RateInfoHeNss
+            | Nl80211RateInfo::RateInfoEhtNss => res.nss = Some(attr.get_payload_as()?),
+            Nl80211RateInfo::RateInfo5MhzWidth => res.bandwidth = Some(5),
+            Nl80211RateInfo::RateInfo10MhzWidth => res.bandwidth = Some(10),
+            Nl80211RateInfo::RateInfo40MhzWidth => res.bandwidth = Some(40),
+            Nl80211RateInfo::RateInfo80MhzWidth => res.bandwidth = Some(80),
+            Nl80211RateInfo::RateInfo80p80MhzWidth | Nl80211RateInfo::RateInfo160MhzWidth => {
+                res.bandwidth = Some(160)
+            }
+            Nl80211RateInfo::RateInfo320MhzWidth => res.bandwidth = Some(320),
+            Nl80211RateInfo::RateInfoShortGi => res.short_gi = true,
+            _ => (),
+        }
+    }
+    Ok(bitrate
+        .or(bitrate16)
+        .map(|bitrate| RateInfo { bitrate, ..res }))
+}
+
 #[cfg(test)]
 mod tests_station {
     use super::*;
@@ -264,21 +823,433 @@ mod tests_station {
         let station: Station = AttrHandle::new(handler.into_iter().collect())
             .try_into()
             .unwrap();
+        // All TIDs report zeroed counters, except best effort, voice and non-QoS traffic
+        let mut tid_stats: Vec<_> = (0..=16)
+            .map(|tid| TidStats {
+                tid,
+                rx_msdu: Some(0),
+                tx_msdu: Some(0),
+                tx_msdu_retries: Some(0),
+                tx_msdu_failed: Some(0),
+            })
+            .collect();
+        tid_stats[0].rx_msdu = Some(354216);
+        tid_stats[0].tx_msdu = Some(174407);
+        tid_stats[6].tx_msdu = Some(180);
+        tid_stats[7].tx_msdu = Some(2);
+        tid_stats[16].rx_msdu = Some(6509);
+        tid_stats[16].tx_msdu = Some(4);
         let expected_station = Station {
+            ack_signal: Some(i8::from_le_bytes([46])),
             average_signal: Some(i8::from_le_bytes([215])),
             beacon_loss: Some(u32::from_le_bytes([0, 0, 0, 0])),
-            bssid: Some(vec![46, 46, 46, 46, 46, 46]),
+            beacon_rx: Some(u64::from_le_bytes([225, 254, 0, 0, 0, 0, 0, 0])),
+            beacon_signal_avg: Some(i8::from_le_bytes([216])),
+            chain_signal: vec![-40, -43],
+            chain_signal_avg: vec![-44, -45],
+            expected_throughput: Some(41156),
+            bss_param: Some(StationBssParam {
+                short_preamble: true,
+                short_slot_time: true,
+                dtim_period: Some(1),
+                beacon_interval: Some(100),
+                ..Default::default()
+            }),
+            bssid: Some(MacAddress([46; 6])),
             connected_time: Some(u32::from_le_bytes([17, 27, 0, 0])),
-            rx_bitrate: Some(u32::from_le_bytes([134, 1, 0, 0])),
+            inactive_time: Some(u32::from_le_bytes([248, 2, 0, 0])),
+            // HT MCS 4 and 13 on a 20 MHz channel
+            rx_bitrate: Some(RateInfo {
+                bitrate: 390,
+                mode: RateMode::Ht,
+                mcs: Some(4),
+                bandwidth: Some(20),
+                ..Default::default()
+            }),
+            rx_bytes: Some(u64::from_le_bytes([43, 98, 156, 29, 0, 0, 0, 0])),
+            rx_drop_misc: Some(u64::from_le_bytes([183, 3, 0, 0, 0, 0, 0, 0])),
             rx_packets: Some(u32::from_le_bytes([226, 128, 7, 0])),
             signal: Some(i8::from_le_bytes([218])),
-            tx_bitrate: Some(u32::from_le_bytes([16, 4, 0, 0])),
+            tx_bitrate: Some(RateInfo {
+                bitrate: 1040,
+                mode: RateMode::Ht,
+                mcs: Some(13),
+                bandwidth: Some(20),
+                ..Default::default()
+            }),
+            tx_bytes: Some(u64::from_le_bytes([99, 123, 109, 1, 0, 0, 0, 0])),
             tx_failed: Some(u32::from_le_bytes([47, 0, 0, 0])),
             tx_packets: Some(u32::from_le_bytes([9, 170, 2, 0])),
             tx_retries: Some(u32::from_le_bytes([27, 130, 0, 0])),
+            tid_stats,
             ..Default::default()
         };
 
         assert_eq!(station, expected_station)
     }
+
+    #[test]
+    fn test_signal_averages() {
+        let handler = vec![new_attr(
+            AttrStaInfo,
+            vec![5, 0, 13, 0, 215, 0, 0, 0, 5, 0, 30, 0, 196, 0, 0, 0],
+        )];
+
+        let station: Station = AttrHandle::new(handler.into_iter().collect())
+            .try_into()
+            .unwrap();
+
+        assert_eq!(station.average_signal, Some(-41));
+        assert_eq!(station.beacon_signal_avg, Some(-60));
+        assert_eq!(station.beacon_signal_dbm(), Some(-60));
+    }
+
+    #[test]
+    fn test_chain_signal() {
+        // Two chains at -41 and -45 dBm, averaging -42 and -44 dBm
+        let handler = vec![new_attr(
+            AttrStaInfo,
+            vec![
+                20, 0, 25, 0, 5, 0, 0, 0, 215, 0, 0, 0, 5, 0, 1, 0, 211, 0, 0, 0, 20, 0, 26, 0, 5,
+                0, 0, 0, 214, 0, 0, 0, 5, 0, 1, 0, 212, 0, 0, 0,
+            ],
+        )];
+
+        let station: Station = AttrHandle::new(handler.into_iter().collect())
+            .try_into()
+            .unwrap();
+
+        assert_eq!(station.chain_signal, vec![-41, -45]);
+        assert_eq!(station.chain_signal_avg, vec![-42, -44]);
+        assert!(Station::default().chain_signal.is_empty());
+    }
+
+    #[test]
+    fn test_tid_stats() {
+        // 10 MSDUs received on TID 0, 5 non-QoS MSDUs sent with one failure, the driver doesn't
+        // track the other counters
+        let handler = vec![new_attr(
+            AttrStaInfo,
+            vec![
+                48, 0, 31, 0, 16, 0, 1, 0, 12, 0, 1, 0, 10, 0, 0, 0, 0, 0, 0, 0, 28, 0, 17, 0, 12,
+                0, 2, 0, 5, 0, 0, 0, 0, 0, 0, 0, 12, 0, 4, 0, 1, 0, 0, 0, 0, 0, 0, 0,
+            ],
+        )];
+
+        let station: Station = AttrHandle::new(handler.into_iter().collect())
+            .try_into()
+            .unwrap();
+
+        assert_eq!(
+            station.tid_stats,
+            vec![
+                TidStats {
+                    tid: 0,
+                    rx_msdu: Some(10),
+                    ..Default::default()
+                },
+                TidStats {
+                    tid: 16,
+                    tx_msdu: Some(5),
+                    tx_msdu_failed: Some(1),
+                    ..Default::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_byte_counters_prefer_64bit() {
+        // 32-bit counters which wrapped: 1000 tx and 7 rx bytes, the 64-bit counters are at
+        // 5000000000 tx and 4294967303 rx bytes
+        let tx32: &[u8] = &[8, 0, 3, 0, 232, 3, 0, 0];
+        let tx64: &[u8] = &[12, 0, 24, 0, 0, 242, 5, 42, 1, 0, 0, 0];
+        let rx32: &[u8] = &[8, 0, 2, 0, 7, 0, 0, 0];
+        let rx64: &[u8] = &[12, 0, 23, 0, 7, 0, 0, 0, 1, 0, 0, 0];
+
+        for info in [
+            [tx32, tx64, rx32, rx64].concat(),
+            [tx64, tx32, rx64, rx32].concat(),
+        ] {
+            let handler = vec![new_attr(AttrStaInfo, info)];
+            let station: Station = AttrHandle::new(handler.into_iter().collect())
+                .try_into()
+                .unwrap();
+
+            assert_eq!(station.tx_bytes, Some(5000000000));
+            assert_eq!(station.rx_bytes, Some(4294967303));
+        }
+
+        // Old kernels only report the 32-bit counters
+        let handler = vec![new_attr(AttrStaInfo, [tx32, rx32].concat())];
+        let station: Station = AttrHandle::new(handler.into_iter().collect())
+            .try_into()
+            .unwrap();
+        assert_eq!(station.tx_bytes, Some(1000));
+        assert_eq!(station.rx_bytes, Some(7));
+    }
+
+    #[test]
+    fn test_link_details() {
+        // Client of a BSS with CTS protection and short slots, DTIM period 2 and a beacon
+        // interval of 100 TU, associated 5 s after boot
+        let handler = vec![
+            new_attr(AttrMac, vec![2, 0, 0, 0, 0, 1]),
+            new_attr(
+                AttrStaInfo,
+                vec![
+                    28, 0, 15, 0, 4, 0, 1, 0, 4, 0, 3, 0, 5, 0, 4, 0, 2, 0, 0, 0, 6, 0, 5, 0, 100,
+                    0, 0, 0, 8, 0, 36, 0, 16, 39, 0, 0, 8, 0, 37, 0, 42, 0, 0, 0, 12, 0, 42, 0, 0,
+                    242, 5, 42, 1, 0, 0, 0,
+                ],
+            ),
+        ];
+
+        let station: Station = AttrHandle::new(handler.into_iter().collect())
+            .try_into()
+            .unwrap();
+
+        assert_eq!(
+            station.bss_param,
+            Some(StationBssParam {
+                cts_protection: true,
+                short_preamble: false,
+                short_slot_time: true,
+                dtim_period: Some(2),
+                beacon_interval: Some(100),
+            })
+        );
+        assert_eq!(station.rx_mpdus, Some(10000));
+        assert_eq!(station.fcs_error_count, Some(42));
+        assert_eq!(station.assoc_at_boottime, Some(5_000_000_000));
+    }
+
+    #[test]
+    fn test_cmp_signal() {
+        let station = |signal| Station {
+            signal,
+            ..Default::default()
+        };
+
+        let mut stations = vec![
+            station(Some(-70)),
+            station(None),
+            station(Some(-40)),
+            station(Some(-55)),
+        ];
+        stations.sort_by(Station::cmp_signal);
+
+        assert_eq!(
+            stations,
+            vec![
+                station(Some(-40)),
+                station(Some(-55)),
+                station(Some(-70)),
+                station(None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_legacy_bitrate() {
+        // Only the 16-bit bitrates: 54 Mbit/s tx, 1 Mbit/s rx
+        let handler = vec![
+            new_attr(AttrMac, vec![2, 0, 0, 0, 0, 1]),
+            new_attr(
+                AttrStaInfo,
+                vec![
+                    12, 0, 8, 0, 6, 0, 1, 0, 28, 2, 0, 0, 12, 0, 14, 0, 6, 0, 1, 0, 10, 0, 0, 0,
+                ],
+            ),
+        ];
+
+        let station: Station = AttrHandle::new(handler.into_iter().collect())
+            .try_into()
+            .unwrap();
+
+        assert_eq!(station.tx_bitrate.map(|rate| rate.bitrate), Some(540));
+        assert_eq!(station.rx_bitrate.map(|rate| rate.bitrate), Some(10));
+        assert_eq!(station.expected_throughput, None);
+    }
+
+    #[test]
+    fn test_he_rate() {
+        // HE MCS 11 with 2 spatial streams on a 160 MHz channel, 2402 Mbit/s, of which rate
+        // control expects 1500 Mbit/s
+        let handler = vec![new_attr(
+            AttrStaInfo,
+            vec![
+                40, 0, 8, 0, 8, 0, 5, 0, 212, 93, 0, 0, 5, 0, 13, 0, 11, 0, 0, 0, 5, 0, 14, 0, 2,
+                0, 0, 0, 4, 0, 10, 0, 5, 0, 15, 0, 0, 0, 0, 0, 8, 0, 27, 0, 96, 227, 22, 0,
+            ],
+        )];
+
+        let station: Station = AttrHandle::new(handler.into_iter().collect())
+            .try_into()
+            .unwrap();
+
+        let rate = station.tx_bitrate.unwrap();
+        assert_eq!(
+            rate,
+            RateInfo {
+                bitrate: 24020,
+                mode: RateMode::He,
+                mcs: Some(11),
+                nss: Some(2),
+                bandwidth: Some(160),
+                short_gi: false,
+            }
+        );
+        assert_eq!(rate.mbps(), 2402.0);
+        assert_eq!(station.expected_throughput, Some(1_500_000));
+    }
+
+    #[test]
+    fn test_mesh_peer() {
+        // Established mesh peering in active mode, connected to a gate
+        let handler = vec![
+            new_attr(AttrMac, vec![2, 0, 0, 0, 2, 0]),
+            new_attr(
+                AttrStaInfo,
+                vec![
+                    5, 0, 7, 0, 200, 0, 0, 0, 6, 0, 4, 0, 52, 18, 0, 0, 6, 0, 5, 0, 120, 86, 0, 0,
+                    5, 0, 6, 0, 4, 0, 0, 0, 8, 0, 20, 0, 1, 0, 0, 0, 8, 0, 21, 0, 2, 0, 0, 0, 8, 0,
+                    22, 0, 1, 0, 0, 0, 8, 0, 41, 0, 108, 1, 0, 0, 5, 0, 38, 0, 1, 0, 0, 0, 5, 0,
+                    43, 0, 0, 0, 0, 0,
+                ],
+            ),
+        ];
+
+        let station: Station = AttrHandle::new(handler.into_iter().collect())
+            .try_into()
+            .unwrap();
+
+        assert_eq!(station.signal, Some(-56));
+        assert_eq!(
+            station.mesh,
+            Some(MeshStationInfo {
+                llid: Some(0x1234),
+                plid: Some(0x5678),
+                plink_state: Some(4),
+                local_pm: Some(1),
+                peer_pm: Some(2),
+                nonpeer_pm: Some(1),
+                airtime_link_metric: Some(364),
+                connected_to_gate: Some(true),
+                connected_to_as: Some(false),
+            })
+        );
+    }
+
+    #[test]
+    fn test_metrics() {
+        let station = Station {
+            bssid: Some(MacAddress([46; 6])),
+            rx_bytes: Some(1 << 40),
+            signal: Some(-56),
+            tx_bitrate: Some(RateInfo {
+                bitrate: 8667,
+                ..Default::default()
+            }),
+            connected_time: Some(3600),
+            ..Default::default()
+        };
+
+        let metrics = station.metrics();
+        assert_eq!(metrics.len(), 4);
+        assert_eq!(metrics["rx_bytes"], 1099511627776.0);
+        assert_eq!(metrics["signal_dbm"], -56.0);
+        assert_eq!(metrics["tx_bitrate_mbps"], 866.7);
+        assert_eq!(metrics["connected_time_s"], 3600.0);
+        assert!(Station::default().metrics().is_empty());
+    }
+
+    #[test]
+    fn test_station_window() {
+        let snapshot = |signal, ack_signal| Station {
+            signal,
+            ack_signal,
+            ..Default::default()
+        };
+
+        let mut window = StationWindow::new();
+        assert_eq!(window.signal(), None);
+
+        window.push(&snapshot(Some(-60), Some(-58)));
+        window.push(&snapshot(Some(-50), None));
+        window.push(&snapshot(Some(-70), Some(-62)));
+        window.push(&snapshot(None, Some(-54)));
+
+        assert_eq!(
+            window.signal(),
+            Some(SignalStats {
+                min: -70,
+                max: -50,
+                avg: -60.0,
+                count: 3,
+            })
+        );
+        assert_eq!(
+            window.ack_signal(),
+            Some(SignalStats {
+                min: -62,
+                max: -54,
+                avg: -58.0,
+                count: 3,
+            })
+        );
+
+        window.reset();
+        assert_eq!(window.ack_signal(), None);
+    }
+
+    #[test]
+    fn test_station_delta() {
+        let prev = Station {
+            rx_packets: Some(1000),
+            tx_packets: Some(500),
+            rx_bytes: Some(10_000),
+            ..Default::default()
+        };
+        let cur = Station {
+            rx_packets: Some(1300),
+            tx_packets: Some(400),
+            rx_bytes: Some(30_000),
+            ..Default::default()
+        };
+
+        let delta = StationDelta::between(&prev, &cur, Duration::from_secs(2));
+        assert_eq!(delta.rx_packets_per_sec(), Some(150.0));
+        assert_eq!(delta.rx_bytes_per_sec(), Some(10_000.0));
+        // Counter went backwards
+        assert_eq!(delta.tx_packets_per_sec(), None);
+        // Unknown in both snapshots
+        assert_eq!(delta.tx_bytes_per_sec(), None);
+
+        let delta = StationDelta::between(&prev, &cur, Duration::ZERO);
+        assert_eq!(delta.rx_packets, Some(300));
+        assert_eq!(delta.rx_packets_per_sec(), None);
+    }
+
+    #[test]
+    fn test_builder() {
+        let rate = RateInfo {
+            bitrate: 866,
+            ..Default::default()
+        };
+        let station = Station::default()
+            .with_bssid(MacAddress([2, 0, 0, 0, 0, 1]))
+            .with_signal(-42)
+            .with_bytes(1000, 2000)
+            .with_tx_bitrate(rate)
+            .with_connected_time(60);
+
+        assert_eq!(station.bssid, Some(MacAddress([2, 0, 0, 0, 0, 1])));
+        assert_eq!(station.signal, Some(-42));
+        assert_eq!(station.rx_bytes, Some(1000));
+        assert_eq!(station.tx_bytes, Some(2000));
+        assert_eq!(station.tx_bitrate, Some(rate));
+        assert_eq!(station.connected_time, Some(60));
+        assert_eq!(station.rx_bitrate, None);
+    }
 }
@@ -0,0 +1,132 @@
+use crate::attr::{Attrs, Nl80211Attr, Nl80211SurveyInfo};
+
+use neli::attr::Attribute;
+use neli::err::DeError;
+
+/// Survey of a channel: noise floor and how much of the time the channel was busy
+///
+/// Times are in ms and accumulate since the driver started counting (usually since the
+/// interface went up), so utilization over an interval is the difference of two surveys.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Survey {
+    /// Center frequency of the channel in MHz
+    pub frequency: Option<u32>,
+    /// Noise level (dBm)
+    pub noise: Option<i8>,
+    /// Whether the interface currently operates on this channel
+    pub in_use: bool,
+    /// Time the radio was on the channel
+    pub channel_time: Option<u64>,
+    /// Time the primary channel was sensed busy
+    pub channel_busy_time: Option<u64>,
+    /// Time the extension (secondary) channel was sensed busy
+    pub channel_ext_busy_time: Option<u64>,
+    /// Time spent receiving data
+    pub channel_rx_time: Option<u64>,
+    /// Time spent transmitting data
+    pub channel_tx_time: Option<u64>,
+}
+
+impl Survey {
+    /// Fraction of [`channel_time`](Self::channel_time) the channel was busy, from 0 to 1
+    pub fn busy_fraction(&self) -> Option<f64> {
+        match (self.channel_busy_time, self.channel_time) {
+            (Some(busy), Some(time)) if time > 0 => Some(busy as f64 / time as f64),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<Attrs<'_, Nl80211Attr>> for Survey {
+    type Error = DeError;
+
+    fn try_from(attrs: Attrs<'_, Nl80211Attr>) -> Result<Self, Self::Error> {
+        let mut res = Self::default();
+        if let Some(info) = attrs.get_attribute(Nl80211Attr::AttrSurveyInfo) {
+            for attr in info.get_attr_handle::<Nl80211SurveyInfo>()?.iter() {
+                match attr.nla_type.nla_type {
+                    Nl80211SurveyInfo::SurveyInfoFrequency => {
+                        res.frequency = Some(attr.get_payload_as()?)
+                    }
+                    Nl80211SurveyInfo::SurveyInfoNoise => res.noise = Some(attr.get_payload_as()?),
+                    Nl80211SurveyInfo::SurveyInfoInUse => res.in_use = true,
+                    Nl80211SurveyInfo::SurveyInfoTime => {
+                        res.channel_time = Some(attr.get_payload_as()?)
+                    }
+                    Nl80211SurveyInfo::SurveyInfoTimeBusy => {
+                        res.channel_busy_time = Some(attr.get_payload_as()?)
+                    }
+                    Nl80211SurveyInfo::SurveyInfoTimeExtBusy => {
+                        res.channel_ext_busy_time = Some(attr.get_payload_as()?)
+                    }
+                    Nl80211SurveyInfo::SurveyInfoTimeRx => {
+                        res.channel_rx_time = Some(attr.get_payload_as()?)
+                    }
+                    Nl80211SurveyInfo::SurveyInfoTimeTx => {
+                        res.channel_tx_time = Some(attr.get_payload_as()?)
+                    }
+                    _ => (),
+                }
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod test_survey {
+    use super::*;
+    use crate::attr::Nl80211Attr::*;
+    use neli::attr::AttrHandle;
+    use neli::genl::{AttrType, Nlattr};
+    use neli::types::Buffer;
+
+    fn new_attr(t: Nl80211Attr, d: Vec<u8>) -> Nlattr<Nl80211Attr, Buffer> {
+        Nlattr {
+            nla_len: (4 + d.len()) as _,
+            nla_type: AttrType {
+                nla_nested: false,
+                nla_network_order: true,
+                nla_type: t,
+            },
+            nla_payload: d.into(),
+        }
+    }
+
+    #[test]
+    fn test_parser() {
+        // Channel 36 in use at -95 dBm noise, busy 250 of 1000 ms
+        let handler = vec![
+            new_attr(AttrIfindex, vec![3, 0, 0, 0]),
+            new_attr(
+                AttrSurveyInfo,
+                vec![
+                    8, 0, 1, 0, 60, 20, 0, 0, 5, 0, 2, 0, 161, 0, 0, 0, 4, 0, 3, 0, 12, 0, 4, 0,
+                    232, 3, 0, 0, 0, 0, 0, 0, 12, 0, 5, 0, 250, 0, 0, 0, 0, 0, 0, 0, 12, 0, 7, 0,
+                    200, 0, 0, 0, 0, 0, 0, 0, 12, 0, 8, 0, 30, 0, 0, 0, 0, 0, 0, 0,
+                ],
+            ),
+        ];
+
+        let survey: Survey = AttrHandle::new(handler.into_iter().collect())
+            .try_into()
+            .unwrap();
+
+        assert_eq!(
+            survey,
+            Survey {
+                frequency: Some(5180),
+                noise: Some(-95),
+                in_use: true,
+                channel_time: Some(1000),
+                channel_busy_time: Some(250),
+                channel_ext_busy_time: None,
+                channel_rx_time: Some(200),
+                channel_tx_time: Some(30),
+            }
+        );
+        assert_eq!(survey.busy_fraction(), Some(0.25));
+        assert_eq!(Survey::default().busy_fraction(), None);
+    }
+}
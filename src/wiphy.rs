@@ -0,0 +1,870 @@
+use crate::attr::{
+    Attrs, Nl80211Attr, Nl80211BandAttr, Nl80211BandIftypeAttr, Nl80211Bandc,
+    Nl80211ExtFeatureIndex, Nl80211FrequencyAttr,
+};
+use crate::channel::channel_of_freq;
+use crate::cmd::Nl80211Cmd;
+use crate::interface::InterfaceType;
+use crate::security::{AkmSuite, CipherSuite};
+
+use neli::attr::Attribute;
+use neli::consts::genl::Index;
+use neli::err::DeError;
+use neli::genl::Nlattr;
+use neli::types::Buffer;
+
+/// A struct representing a wireless physical device (phy) and its capabilities
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Wiphy {
+    /// Index of the wiphy, cf. [`Interface::phy`](crate::Interface::phy)
+    pub index: Option<u32>,
+    /// Name of the wiphy, like `phy0`
+    pub name: Option<String>,
+    /// Maximum number of SSIDs a single scan can probe for, see [`ScanRequest`](crate::ScanRequest)
+    pub max_scan_ssids: Option<u8>,
+    /// Frequency bands supported by the wiphy
+    pub bands: Vec<Band>,
+    /// Interface types the wiphy supports
+    pub supported_iftypes: Vec<InterfaceType>,
+    /// Interface types that are purely managed in software and can always be added,
+    /// regardless of hardware interface combination limits
+    pub software_iftypes: Vec<InterfaceType>,
+    /// Whether the wiphy schedules transmissions by airtime fairness, `None` if the kernel
+    /// doesn't report extended features
+    ///
+    /// With airtime fairness the airtime share of a station follows its
+    /// [`Station::airtime_weight`](crate::Station::airtime_weight), otherwise the weight has no
+    /// effect and station airtime metrics reflect plain per-station queueing.
+    pub airtime_fairness: Option<bool>,
+    /// Whether the wiphy can dwell on each channel of a scan for a given time, cf.
+    /// [`ScanRequest::duration_ms`](crate::ScanRequest::duration_ms); `None` if the kernel
+    /// doesn't report extended features
+    pub scan_dwell: Option<bool>,
+    /// Cipher suites the wiphy supports for encryption
+    pub cipher_suites: Vec<CipherSuite>,
+    /// AKM suites the wiphy supports, only reported by drivers which restrict them, cf.
+    /// [`Wiphy::supports_akm`]
+    pub akm_suites: Vec<AkmSuite>,
+    /// Maximum number of AKM suites a connect request may offer, `None` for older kernels,
+    /// which allow two
+    pub max_num_akm_suites: Option<u16>,
+    /// Commands the wiphy supports, cf. [`Wiphy::supports_channel_switch`]
+    pub supported_commands: Vec<Nl80211Cmd>,
+    /// Maximum number of channel switch counters in beacons and probe responses during a
+    /// channel switch announcement (CSA)
+    pub max_csa_counters: Option<u8>,
+    /// Vendor commands the wiphy supports, as (OUI, subcommand) pairs
+    pub vendor_commands: Vec<(u32, u32)>,
+    /// Vendor events the wiphy can send, as (OUI, subcommand) pairs
+    pub vendor_events: Vec<(u32, u32)>,
+}
+
+/// A frequency band supported by a wiphy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Band {
+    /// Which band this is
+    pub band: Nl80211Bandc,
+    /// The channels of this band
+    pub frequencies: Vec<Frequency>,
+    /// Maximum number of HT spatial streams the wiphy can receive in this band
+    pub ht_max_nss: Option<u8>,
+    /// Whether the wiphy supports HT 40 MHz channels in this band
+    pub ht_supports_40mhz: bool,
+    /// Whether the wiphy can receive HT frames with a short guard interval on 20 MHz channels
+    pub ht_supports_sgi20: bool,
+    /// Whether the wiphy can receive HT frames with a short guard interval on 40 MHz channels
+    pub ht_supports_sgi40: bool,
+    /// Maximum number of VHT spatial streams the wiphy can receive in this band
+    pub vht_max_nss: Option<u8>,
+    /// Highest VHT MCS index (7, 8 or 9) the wiphy can receive in this band
+    pub vht_max_mcs: Option<u8>,
+    /// Whether the wiphy supports 320 MHz EHT channels in this band, see
+    /// [`Band::centers_320mhz`]
+    pub supports_320mhz: bool,
+}
+
+/// A channel of a [`Band`]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Frequency {
+    /// Center frequency of the 20 MHz channel in MHz
+    pub frequency: Option<u32>,
+    /// Whether the channel is disabled in the current regulatory domain
+    pub disabled: bool,
+    /// Whether initiating radiation is forbidden on the channel (no-IR), so it can't be used
+    /// e.g. for an access point or to send probe requests
+    pub no_ir: bool,
+    /// Whether radar detection (DFS) is required on the channel
+    pub radar: bool,
+    /// Maximum transmission power (mBm)
+    pub max_tx_power: Option<u32>,
+    /// Whether a 40 MHz channel with the secondary channel above this one may be used
+    pub ht40_plus_allowed: bool,
+    /// Whether a 40 MHz channel with the secondary channel below this one may be used
+    pub ht40_minus_allowed: bool,
+    /// Duration of the channel availability check (CAC) before a DFS channel can be used, in
+    /// ms (typically 60 s, 600 s on weather radar channels)
+    pub dfs_cac_time_ms: Option<u32>,
+}
+
+impl Wiphy {
+    /// Merge a fragment of a split wiphy dump describing the same wiphy into `self`
+    pub(crate) fn merge(&mut self, other: Wiphy) {
+        self.index = self.index.or(other.index);
+        self.name = self.name.take().or(other.name);
+        self.max_scan_ssids = self.max_scan_ssids.or(other.max_scan_ssids);
+        for band in other.bands {
+            match self.bands.iter_mut().find(|b| b.band == band.band) {
+                Some(existing) => {
+                    existing.frequencies.extend(band.frequencies);
+                    existing.ht_max_nss = existing.ht_max_nss.or(band.ht_max_nss);
+                    existing.ht_supports_40mhz |= band.ht_supports_40mhz;
+                    existing.ht_supports_sgi20 |= band.ht_supports_sgi20;
+                    existing.ht_supports_sgi40 |= band.ht_supports_sgi40;
+                    existing.vht_max_nss = existing.vht_max_nss.or(band.vht_max_nss);
+                    existing.vht_max_mcs = existing.vht_max_mcs.or(band.vht_max_mcs);
+                    existing.supports_320mhz |= band.supports_320mhz;
+                }
+                None => self.bands.push(band),
+            }
+        }
+        self.supported_iftypes.extend(other.supported_iftypes);
+        self.software_iftypes.extend(other.software_iftypes);
+        self.airtime_fairness = self.airtime_fairness.or(other.airtime_fairness);
+        self.scan_dwell = self.scan_dwell.or(other.scan_dwell);
+        self.cipher_suites.extend(other.cipher_suites);
+        self.akm_suites.extend(other.akm_suites);
+        self.supported_commands.extend(other.supported_commands);
+        self.max_csa_counters = self.max_csa_counters.or(other.max_csa_counters);
+        self.max_num_akm_suites = self.max_num_akm_suites.or(other.max_num_akm_suites);
+        self.vendor_commands.extend(other.vendor_commands);
+        self.vendor_events.extend(other.vendor_events);
+    }
+
+    /// Whether interfaces of type `iftype` can be created on this wiphy
+    pub fn supports_iftype(&self, iftype: InterfaceType) -> bool {
+        self.supported_iftypes.contains(&iftype) || self.software_iftypes.contains(&iftype)
+    }
+
+    /// Whether connections with the AKM suite `akm` can be made on this wiphy
+    ///
+    /// Wiphys without a list of AKM suites leave it to the kernel to reject unsupported
+    /// suites, so every suite is assumed to be supported.
+    pub fn supports_akm(&self, akm: AkmSuite) -> bool {
+        self.akm_suites.is_empty() || self.akm_suites.contains(&akm)
+    }
+
+    /// Whether interfaces of this wiphy can announce a channel switch (CSA), e.g. as an AP
+    pub fn supports_channel_switch(&self) -> bool {
+        self.supported_commands
+            .contains(&Nl80211Cmd::CmdChannelSwitch)
+    }
+}
+
+/// Decode the maximum number of spatial streams from an HT MCS set
+///
+/// The set starts with a bitmap of the supported RX MCS indices 0-76, where each of the first
+/// four bytes holds the MCS indices of one to four spatial streams.
+fn ht_max_nss(mcs_set: &[u8]) -> Option<u8> {
+    let streams = &mcs_set[..mcs_set.len().min(4)];
+    streams
+        .iter()
+        .rposition(|&mcs| mcs != 0)
+        .map(|index| index as u8 + 1)
+}
+
+/// Decode the maximum number of spatial streams and the highest MCS index from a VHT MCS map
+///
+/// The map holds two bits per spatial stream 1-8, where 0 means MCS 0-7, 1 MCS 0-8, 2 MCS 0-9
+/// and 3 that the number of streams is not supported.
+fn vht_max_nss_mcs(mcs_map: u16) -> Option<(u8, u8)> {
+    (1..=8u8)
+        .map(|nss| (nss, (mcs_map >> (2 * (nss - 1))) & 0b11))
+        .filter(|&(_, mcs)| mcs != 3)
+        .fold(None, |max, (nss, mcs)| {
+            let mcs = 7 + mcs as u8;
+            let (_, max_mcs) = max.unwrap_or((0, 0));
+            Some((nss, max_mcs.max(mcs)))
+        })
+}
+
+/// Check an extended feature in the bitmap of `AttrExtFeatures`
+fn has_ext_feature(features: &[u8], feature: Nl80211ExtFeatureIndex) -> bool {
+    let index = usize::from(u16::from(feature));
+    features
+        .get(index / 8)
+        .is_some_and(|byte| byte & (1 << (index % 8)) != 0)
+}
+
+/// Decode a nested list of interface types, each given as a flag attribute of that type
+fn iftypes(attr: &Nlattr<Nl80211Attr, Buffer>) -> Result<Vec<InterfaceType>, DeError> {
+    Ok(attr
+        .get_attr_handle::<u16>()?
+        .iter()
+        .map(|iftype| u32::from(iftype.nla_type.nla_type).into())
+        .collect())
+}
+
+/// Decode a nested list of `struct nl80211_vendor_cmd_info`, the vendor OUI and subcommand
+fn vendor_cmds(attr: &Nlattr<Nl80211Attr, Buffer>) -> Result<Vec<(u32, u32)>, DeError> {
+    attr.get_attr_handle::<Index>()?
+        .iter()
+        .map(|info| match *info.nla_payload.as_ref() {
+            [v0, v1, v2, v3, s0, s1, s2, s3] => Ok((
+                u32::from_ne_bytes([v0, v1, v2, v3]),
+                u32::from_ne_bytes([s0, s1, s2, s3]),
+            )),
+            ref payload => Err(DeError::new(format!(
+                "Vendor command info must be 8 bytes, got {}",
+                payload.len()
+            ))),
+        })
+        .collect()
+}
+
+/// Center frequencies of the 320 MHz channels in the 6 GHz band (channels 31 to 191)
+const CENTERS_320MHZ: [u32; 6] = [6105, 6265, 6425, 6585, 6745, 6905];
+
+impl Band {
+    /// Center frequencies of the 320 MHz channels the wiphy can use in this band
+    ///
+    /// Empty unless the band [supports 320 MHz](Band::supports_320mhz). A center is only
+    /// listed if all 20 MHz channels it spans are channels of the band, so these are the
+    /// channels for which `ChannelWidth::Width320` can be offered when setting a channel.
+    pub fn centers_320mhz(&self) -> Vec<u32> {
+        if !self.supports_320mhz {
+            return Vec::new();
+        }
+        CENTERS_320MHZ
+            .into_iter()
+            .filter(|center| {
+                (0..16).all(|i| {
+                    let freq = center - 150 + i * 20;
+                    self.frequencies.iter().any(|f| f.frequency == Some(freq))
+                })
+            })
+            .collect()
+    }
+}
+
+impl Frequency {
+    /// The channel number of [`frequency`](Self::frequency)
+    pub fn channel_number(&self) -> Option<u32> {
+        self.frequency.and_then(channel_of_freq)
+    }
+
+    /// Whether the channel can be used at all, which excludes initiating radiation if it is
+    /// [`no_ir`](Self::no_ir)
+    pub fn is_usable(&self) -> bool {
+        self.frequency.is_some() && !self.disabled
+    }
+}
+
+impl TryFrom<Attrs<'_, Nl80211Attr>> for Wiphy {
+    type Error = DeError;
+
+    fn try_from(attrs: Attrs<'_, Nl80211Attr>) -> Result<Self, Self::Error> {
+        let mut res = Self::default();
+        for attr in attrs.iter() {
+            match attr.nla_type.nla_type {
+                Nl80211Attr::AttrWiphy => res.index = Some(attr.get_payload_as()?),
+                Nl80211Attr::AttrWiphyName => res.name = Some(attr.get_payload_as_with_len()?),
+                Nl80211Attr::AttrMaxNumScanSsids => {
+                    res.max_scan_ssids = Some(attr.get_payload_as()?)
+                }
+                Nl80211Attr::AttrWiphyBands => {
+                    for band in attr.get_attr_handle::<Nl80211Bandc>()?.iter() {
+                        res.bands.push(band.try_into()?);
+                    }
+                }
+                Nl80211Attr::AttrSupportedIftypes => res.supported_iftypes = iftypes(attr)?,
+                Nl80211Attr::AttrSoftwareIftypes => res.software_iftypes = iftypes(attr)?,
+                Nl80211Attr::AttrCipherSuites => {
+                    res.cipher_suites = attr
+                        .nla_payload
+                        .as_ref()
+                        .chunks_exact(4)
+                        .map(|selector| u32::from_ne_bytes(selector.try_into().unwrap()).into())
+                        .collect();
+                }
+                Nl80211Attr::AttrAkmSuites => {
+                    res.akm_suites = attr
+                        .nla_payload
+                        .as_ref()
+                        .chunks_exact(4)
+                        .map(|selector| u32::from_ne_bytes(selector.try_into().unwrap()).into())
+                        .collect();
+                }
+                Nl80211Attr::AttrMaxNumAkmSuites => {
+                    res.max_num_akm_suites = Some(attr.get_payload_as()?);
+                }
+                Nl80211Attr::AttrSupportedCommands => {
+                    for cmd in attr.get_attr_handle::<Index>()?.iter() {
+                        let cmd: u32 = cmd.get_payload_as()?;
+                        if let Ok(cmd) = u8::try_from(cmd) {
+                            res.supported_commands.push(cmd.into());
+                        }
+                    }
+                }
+                Nl80211Attr::AttrVendorData => res.vendor_commands = vendor_cmds(attr)?,
+                Nl80211Attr::AttrVendorEvents => res.vendor_events = vendor_cmds(attr)?,
+                Nl80211Attr::AttrMaxCsaCounters => {
+                    res.max_csa_counters = Some(attr.get_payload_as()?);
+                }
+                Nl80211Attr::AttrExtFeatures => {
+                    let features = attr.nla_payload.as_ref();
+                    res.airtime_fairness = Some(has_ext_feature(
+                        features,
+                        Nl80211ExtFeatureIndex::ExtFeatureAirtimeFairness,
+                    ));
+                    res.scan_dwell = Some(has_ext_feature(
+                        features,
+                        Nl80211ExtFeatureIndex::ExtFeatureSetScanDwell,
+                    ));
+                }
+                _ => (),
+            }
+        }
+        Ok(res)
+    }
+}
+
+impl TryFrom<&Nlattr<Nl80211Bandc, Buffer>> for Band {
+    type Error = DeError;
+
+    fn try_from(attr: &Nlattr<Nl80211Bandc, Buffer>) -> Result<Self, Self::Error> {
+        let mut res = Self {
+            band: attr.nla_type.nla_type,
+            frequencies: Vec::new(),
+            ht_max_nss: None,
+            ht_supports_40mhz: false,
+            ht_supports_sgi20: false,
+            ht_supports_sgi40: false,
+            vht_max_nss: None,
+            vht_max_mcs: None,
+            supports_320mhz: false,
+        };
+        for attr in attr.get_attr_handle::<Nl80211BandAttr>()?.iter() {
+            match attr.nla_type.nla_type {
+                Nl80211BandAttr::BandAttrFreqs => {
+                    for freq in attr.get_attr_handle::<Index>()?.iter() {
+                        res.frequencies.push(freq.try_into()?);
+                    }
+                }
+                Nl80211BandAttr::BandAttrHtMcsSet => {
+                    res.ht_max_nss = ht_max_nss(attr.nla_payload.as_ref())
+                }
+                Nl80211BandAttr::BandAttrHtCapa => {
+                    // HT capability info of the HT capabilities element
+                    let capa: u16 = attr.get_payload_as()?;
+                    res.ht_supports_40mhz = capa & 0x0002 != 0;
+                    res.ht_supports_sgi20 = capa & 0x0020 != 0;
+                    res.ht_supports_sgi40 = capa & 0x0040 != 0;
+                }
+                Nl80211BandAttr::BandAttrVhtMcsSet => {
+                    // RX MCS map, RX highest rate, TX MCS map, TX highest rate
+                    let mcs_set = attr.nla_payload.as_ref();
+                    if mcs_set.len() >= 2 {
+                        let rx_mcs_map = u16::from_le_bytes([mcs_set[0], mcs_set[1]]);
+                        if let Some((nss, mcs)) = vht_max_nss_mcs(rx_mcs_map) {
+                            res.vht_max_nss = Some(nss);
+                            res.vht_max_mcs = Some(mcs);
+                        }
+                    }
+                }
+                Nl80211BandAttr::BandAttrIftypeData if res.band == Nl80211Bandc::Band6ghz => {
+                    for iftype_data in attr.get_attr_handle::<Index>()?.iter() {
+                        let iftype_data = iftype_data.get_attr_handle::<Nl80211BandIftypeAttr>()?;
+                        if let Some(phy) = iftype_data
+                            .get_attribute(Nl80211BandIftypeAttr::BandIftypeAttrEhtCapPhy)
+                        {
+                            // Support for 320 MHz in 6 GHz is bit 1 of the EHT PHY capabilities
+                            let phy = phy.nla_payload.as_ref();
+                            res.supports_320mhz |= phy.first().is_some_and(|b| b & 0x02 != 0);
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(res)
+    }
+}
+
+impl TryFrom<&Nlattr<Index, Buffer>> for Frequency {
+    type Error = DeError;
+
+    fn try_from(attr: &Nlattr<Index, Buffer>) -> Result<Self, Self::Error> {
+        let mut res = Self {
+            ht40_plus_allowed: true,
+            ht40_minus_allowed: true,
+            ..Default::default()
+        };
+        for attr in attr.get_attr_handle::<Nl80211FrequencyAttr>()?.iter() {
+            match attr.nla_type.nla_type {
+                Nl80211FrequencyAttr::FrequencyAttrFreq => {
+                    res.frequency = Some(attr.get_payload_as()?);
+                }
+                Nl80211FrequencyAttr::FrequencyAttrDisabled => res.disabled = true,
+                Nl80211FrequencyAttr::FrequencyAttrNoIr => res.no_ir = true,
+                Nl80211FrequencyAttr::FrequencyAttrRadar => res.radar = true,
+                Nl80211FrequencyAttr::FrequencyAttrMaxTxPower => {
+                    res.max_tx_power = Some(attr.get_payload_as()?);
+                }
+                Nl80211FrequencyAttr::FrequencyAttrNoHt40Plus => res.ht40_plus_allowed = false,
+                Nl80211FrequencyAttr::FrequencyAttrNoHt40Minus => res.ht40_minus_allowed = false,
+                Nl80211FrequencyAttr::FrequencyAttrDfsCacTime => {
+                    res.dfs_cac_time_ms = Some(attr.get_payload_as()?);
+                }
+                _ => (),
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod test_wiphy {
+    use super::*;
+    use crate::attr::Nl80211Attr::*;
+    use neli::attr::AttrHandle;
+    use neli::genl::AttrType;
+
+    fn new_attr(t: Nl80211Attr, d: Vec<u8>) -> Nlattr<Nl80211Attr, Buffer> {
+        Nlattr {
+            nla_len: (4 + d.len()) as _,
+            nla_type: AttrType {
+                nla_nested: false,
+                nla_network_order: true,
+                nla_type: t,
+            },
+            nla_payload: d.into(),
+        }
+    }
+
+    #[test]
+    fn test_ht40() {
+        // 2.4 GHz band with channel 1 (HT40- forbidden) and channel 13 (HT40+ forbidden)
+        let handler = vec![
+            new_attr(AttrWiphy, vec![0, 0, 0, 0]),
+            new_attr(
+                AttrWiphyBands,
+                vec![
+                    40, 0, 0, 0, 36, 0, 1, 0, 16, 0, 0, 0, 8, 0, 1, 0, 108, 9, 0, 0, 4, 0, 9, 0,
+                    16, 0, 1, 0, 8, 0, 1, 0, 168, 9, 0, 0, 4, 0, 10, 0,
+                ],
+            ),
+        ];
+
+        let wiphy: Wiphy = AttrHandle::new(handler.into_iter().collect())
+            .try_into()
+            .unwrap();
+        let expected_wiphy = Wiphy {
+            index: Some(0),
+            bands: vec![Band {
+                band: Nl80211Bandc::Band2ghz,
+                frequencies: vec![
+                    Frequency {
+                        frequency: Some(2412),
+                        ht40_plus_allowed: true,
+                        ht40_minus_allowed: false,
+                        ..Default::default()
+                    },
+                    Frequency {
+                        frequency: Some(2472),
+                        ht40_plus_allowed: false,
+                        ht40_minus_allowed: true,
+                        ..Default::default()
+                    },
+                ],
+                ht_max_nss: None,
+                ht_supports_40mhz: false,
+                ht_supports_sgi20: false,
+                ht_supports_sgi40: false,
+                vht_max_nss: None,
+                vht_max_mcs: None,
+                supports_320mhz: false,
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(wiphy, expected_wiphy);
+    }
+
+    #[test]
+    fn test_iftypes() {
+        // Station, AP and monitor supported, monitor and AP/VLAN added in software
+        let handler = vec![
+            new_attr(AttrWiphy, vec![1, 0, 0, 0]),
+            new_attr(
+                AttrSupportedIftypes,
+                vec![4, 0, 2, 0, 4, 0, 3, 0, 4, 0, 6, 0],
+            ),
+            new_attr(AttrSoftwareIftypes, vec![4, 0, 4, 0, 4, 0, 6, 0]),
+        ];
+
+        let wiphy: Wiphy = AttrHandle::new(handler.into_iter().collect())
+            .try_into()
+            .unwrap();
+
+        assert_eq!(
+            wiphy.supported_iftypes,
+            vec![
+                InterfaceType::Station,
+                InterfaceType::Ap,
+                InterfaceType::Monitor
+            ]
+        );
+        assert_eq!(
+            wiphy.software_iftypes,
+            vec![InterfaceType::ApVlan, InterfaceType::Monitor]
+        );
+        assert!(wiphy.supports_iftype(InterfaceType::ApVlan));
+        assert!(!wiphy.supports_iftype(InterfaceType::MeshPoint));
+    }
+
+    #[test]
+    fn test_ht_capa() {
+        assert_eq!(
+            ht_max_nss(&[0xff, 0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+            Some(2)
+        );
+        assert_eq!(ht_max_nss(&[0xff, 0, 0, 0, 1, 0, 0, 0, 0, 0]), Some(1));
+        assert_eq!(ht_max_nss(&[0; 16]), None);
+
+        // 2.4 GHz band of a 2x2 wiphy with HT capabilities 0x01ef: 20/40 MHz and short GI
+        let handler = vec![new_attr(
+            AttrWiphyBands,
+            vec![
+                32, 0, 0, 0, 20, 0, 3, 0, 255, 255, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 6, 0,
+                4, 0, 239, 1, 0, 0,
+            ],
+        )];
+
+        let wiphy: Wiphy = AttrHandle::new(handler.into_iter().collect())
+            .try_into()
+            .unwrap();
+        let band = &wiphy.bands[0];
+        assert_eq!(band.band, Nl80211Bandc::Band2ghz);
+        assert_eq!(band.ht_max_nss, Some(2));
+        assert!(band.ht_supports_40mhz);
+        assert!(band.ht_supports_sgi20);
+        assert!(band.ht_supports_sgi40);
+    }
+
+    #[test]
+    fn test_vht_mcs() {
+        // 4x4 with MCS 0-9 on the first two streams and MCS 0-8 on the others
+        assert_eq!(vht_max_nss_mcs(0xff5a), Some((4, 9)));
+        // 2x2 MCS 0-7
+        assert_eq!(vht_max_nss_mcs(0xfff0), Some((2, 7)));
+        assert_eq!(vht_max_nss_mcs(0xffff), None);
+
+        // 5 GHz band with a VHT MCS set advertising 2x2 MCS 0-9
+        let handler = vec![new_attr(
+            AttrWiphyBands,
+            vec![
+                20, 0, 1, 0, 12, 0, 7, 0, 250, 255, 0, 0, 250, 255, 0, 0, 4, 0, 1, 0,
+            ],
+        )];
+
+        let wiphy: Wiphy = AttrHandle::new(handler.into_iter().collect())
+            .try_into()
+            .unwrap();
+        assert_eq!(wiphy.bands[0].band, Nl80211Bandc::Band5ghz);
+        assert_eq!(wiphy.bands[0].vht_max_nss, Some(2));
+        assert_eq!(wiphy.bands[0].vht_max_mcs, Some(9));
+    }
+
+    #[test]
+    fn test_airtime_fairness() {
+        // Feature 33 is bit 1 of the fifth byte
+        let wiphy = |features: Vec<u8>| -> Wiphy {
+            AttrHandle::new(
+                vec![new_attr(AttrExtFeatures, features)]
+                    .into_iter()
+                    .collect(),
+            )
+            .try_into()
+            .unwrap()
+        };
+
+        assert_eq!(wiphy(vec![0, 0, 0, 0, 2, 0]).airtime_fairness, Some(true));
+        assert_eq!(wiphy(vec![0, 0, 0, 0, 2, 0]).scan_dwell, Some(false));
+        // Feature 5 is bit 5 of the first byte
+        assert_eq!(wiphy(vec![32]).scan_dwell, Some(true));
+        assert_eq!(
+            wiphy(vec![255, 255, 255, 255, 253, 255]).airtime_fairness,
+            Some(false)
+        );
+        // Older kernels send a shorter bitmap
+        assert_eq!(wiphy(vec![255, 255]).airtime_fairness, Some(false));
+        assert_eq!(Wiphy::default().airtime_fairness, None);
+    }
+
+    #[test]
+    fn test_channel_switch() {
+        // Supports CmdTriggerScan and CmdChannelSwitch with up to two CSA counters
+        let handler = vec![
+            new_attr(AttrWiphy, vec![0, 0, 0, 0]),
+            new_attr(
+                AttrSupportedCommands,
+                vec![8, 0, 0, 0, 33, 0, 0, 0, 8, 0, 1, 0, 102, 0, 0, 0],
+            ),
+            new_attr(AttrMaxCsaCounters, vec![2]),
+        ];
+
+        let wiphy: Wiphy = AttrHandle::new(handler.into_iter().collect())
+            .try_into()
+            .unwrap();
+
+        assert_eq!(
+            wiphy.supported_commands,
+            vec![Nl80211Cmd::CmdTriggerScan, Nl80211Cmd::CmdChannelSwitch]
+        );
+        assert_eq!(wiphy.max_csa_counters, Some(2));
+        assert!(wiphy.supports_channel_switch());
+        assert!(!Wiphy::default().supports_channel_switch());
+    }
+
+    #[test]
+    fn test_cipher_suites() {
+        // WEP-40, TKIP, CCMP, GCMP-256 and the vendor specific SMS4 cipher
+        let handler = vec![new_attr(
+            AttrCipherSuites,
+            vec![
+                1, 172, 15, 0, 2, 172, 15, 0, 4, 172, 15, 0, 9, 172, 15, 0, 1, 114, 20, 0,
+            ],
+        )];
+
+        let wiphy: Wiphy = AttrHandle::new(handler.into_iter().collect())
+            .try_into()
+            .unwrap();
+        assert_eq!(
+            wiphy.cipher_suites,
+            vec![
+                CipherSuite::Wep40,
+                CipherSuite::Tkip,
+                CipherSuite::Ccmp,
+                CipherSuite::Gcmp256,
+                CipherSuite::Unknown(0x00147201),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_akm_suites() {
+        // PSK, SAE and the WPA (version 1) PSK
+        let handler = vec![
+            new_attr(
+                AttrAkmSuites,
+                vec![2, 172, 15, 0, 8, 172, 15, 0, 2, 242, 80, 0],
+            ),
+            new_attr(AttrMaxNumAkmSuites, vec![10, 0]),
+        ];
+
+        let wiphy: Wiphy = AttrHandle::new(handler.into_iter().collect())
+            .try_into()
+            .unwrap();
+        assert_eq!(
+            wiphy.akm_suites,
+            vec![AkmSuite::Psk, AkmSuite::Sae, AkmSuite::Unknown(0x0050f202)]
+        );
+        assert!(wiphy.supports_akm(AkmSuite::Sae));
+        assert!(!wiphy.supports_akm(AkmSuite::Owe));
+        assert!(Wiphy::default().supports_akm(AkmSuite::Owe));
+        assert_eq!(wiphy.max_num_akm_suites, Some(10));
+        assert_eq!(Wiphy::default().max_num_akm_suites, None);
+    }
+
+    #[test]
+    fn test_vendor_commands() {
+        // Two commands of the Broadcom OUI (0x001018) and one event of Qualcomm (0x001374)
+        let handler = vec![
+            new_attr(AttrWiphy, vec![0, 0, 0, 0]),
+            new_attr(
+                AttrVendorData,
+                vec![
+                    12, 0, 1, 0, 24, 16, 0, 0, 1, 0, 0, 0, 12, 0, 2, 0, 24, 16, 0, 0, 2, 0, 0, 0,
+                ],
+            ),
+            new_attr(
+                AttrVendorEvents,
+                vec![12, 0, 1, 0, 116, 19, 0, 0, 20, 0, 0, 0],
+            ),
+        ];
+
+        let wiphy: Wiphy = AttrHandle::new(handler.into_iter().collect())
+            .try_into()
+            .unwrap();
+
+        assert_eq!(wiphy.vendor_commands, vec![(0x001018, 1), (0x001018, 2)]);
+        assert_eq!(wiphy.vendor_events, vec![(0x001374, 20)]);
+
+        let truncated = vec![new_attr(AttrVendorData, vec![8, 0, 1, 0, 24, 16, 0, 0])];
+        let wiphy: Result<Wiphy, _> = AttrHandle::new(truncated.into_iter().collect()).try_into();
+        assert!(wiphy.is_err());
+    }
+
+    #[test]
+    fn test_eht_320mhz() {
+        // 6 GHz band with two channels and an EHT capable station entry in the iftype data
+        let handler = vec![new_attr(
+            AttrWiphyBands,
+            vec![
+                64, 0, 3, 0, 28, 0, 1, 0, 12, 0, 0, 0, 8, 0, 1, 0, 67, 23, 0, 0, 12, 0, 1, 0, 8, 0,
+                1, 0, 87, 23, 0, 0, 32, 0, 9, 0, 28, 0, 1, 0, 8, 0, 1, 0, 4, 0, 2, 0, 13, 0, 9, 0,
+                2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            ],
+        )];
+
+        let wiphy: Wiphy = AttrHandle::new(handler.into_iter().collect())
+            .try_into()
+            .unwrap();
+        let band = &wiphy.bands[0];
+        assert_eq!(band.band, Nl80211Bandc::Band6ghz);
+        assert_eq!(band.frequencies.len(), 2);
+        assert!(band.supports_320mhz);
+        // Two channels don't span any 320 MHz channel
+        assert!(band.centers_320mhz().is_empty());
+    }
+
+    #[test]
+    fn test_centers_320mhz() {
+        // Channels 1 to 61 (5955 - 6255 MHz), covering the 320 MHz channel 31 only
+        let mut band = Band {
+            band: Nl80211Bandc::Band6ghz,
+            frequencies: (0..16)
+                .map(|i| Frequency {
+                    frequency: Some(5955 + i * 20),
+                    ..Default::default()
+                })
+                .collect(),
+            ht_max_nss: None,
+            ht_supports_40mhz: false,
+            ht_supports_sgi20: false,
+            ht_supports_sgi40: false,
+            vht_max_nss: None,
+            vht_max_mcs: None,
+            supports_320mhz: true,
+        };
+
+        assert_eq!(band.centers_320mhz(), vec![6105]);
+        band.supports_320mhz = false;
+        assert!(band.centers_320mhz().is_empty());
+    }
+
+    #[test]
+    fn test_channel_flags() {
+        // 5 GHz channels 36 (23 dBm), 52 (20 dBm, no-IR and radar) and 165 (disabled)
+        let handler = vec![new_attr(
+            AttrWiphyBands,
+            vec![
+                72, 0, 1, 0, 68, 0, 1, 0, 20, 0, 0, 0, 8, 0, 1, 0, 60, 20, 0, 0, 8, 0, 6, 0, 252,
+                8, 0, 0, 28, 0, 1, 0, 8, 0, 1, 0, 140, 20, 0, 0, 4, 0, 3, 0, 4, 0, 5, 0, 8, 0, 6,
+                0, 208, 7, 0, 0, 16, 0, 2, 0, 8, 0, 1, 0, 193, 22, 0, 0, 4, 0, 2, 0,
+            ],
+        )];
+
+        let wiphy: Wiphy = AttrHandle::new(handler.into_iter().collect())
+            .try_into()
+            .unwrap();
+        let frequencies = &wiphy.bands[0].frequencies;
+        assert_eq!(
+            frequencies
+                .iter()
+                .map(Frequency::channel_number)
+                .collect::<Vec<_>>(),
+            vec![Some(36), Some(52), Some(165)]
+        );
+
+        assert_eq!(frequencies[0].max_tx_power, Some(2300));
+        assert!(!frequencies[0].no_ir && !frequencies[0].radar);
+        assert!(frequencies[0].is_usable());
+
+        assert_eq!(frequencies[1].max_tx_power, Some(2000));
+        assert!(frequencies[1].no_ir && frequencies[1].radar);
+        assert!(frequencies[1].is_usable());
+
+        assert!(frequencies[2].disabled);
+        assert!(!frequencies[2].is_usable());
+    }
+
+    #[test]
+    fn test_dfs_cac_time() {
+        // Weather radar channel 120 of the 5 GHz band
+        let handler = vec![new_attr(
+            AttrWiphyBands,
+            vec![
+                32, 0, 1, 0, 28, 0, 1, 0, 24, 0, 0, 0, 8, 0, 1, 0, 224, 21, 0, 0, 4, 0, 5, 0, 8, 0,
+                13, 0, 192, 39, 9, 0,
+            ],
+        )];
+
+        let wiphy: Wiphy = AttrHandle::new(handler.into_iter().collect())
+            .try_into()
+            .unwrap();
+        let frequency = &wiphy.bands[0].frequencies[0];
+        assert_eq!(frequency.frequency, Some(5600));
+        assert_eq!(frequency.dfs_cac_time_ms, Some(600000));
+    }
+
+    #[test]
+    fn test_merge() {
+        let frequency = |frequency| Frequency {
+            frequency: Some(frequency),
+            ..Default::default()
+        };
+        let band = |band, frequencies| Band {
+            band,
+            frequencies,
+            ht_max_nss: None,
+            ht_supports_40mhz: false,
+            ht_supports_sgi20: false,
+            ht_supports_sgi40: false,
+            vht_max_nss: None,
+            vht_max_mcs: None,
+            supports_320mhz: false,
+        };
+
+        // The first message of a split dump carries the name and scan limits
+        let mut wiphy: Wiphy = AttrHandle::new(
+            vec![
+                new_attr(AttrWiphy, vec![1, 0, 0, 0]),
+                new_attr(AttrWiphyName, b"phy1\0".to_vec()),
+                new_attr(AttrMaxNumScanSsids, vec![20]),
+            ]
+            .into_iter()
+            .collect(),
+        )
+        .try_into()
+        .unwrap();
+        wiphy.merge(Wiphy {
+            index: Some(1),
+            bands: vec![band(Nl80211Bandc::Band2ghz, vec![frequency(2412)])],
+            ..Default::default()
+        });
+        wiphy.merge(Wiphy {
+            index: Some(1),
+            bands: vec![band(Nl80211Bandc::Band2ghz, vec![frequency(2417)])],
+            ..Default::default()
+        });
+        wiphy.merge(Wiphy {
+            index: Some(1),
+            bands: vec![band(Nl80211Bandc::Band5ghz, vec![frequency(5180)])],
+            ..Default::default()
+        });
+
+        assert_eq!(
+            wiphy.bands,
+            vec![
+                band(
+                    Nl80211Bandc::Band2ghz,
+                    vec![frequency(2412), frequency(2417)]
+                ),
+                band(Nl80211Bandc::Band5ghz, vec![frequency(5180)]),
+            ]
+        );
+        assert_eq!(wiphy.name.as_deref(), Some("phy1"));
+        assert_eq!(wiphy.max_scan_ssids, Some(20));
+    }
+}